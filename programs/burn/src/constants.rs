@@ -1,14 +1,29 @@
 use anchor_lang::{prelude::Pubkey, pubkey};
 
 pub const DECIMALS: u8 = 6;
+/// Decimals of the native token (SOL), in which the curve is denominated.
+pub const NATIVE_DECIMALS: u8 = 9;
+pub const MAX_QUOTE_DECIMALS: u8 = 18;
 pub const MAX_TOKEN_SUPPLY: u64 = (10e8 * 1e6) as u64;
 pub const FREE_TRANSFER_THRESHOLD: u64 = MAX_TOKEN_SUPPLY * 1 / 100;
 pub const CONFIG: &str = "config";
 pub const MARKET_SEED: &str = "market";
 pub const MARKET_VAULT_SEED: &str = "market_vault";
+/// The current layout version of the market.
+pub const MARKET_VERSION: u8 = 1;
+/// Bytes reserved at the end of the market for future fields.
+pub const MARKET_RESERVED_SPACE: usize = 64;
 pub const SYMBOL_MIN_LEN: usize = 2;
 pub const SYMBOL_MAX_LEN: usize = 10;
 pub const SYMBOL_BURN: &str = "BURN";
 pub const BURN_ACCOUNT_SEED: &str = "burn_account";
+pub const BURN_LEADERBOARD_SEED: &str = "burn_leaderboard";
+pub const BUY_BURN_SCHEDULE_SEED: &str = "buy_burn_schedule";
+/// Number of entries kept in the burn leaderboard of a market.
+pub const BURN_LEADERBOARD_SIZE: usize = 10;
+pub const WSOL_ACCOUNT_SEED: &str = "wsol_account";
 pub const HOOKS_PROGRAM_ID: Pubkey = pubkey!("burnhzSCeNMFuTsQJRC8dc1EPffWAecnYk8CxxRuQzT");
+/// The instruction discriminator of `initialize_account_meta_list` of the hooks program,
+/// checked against the hooks program at compile time.
+pub const INITIALIZE_ACCOUNT_META_LIST_DISCRIMINATOR: [u8; 8] = [116, 168, 178, 88, 207, 22, 191, 212];
 pub const BLACK_HOLE: Pubkey = pubkey!("1nc1nerator11111111111111111111111111111111");
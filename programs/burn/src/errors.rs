@@ -48,4 +48,64 @@ pub enum Error {
     BurnProgramIsMissing,
     #[msg("Burn program is incorrect")]
     BurnProgramIsIncorrect,
+    #[msg("Token program account mismatch")]
+    TokenProgramAccountMismatch,
+    #[msg("Market is already migrated")]
+    MarketAlreadyMigrated,
+    #[msg("Market account mismatch")]
+    MarketAccountMismatch,
+    #[msg("Quote mint account mismatch")]
+    QuoteMintAccountMismatch,
+    #[msg("Quote mint is missing")]
+    QuoteMintIsMissing,
+    #[msg("Quote token program is missing")]
+    QuoteTokenProgramIsMissing,
+    #[msg("Quote token payer is missing")]
+    QuoteTokenPayerIsMissing,
+    #[msg("Fee recipient quote account is missing")]
+    FeeRecipientQuoteAccountIsMissing,
+    #[msg("Invalid quote decimals")]
+    InvalidQuoteDecimals,
+    #[msg("Quote mint with a transfer fee or a transfer hook is not supported")]
+    UnsupportedQuoteMint,
+    #[msg("Not config authority or market creator")]
+    NotMetadataAuthority,
+    #[msg("Additional metadata is not supported")]
+    AdditionalMetadataNotSupported,
+    #[msg("Metadata account is missing")]
+    MetadataAccountIsMissing,
+    #[msg("Metadata account mismatch")]
+    MetadataAccountMismatch,
+    #[msg("Token metadata program is missing")]
+    TokenMetadataProgramIsMissing,
+    #[msg("Fee recipient is missing")]
+    FeeRecipientIsMissing,
+    #[msg("Token recipient is missing")]
+    TokenRecipientIsMissing,
+    #[msg("Buy-burn is not enabled on the market")]
+    BuyBurnNotEnabled,
+    #[msg("Burn account is already migrated")]
+    BurnAccountAlreadyMigrated,
+    #[msg("Voucher expired")]
+    VoucherExpired,
+    #[msg("Voucher is not signed by the buy-burn authority")]
+    InvalidVoucherSignature,
+    #[msg("Burn accounts do not match the entries")]
+    BurnAccountsMismatch,
+    #[msg("Buy-burn is called too early by the schedule")]
+    BuyBurnTooEarly,
+    #[msg("Buy-burn schedule account mismatch")]
+    BuyBurnScheduleMismatch,
+    #[msg("Buy-burn authority is missing")]
+    BuyBurnAuthorityIsMissing,
+    #[msg("Average price exceeds the limit price")]
+    PriceLimitExceeded,
+    #[msg("Market is unhealthy")]
+    MarketUnhealthy,
+    #[msg("Deadline exceeded")]
+    DeadlineExceeded,
+    #[msg("Cannot swap on the same market")]
+    SameMarket,
+    #[msg("Close is neither co-signed by the authority nor vouched")]
+    MissingCloseAuthorization,
 }
@@ -2,8 +2,8 @@ use crate::constants::*;
 use crate::errors::Error;
 use crate::program::Burn;
 use crate::state::*;
+use crate::trade_guard::TradeGuard;
 use anchor_lang::prelude::*;
-use anchor_spl::token_2022::spl_token_2022;
 use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
 
 #[derive(Accounts)]
@@ -18,14 +18,16 @@ pub struct BuyBurn<'info> {
     #[account(mut,
         seeds = [
             MARKET_SEED.as_bytes(),
-            SYMBOL_BURN.as_bytes(),
+            market.symbol.as_bytes(),
             config.key().as_ref()
         ],
         bump,
+        constraint = market.buy_burn_enabled @ Error::BuyBurnNotEnabled,
         has_one = config @ Error::ConfigAccountMismatch,
         has_one = native_vault @ Error::NativeVaultAccountMismatch,
         has_one = token_mint @ Error::TokenMintAccountMismatch,
         has_one = token_vault @ Error::TokenVaultAccountMismatch,
+        has_one = token_program @ Error::TokenProgramAccountMismatch,
     )]
     pub market: Box<Account<'info, Market>>,
     /// CHECK: Already checked in the market.
@@ -40,7 +42,7 @@ pub struct BuyBurn<'info> {
         seeds = [
             BURN_ACCOUNT_SEED.as_bytes(),
             payer.key().as_ref(),
-            config.key().as_ref()
+            market.burn_account_scope(&market.key()).as_ref()
         ], bump)]
     pub burn_account: Account<'info, BurnAccount>,
     #[account(mut,
@@ -51,16 +53,30 @@ pub struct BuyBurn<'info> {
     #[account(mut)]
     pub payer: Signer<'info>,
     pub buy_burn_authority: Signer<'info>,
-    #[account(address = spl_token_2022::ID)]
     pub token_program: Interface<'info, TokenInterface>,
     pub system_program: Program<'info, System>,
 
-    /// CHECK: ExtraAccountMetaList Account, MUST use these exact seeds
-    pub extra_account_meta_list: AccountInfo<'info>,
+    /// CHECK: ExtraAccountMetaList Account, MUST use these exact seeds, required if the transfer hook is enabled.
+    pub extra_account_meta_list: Option<AccountInfo<'info>>,
     #[account(address = HOOKS_PROGRAM_ID)]
-    /// CHECK:
-    pub hooks_program: AccountInfo<'info>,
+    /// CHECK: Required if the transfer hook is enabled.
+    pub hooks_program: Option<AccountInfo<'info>>,
     pub burn_program: Program<'info, Burn>,
+
+    /// The quote token mint, required if the market is quoted in a token.
+    #[account(address = market.quote_mint @ Error::QuoteMintAccountMismatch)]
+    pub quote_mint: Option<Box<InterfaceAccount<'info, Mint>>>,
+    /// The payer's quote token account, required if the market is quoted in a token.
+    #[account(mut, token::mint = market.quote_mint, token::authority = payer)]
+    pub quote_token_payer: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    /// The fee recipient's quote token account, required if the market is quoted in a token.
+    #[account(mut, token::mint = market.quote_mint, token::authority = fee_recipient)]
+    pub fee_recipient_quote_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    pub quote_token_program: Option<Interface<'info, TokenInterface>>,
+
+    /// The burn leaderboard of the market, updated if provided (best-effort, see [BurnLeaderboard]).
+    #[account(mut, seeds = [BURN_LEADERBOARD_SEED.as_bytes(), market.key().as_ref()], bump)]
+    pub leaderboard: Option<Box<Account<'info, BurnLeaderboard>>>,
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize)]
@@ -68,6 +84,8 @@ pub struct BuyBurnArgs {
     pub next_nonce: u32,
     pub next_buy_amount: u64,
     pub max_pay: u64,
+    /// Optional deadline and limit price of the trade.
+    pub guard: TradeGuard,
 }
 
 pub fn handler<'a, 'b, 'c, 'info>(ctx: Context<'a, 'b, 'c, 'info, BuyBurn<'info>>, args: BuyBurnArgs) -> Result<()> {
@@ -76,10 +94,27 @@ pub fn handler<'a, 'b, 'c, 'info>(ctx: Context<'a, 'b, 'c, 'info, BuyBurn<'info>
         .burn_account
         .update_buy_amount(args.next_nonce, args.next_buy_amount)?;
 
+    let hook = TransferHookAccounts::from_accounts(
+        &accounts.market,
+        accounts.extra_account_meta_list.as_ref(),
+        accounts.hooks_program.as_ref(),
+        &accounts.burn_program,
+    )?;
+    let quote = QuoteAccounts::load(
+        &accounts.market,
+        accounts.quote_mint.as_deref(),
+        accounts.quote_token_payer.as_deref(),
+        accounts.fee_recipient_quote_account.as_deref(),
+        accounts.quote_token_program.as_ref(),
+    )?;
+
+    let (pay_amount, fee) = accounts.market.compute_buy_token_pay(buy_amount)?;
+
     let market_account_info = accounts.market.to_account_info().clone();
     accounts.market.buy_token(crate::state::BuyTokenArgs {
         buy_amount,
         max_pay: args.max_pay,
+        guard: args.guard,
         config: &accounts.config,
         market: market_account_info,
         native_vault: &accounts.native_vault,
@@ -91,8 +126,21 @@ pub fn handler<'a, 'b, 'c, 'info>(ctx: Context<'a, 'b, 'c, 'info, BuyBurn<'info>
         token_program: &accounts.token_program,
         system_program: &accounts.system_program,
 
-        extra_account_meta_list: Some(&accounts.extra_account_meta_list),
-        hooks_program: Some(&accounts.hooks_program),
-        burn_program: Some(accounts.burn_program.to_account_info()),
-    })
+        extra_account_meta_list: hook.extra_account_meta_list,
+        hooks_program: hook.hooks_program,
+        burn_program: hook.burn_program,
+
+        quote,
+    })?;
+
+    // only the tokens sent to the black hole count as burned
+    if accounts.token_recipient.owner == BLACK_HOLE {
+        accounts
+            .burn_account
+            .record_burn(buy_amount, pay_amount + fee, Clock::get()?.unix_timestamp);
+        if let Some(leaderboard) = &mut accounts.leaderboard {
+            leaderboard.update(accounts.payer.key(), accounts.burn_account.total_burned);
+        }
+    }
+    Ok(())
 }
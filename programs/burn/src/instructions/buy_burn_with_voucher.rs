@@ -0,0 +1,179 @@
+use crate::constants::*;
+use crate::errors::Error;
+use crate::program::Burn;
+use crate::state::*;
+use crate::trade_guard::TradeGuard;
+use crate::voucher::{self, BuyBurnVoucher};
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::sysvar;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+#[derive(Accounts)]
+pub struct BuyBurnWithVoucher<'info> {
+    #[account(
+        has_one = fee_recipient @ Error::FeeRecipientMismatch,
+        seeds = [CONFIG.as_bytes()],
+        bump
+    )]
+    pub config: Box<Account<'info, Config>>,
+    #[account(mut,
+        seeds = [
+            MARKET_SEED.as_bytes(),
+            market.symbol.as_bytes(),
+            config.key().as_ref()
+        ],
+        bump,
+        constraint = market.buy_burn_enabled @ Error::BuyBurnNotEnabled,
+        has_one = config @ Error::ConfigAccountMismatch,
+        has_one = native_vault @ Error::NativeVaultAccountMismatch,
+        has_one = token_mint @ Error::TokenMintAccountMismatch,
+        has_one = token_vault @ Error::TokenVaultAccountMismatch,
+        has_one = token_program @ Error::TokenProgramAccountMismatch,
+    )]
+    pub market: Box<Account<'info, Market>>,
+    /// CHECK: Already checked in the market.
+    #[account(mut)]
+    pub native_vault: UncheckedAccount<'info>,
+    /// CHECK: Only used to receive fees.
+    #[account(mut)]
+    pub fee_recipient: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub token_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(mut,
+        seeds = [
+            BURN_ACCOUNT_SEED.as_bytes(),
+            payer.key().as_ref(),
+            market.burn_account_scope(&market.key()).as_ref()
+        ], bump)]
+    pub burn_account: Account<'info, BurnAccount>,
+    /// If `market.free_transfer_allowed` is `false`, then `token_recipient.owner`
+    /// MUST be `BLACK_HOLE`, otherwise no restrictions
+    #[account(mut,
+        constraint = token_recipient.mint == market.token_mint.key() @ Error::TokenMintAccountMismatch
+    )]
+    pub token_recipient: Box<InterfaceAccount<'info, TokenAccount>>,
+    pub token_mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    /// CHECK: Checked by the address constraint.
+    #[account(address = sysvar::instructions::ID)]
+    pub instructions_sysvar: UncheckedAccount<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+
+    /// CHECK: ExtraAccountMetaList Account, MUST use these exact seeds, required if the transfer hook is enabled.
+    pub extra_account_meta_list: Option<AccountInfo<'info>>,
+    #[account(address = HOOKS_PROGRAM_ID)]
+    /// CHECK: Required if the transfer hook is enabled.
+    pub hooks_program: Option<AccountInfo<'info>>,
+    pub burn_program: Program<'info, Burn>,
+
+    /// The quote token mint, required if the market is quoted in a token.
+    #[account(address = market.quote_mint @ Error::QuoteMintAccountMismatch)]
+    pub quote_mint: Option<Box<InterfaceAccount<'info, Mint>>>,
+    /// The payer's quote token account, required if the market is quoted in a token.
+    #[account(mut, token::mint = market.quote_mint, token::authority = payer)]
+    pub quote_token_payer: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    /// The fee recipient's quote token account, required if the market is quoted in a token.
+    #[account(mut, token::mint = market.quote_mint, token::authority = fee_recipient)]
+    pub fee_recipient_quote_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    pub quote_token_program: Option<Interface<'info, TokenInterface>>,
+
+    /// The burn leaderboard of the market, updated if provided (best-effort, see [BurnLeaderboard]).
+    #[account(mut, seeds = [BURN_LEADERBOARD_SEED.as_bytes(), market.key().as_ref()], bump)]
+    pub leaderboard: Option<Box<Account<'info, BurnLeaderboard>>>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct BuyBurnWithVoucherArgs {
+    pub next_nonce: u32,
+    pub next_buy_amount: u64,
+    pub max_pay: u64,
+    /// The expiry of the voucher signed by the buy-burn authority.
+    pub expiry: i64,
+    /// Optional deadline and limit price of the trade.
+    pub guard: TradeGuard,
+}
+
+/// Same as `buy_burn`, but the buy-burn authority signs a voucher off-chain instead of the transaction.
+///
+/// The instruction right before this one MUST be an Ed25519 program instruction,
+/// whose first signature is the buy-burn authority's signature of the voucher message.
+pub fn handler<'a, 'b, 'c, 'info>(
+    ctx: Context<'a, 'b, 'c, 'info, BuyBurnWithVoucher<'info>>,
+    args: BuyBurnWithVoucherArgs,
+) -> Result<()> {
+    let accounts = ctx.accounts;
+    require!(
+        accounts.market.free_transfer_allowed || accounts.token_recipient.owner == BLACK_HOLE,
+        Error::MustBlackHoleOwner
+    );
+
+    let now = Clock::get()?.unix_timestamp;
+    let voucher = BuyBurnVoucher {
+        burn_account: accounts.burn_account.key(),
+        market: accounts.market.key(),
+        next_nonce: args.next_nonce,
+        next_buy_amount: args.next_buy_amount,
+        expiry: args.expiry,
+    };
+    voucher.verify(
+        &accounts.instructions_sysvar,
+        voucher::previous_instruction_index(&accounts.instructions_sysvar)?,
+        0,
+        &accounts.config.buy_burn_authority,
+        now,
+    )?;
+
+    let buy_amount = accounts
+        .burn_account
+        .update_buy_amount(args.next_nonce, args.next_buy_amount)?;
+
+    let hook = TransferHookAccounts::from_accounts(
+        &accounts.market,
+        accounts.extra_account_meta_list.as_ref(),
+        accounts.hooks_program.as_ref(),
+        &accounts.burn_program,
+    )?;
+    let quote = QuoteAccounts::load(
+        &accounts.market,
+        accounts.quote_mint.as_deref(),
+        accounts.quote_token_payer.as_deref(),
+        accounts.fee_recipient_quote_account.as_deref(),
+        accounts.quote_token_program.as_ref(),
+    )?;
+
+    let (pay_amount, fee) = accounts.market.compute_buy_token_pay(buy_amount)?;
+
+    let market_account_info = accounts.market.to_account_info().clone();
+    accounts.market.buy_token(crate::state::BuyTokenArgs {
+        buy_amount,
+        max_pay: args.max_pay,
+        guard: args.guard,
+        config: &accounts.config,
+        market: market_account_info,
+        native_vault: &accounts.native_vault,
+        fee_recipient: &accounts.fee_recipient,
+        token_vault: &accounts.token_vault,
+        token_recipient: &accounts.token_recipient,
+        token_mint: &accounts.token_mint,
+        payer: &accounts.payer,
+        token_program: &accounts.token_program,
+        system_program: &accounts.system_program,
+
+        extra_account_meta_list: hook.extra_account_meta_list,
+        hooks_program: hook.hooks_program,
+        burn_program: hook.burn_program,
+
+        quote,
+    })?;
+
+    // only the tokens sent to the black hole count as burned
+    if accounts.token_recipient.owner == BLACK_HOLE {
+        accounts.burn_account.record_burn(buy_amount, pay_amount + fee, now);
+        if let Some(leaderboard) = &mut accounts.leaderboard {
+            leaderboard.update(accounts.payer.key(), accounts.burn_account.total_burned);
+        }
+    }
+    Ok(())
+}
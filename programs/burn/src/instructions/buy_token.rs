@@ -1,8 +1,7 @@
-use crate::constants::HOOKS_PROGRAM_ID;
 use crate::state::*;
+use crate::trade_guard::TradeGuard;
 use crate::{constants::CONFIG, errors::Error};
 use anchor_lang::prelude::*;
-use anchor_spl::token_2022::spl_token_2022;
 use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
 
 #[derive(Accounts)]
@@ -18,6 +17,7 @@ pub struct BuyToken<'info> {
         has_one = native_vault @ Error::NativeVaultAccountMismatch,
         has_one = token_mint @ Error::TokenMintAccountMismatch,
         has_one = token_vault @ Error::TokenVaultAccountMismatch,
+        has_one = token_program @ Error::TokenProgramAccountMismatch,
     )]
     pub market: Box<Account<'info, Market>>,
     /// CHECK: Already checked in the market.
@@ -35,15 +35,27 @@ pub struct BuyToken<'info> {
     pub token_mint: Box<InterfaceAccount<'info, Mint>>,
     #[account(mut)]
     pub payer: Signer<'info>,
-    #[account(address = spl_token_2022::ID)]
     pub token_program: Interface<'info, TokenInterface>,
     pub system_program: Program<'info, System>,
+
+    /// The quote token mint, required if the market is quoted in a token.
+    #[account(address = market.quote_mint @ Error::QuoteMintAccountMismatch)]
+    pub quote_mint: Option<Box<InterfaceAccount<'info, Mint>>>,
+    /// The payer's quote token account, required if the market is quoted in a token.
+    #[account(mut, token::mint = market.quote_mint, token::authority = payer)]
+    pub quote_token_payer: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    /// The fee recipient's quote token account, required if the market is quoted in a token.
+    #[account(mut, token::mint = market.quote_mint, token::authority = fee_recipient)]
+    pub fee_recipient_quote_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    pub quote_token_program: Option<Interface<'info, TokenInterface>>,
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize)]
 pub struct BuyTokenArgs {
     pub buy_amount: u64,
     pub max_pay: u64,
+    /// Optional deadline and limit price of the trade.
+    pub guard: TradeGuard,
 }
 
 pub fn handler<'a, 'b, 'c, 'info>(ctx: Context<'a, 'b, 'c, 'info, BuyToken<'info>>, args: BuyTokenArgs) -> Result<()> {
@@ -54,28 +66,21 @@ pub fn handler<'a, 'b, 'c, 'info>(ctx: Context<'a, 'b, 'c, 'info, BuyToken<'info
         require!(accounts.market.free_transfer_allowed, Error::CannotUseThisInstruction);
     }
 
-    let mut extra_account_meta_list: Option<&'c AccountInfo<'info>> = None;
-    let mut hooks_program: Option<&'c AccountInfo<'info>> = None;
-    let mut burn_program: Option<AccountInfo<'info>> = None;
-    if accounts.market.transfer_hook_enabled {
-        let mut iter = ctx.remaining_accounts.iter();
-        extra_account_meta_list = Some(iter.next().ok_or(Error::ExtraAccountMetaListAccountIsMissing)?);
-        hooks_program = Some(iter.next().ok_or(Error::HooksProgramIsMissing)?);
-        require!(
-            &hooks_program.unwrap().key() == &HOOKS_PROGRAM_ID,
-            Error::HooksProgramIsIncorrect
-        );
-        burn_program = Some(iter.next().map(|a| a.clone()).ok_or(Error::BurnProgramIsMissing)?);
-        require!(
-            &burn_program.as_ref().unwrap().key() == ctx.program_id,
-            Error::BurnProgramIsIncorrect
-        );
-    }
+    let hook = TransferHookAccounts::load(&accounts.market, &mut ctx.remaining_accounts.iter(), ctx.program_id)?;
+
+    let quote = QuoteAccounts::load(
+        &accounts.market,
+        accounts.quote_mint.as_deref(),
+        accounts.quote_token_payer.as_deref(),
+        accounts.fee_recipient_quote_account.as_deref(),
+        accounts.quote_token_program.as_ref(),
+    )?;
 
     let market_account_info = accounts.market.to_account_info().clone();
     accounts.market.buy_token(crate::state::BuyTokenArgs {
         buy_amount: args.buy_amount,
         max_pay: args.max_pay,
+        guard: args.guard,
         config: &accounts.config,
         market: market_account_info,
         native_vault: &accounts.native_vault,
@@ -87,8 +92,10 @@ pub fn handler<'a, 'b, 'c, 'info>(ctx: Context<'a, 'b, 'c, 'info, BuyToken<'info
         token_program: &accounts.token_program,
         system_program: &accounts.system_program,
 
-        extra_account_meta_list,
-        hooks_program,
-        burn_program,
+        extra_account_meta_list: hook.extra_account_meta_list,
+        hooks_program: hook.hooks_program,
+        burn_program: hook.burn_program,
+
+        quote,
     })
 }
@@ -0,0 +1,107 @@
+use crate::constants::WSOL_ACCOUNT_SEED;
+use crate::state::*;
+use crate::wsol;
+use crate::{constants::CONFIG, errors::Error};
+use anchor_lang::prelude::*;
+use anchor_spl::token::{spl_token::native_mint, Token};
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+/// Same as [crate::instructions::BuyToken], but pays with wrapped SOL.
+#[derive(Accounts)]
+pub struct BuyTokenWsol<'info> {
+    #[account(
+        has_one = fee_recipient @ Error::FeeRecipientMismatch,
+        seeds = [CONFIG.as_bytes()],
+        bump
+    )]
+    pub config: Box<Account<'info, Config>>,
+    #[account(mut,
+        has_one = config @ Error::ConfigAccountMismatch,
+        has_one = native_vault @ Error::NativeVaultAccountMismatch,
+        has_one = token_mint @ Error::TokenMintAccountMismatch,
+        has_one = token_vault @ Error::TokenVaultAccountMismatch,
+        has_one = token_program @ Error::TokenProgramAccountMismatch,
+        constraint = !market.is_quoted_in_token() @ Error::CannotUseThisInstruction,
+    )]
+    pub market: Box<Account<'info, Market>>,
+    /// CHECK: Already checked in the market.
+    #[account(mut)]
+    pub native_vault: UncheckedAccount<'info>,
+    /// CHECK: Only used to receive fees.
+    #[account(mut)]
+    pub fee_recipient: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub token_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(mut,
+        constraint = token_recipient.mint == market.token_mint.key() @ Error::TokenMintAccountMismatch
+    )]
+    pub token_recipient: Box<InterfaceAccount<'info, TokenAccount>>,
+    pub token_mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    /// The payer's wrapped SOL account to pay from.
+    #[account(mut, token::mint = wsol_mint, token::authority = payer)]
+    pub wsol_payer: Box<InterfaceAccount<'info, TokenAccount>>,
+    /// Temporary account used to unwrap the wrapped SOL, closed in the same instruction.
+    #[account(init, payer = payer,
+        seeds = [WSOL_ACCOUNT_SEED.as_bytes(), payer.key().as_ref()],
+        bump,
+        token::mint = wsol_mint,
+        token::authority = payer,
+        token::token_program = wsol_token_program,
+    )]
+    pub wsol_temp: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(address = native_mint::ID)]
+    pub wsol_mint: Box<InterfaceAccount<'info, Mint>>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub wsol_token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler<'a, 'b, 'c, 'info>(
+    ctx: Context<'a, 'b, 'c, 'info, BuyTokenWsol<'info>>,
+    args: super::buy_token::BuyTokenArgs,
+) -> Result<()> {
+    use crate::constants;
+
+    let accounts = ctx.accounts;
+    if accounts.market.symbol == constants::SYMBOL_BURN {
+        require!(accounts.market.free_transfer_allowed, Error::CannotUseThisInstruction);
+    }
+
+    let hook = TransferHookAccounts::load(&accounts.market, &mut ctx.remaining_accounts.iter(), ctx.program_id)?;
+
+    // unwrap exactly the amount to pay, including fees
+    let (native_pay_amount, fee) = accounts.market.compute_buy_token_pay(args.buy_amount)?;
+    wsol::unwrap_to_payer(
+        &accounts.payer,
+        &accounts.wsol_payer,
+        &accounts.wsol_temp,
+        &accounts.wsol_mint,
+        native_pay_amount + fee,
+        &accounts.wsol_token_program,
+    )?;
+
+    let market_account_info = accounts.market.to_account_info().clone();
+    accounts.market.buy_token(crate::state::BuyTokenArgs {
+        buy_amount: args.buy_amount,
+        max_pay: args.max_pay,
+        guard: args.guard,
+        config: &accounts.config,
+        market: market_account_info,
+        native_vault: &accounts.native_vault,
+        fee_recipient: &accounts.fee_recipient,
+        token_vault: &accounts.token_vault,
+        token_recipient: &accounts.token_recipient,
+        token_mint: &accounts.token_mint,
+        payer: &accounts.payer,
+        token_program: &accounts.token_program,
+        system_program: &accounts.system_program,
+
+        extra_account_meta_list: hook.extra_account_meta_list,
+        hooks_program: hook.hooks_program,
+        burn_program: hook.burn_program,
+
+        quote: None,
+    })
+}
@@ -0,0 +1,96 @@
+use crate::constants::{BURN_ACCOUNT_SEED, CONFIG, MARKET_SEED};
+use crate::errors::Error;
+use crate::state::{BurnAccount, ClosedBurnAccount, Config, Market};
+use crate::voucher::{self, BuyBurnVoucher};
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::sysvar;
+
+#[derive(Accounts)]
+pub struct CloseBurnAccount<'info> {
+    #[account(seeds = [CONFIG.as_bytes()], bump)]
+    pub config: Box<Account<'info, Config>>,
+    #[account(
+        seeds = [
+            MARKET_SEED.as_bytes(),
+            market.symbol.as_bytes(),
+            config.key().as_ref()
+        ],
+        bump,
+        has_one = config @ Error::ConfigAccountMismatch,
+    )]
+    pub market: Box<Account<'info, Market>>,
+    /// CHECK: Deserialized as a burn account and replaced by a [ClosedBurnAccount] by the handler.
+    #[account(mut, owner = crate::ID, seeds = [
+        BURN_ACCOUNT_SEED.as_bytes(),
+        owner.key().as_ref(),
+        market.burn_account_scope(&market.key()).as_ref()
+    ], bump)]
+    pub burn_account: UncheckedAccount<'info>,
+    pub owner: Signer<'info>,
+    /// Co-signs the close, not required if a voucher is provided.
+    #[account(address = config.buy_burn_authority @ Error::BuyBurnAuthorityMismatch)]
+    pub buy_burn_authority: Option<Signer<'info>>,
+    /// CHECK: Only used to receive the rent.
+    #[account(mut)]
+    pub recipient: UncheckedAccount<'info>,
+    /// CHECK: Checked by the address constraint, required if a voucher is provided.
+    #[account(address = sysvar::instructions::ID)]
+    pub instructions_sysvar: Option<UncheckedAccount<'info>>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct CloseBurnAccountArgs {
+    /// The expiry of the close voucher signed by the buy-burn authority, if it does not co-sign.
+    pub voucher_expiry: Option<i64>,
+}
+
+/// Closes the burn account of the owner and sends the rent to the recipient.
+///
+/// The burn account is shrunk to a [ClosedBurnAccount] rather than deleted, so the rent of
+/// the tombstone stays locked and only the rest is refunded.
+///
+/// Without the buy-burn authority co-signing, the instruction right before this one MUST be
+/// an Ed25519 program instruction, whose first signature is the buy-burn authority's signature
+/// of the close voucher of the current nonce and buy amount.
+pub fn handler(ctx: Context<CloseBurnAccount>, args: CloseBurnAccountArgs) -> Result<()> {
+    let accounts = ctx.accounts;
+    let burn_account_info = accounts.burn_account.to_account_info();
+    let burn_account = BurnAccount::try_deserialize(&mut &burn_account_info.try_borrow_data()?[..])?;
+    if accounts.buy_burn_authority.is_none() {
+        let expiry = args.voucher_expiry.ok_or(Error::MissingCloseAuthorization)?;
+        let instructions_sysvar = accounts
+            .instructions_sysvar
+            .as_ref()
+            .ok_or(Error::MissingCloseAuthorization)?;
+        let voucher = BuyBurnVoucher {
+            burn_account: burn_account_info.key(),
+            market: accounts.market.key(),
+            next_nonce: burn_account.nonce,
+            next_buy_amount: burn_account.buy_amount,
+            expiry,
+        };
+        voucher.verify_close(
+            instructions_sysvar,
+            voucher::previous_instruction_index(instructions_sysvar)?,
+            0,
+            &accounts.config.buy_burn_authority,
+            Clock::get()?.unix_timestamp,
+        )?;
+    }
+
+    msg!(
+        "nonce:{},buy_amount:{},total_burned:{},total_spent:{}",
+        burn_account.nonce,
+        burn_account.buy_amount,
+        burn_account.total_burned,
+        burn_account.total_spent,
+    );
+    burn_account_info.realloc(ClosedBurnAccount::LEN, false)?;
+    let refund = burn_account_info
+        .lamports()
+        .saturating_sub(Rent::get()?.minimum_balance(ClosedBurnAccount::LEN));
+    burn_account_info.sub_lamports(refund)?;
+    accounts.recipient.add_lamports(refund)?;
+    let mut data = burn_account_info.try_borrow_mut_data()?;
+    ClosedBurnAccount::close(&burn_account).try_serialize(&mut &mut data[..])
+}
@@ -1,6 +1,7 @@
 use crate::{
-    constants::{BURN_ACCOUNT_SEED, CONFIG},
-    state::{BurnAccount, Config},
+    constants::{BURN_ACCOUNT_SEED, CONFIG, MARKET_SEED},
+    errors::Error,
+    state::{BurnAccount, Config, Market},
 };
 use anchor_lang::prelude::*;
 
@@ -8,10 +9,21 @@ use anchor_lang::prelude::*;
 pub struct CreateBurnAccount<'info> {
     #[account(seeds = [CONFIG.as_bytes()], bump)]
     pub config: Box<Account<'info, Config>>,
+    #[account(
+        seeds = [
+            MARKET_SEED.as_bytes(),
+            market.symbol.as_bytes(),
+            config.key().as_ref()
+        ],
+        bump,
+        has_one = config @ Error::ConfigAccountMismatch,
+        constraint = market.buy_burn_enabled @ Error::BuyBurnNotEnabled,
+    )]
+    pub market: Box<Account<'info, Market>>,
     #[account(init, payer = payer, space = BurnAccount::LEN, seeds = [
         BURN_ACCOUNT_SEED.as_bytes(),
         owner.key().as_ref(),
-        config.key().as_ref()
+        market.burn_account_scope(&market.key()).as_ref()
     ], bump)]
     pub burn_account: Account<'info, BurnAccount>,
     /// CHECK:
@@ -0,0 +1,36 @@
+use crate::{
+    constants::{BURN_LEADERBOARD_SEED, CONFIG, MARKET_SEED},
+    errors::Error,
+    state::{BurnLeaderboard, Config, Market},
+};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct CreateBurnLeaderboard<'info> {
+    #[account(seeds = [CONFIG.as_bytes()], bump)]
+    pub config: Box<Account<'info, Config>>,
+    #[account(
+        seeds = [
+            MARKET_SEED.as_bytes(),
+            market.symbol.as_bytes(),
+            config.key().as_ref()
+        ],
+        bump,
+        has_one = config @ Error::ConfigAccountMismatch,
+        constraint = market.buy_burn_enabled @ Error::BuyBurnNotEnabled,
+    )]
+    pub market: Box<Account<'info, Market>>,
+    #[account(init, payer = payer, space = BurnLeaderboard::LEN, seeds = [
+        BURN_LEADERBOARD_SEED.as_bytes(),
+        market.key().as_ref()
+    ], bump)]
+    pub leaderboard: Box<Account<'info, BurnLeaderboard>>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(ctx: Context<CreateBurnLeaderboard>) -> Result<()> {
+    ctx.accounts.leaderboard.market = ctx.accounts.market.key();
+    Ok(())
+}
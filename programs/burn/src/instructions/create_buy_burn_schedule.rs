@@ -0,0 +1,59 @@
+use crate::constants::{BUY_BURN_SCHEDULE_SEED, CONFIG, MARKET_SEED};
+use crate::errors::Error;
+use crate::state::*;
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct CreateBuyBurnSchedule<'info> {
+    #[account(has_one = authority @ Error::NotConfigAuthority, seeds = [CONFIG.as_bytes()], bump)]
+    pub config: Box<Account<'info, Config>>,
+    #[account(mut,
+        seeds = [
+            MARKET_SEED.as_bytes(),
+            market.symbol.as_bytes(),
+            config.key().as_ref()
+        ],
+        bump,
+        has_one = config @ Error::ConfigAccountMismatch,
+    )]
+    pub market: Box<Account<'info, Market>>,
+    #[account(init, payer = payer, space = BuyBurnSchedule::LEN, seeds = [
+        BUY_BURN_SCHEDULE_SEED.as_bytes(),
+        market.key().as_ref()
+    ], bump)]
+    pub schedule: Box<Account<'info, BuyBurnSchedule>>,
+    pub authority: Signer<'info>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct BuyBurnScheduleArgs {
+    /// Max amount of the native (or quote) token to spend per window, including fees.
+    pub max_amount_per_window: u64,
+    /// Max amount of the native (or quote) token to spend per buy-burn, including fees, can't be `0`.
+    pub max_amount_per_call: u64,
+    /// Max average price of a buy-burn, in the native (or quote) token's smallest unit per whole token,
+    /// including fees, `0` means no limit. Required if the schedule is permissionless.
+    pub max_price: u64,
+    /// Length of the window in slots, `0` means the window is an epoch.
+    pub window_slots: u64,
+    /// Min slots between two buy-burns.
+    pub min_interval_slots: u64,
+    /// Whether anyone can call `use_funds_buy_burn`, not only the buy-burn authority.
+    pub permissionless: bool,
+}
+
+pub fn handler(ctx: Context<CreateBuyBurnSchedule>, args: BuyBurnScheduleArgs) -> Result<()> {
+    ctx.accounts.market.buy_burn_scheduled = true;
+    ctx.accounts.schedule.market = ctx.accounts.market.key();
+    ctx.accounts.schedule.configure(
+        args.max_amount_per_window,
+        args.max_amount_per_call,
+        args.max_price,
+        args.window_slots,
+        args.min_interval_slots,
+        args.permissionless,
+    )
+}
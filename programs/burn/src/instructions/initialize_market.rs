@@ -1,9 +1,18 @@
 use std::u64;
 
-use crate::constants::{CONFIG, DECIMALS, HOOKS_PROGRAM_ID, MARKET_SEED};
+use crate::constants::{
+    CONFIG, DECIMALS, HOOKS_PROGRAM_ID, INITIALIZE_ACCOUNT_META_LIST_DISCRIMINATOR, MARKET_SEED, SYMBOL_BURN,
+};
+use crate::errors::Error;
 use crate::state::*;
+use crate::trade_guard::TradeGuard;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::solana_program::program::invoke;
 use anchor_lang::{prelude::*, system_program};
 use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::metadata::mpl_token_metadata::types::DataV2;
+use anchor_spl::metadata::{create_metadata_accounts_v3, CreateMetadataAccountsV3, Metadata};
+use anchor_spl::token;
 use anchor_spl::token_2022::spl_token_2022;
 use anchor_spl::token_interface::{
     token_metadata_initialize, Mint, TokenAccount, TokenInterface, TokenMetadataInitialize,
@@ -52,6 +61,27 @@ pub struct InitializeMarket<'info> {
     pub associated_token_program: Program<'info, AssociatedToken>,
     pub system_program: Program<'info, System>,
     pub rent: Sysvar<'info, Rent>,
+
+    /// The quote token mint, if the market is quoted in a token instead of SOL.
+    pub quote_mint: Option<Box<InterfaceAccount<'info, Mint>>>,
+    pub quote_token_program: Option<Interface<'info, TokenInterface>>,
+
+    /// CHECK: Only used to receive fees, only required by the initial buy.
+    #[account(mut, address = config.fee_recipient @ Error::FeeRecipientMismatch)]
+    pub fee_recipient: Option<UncheckedAccount<'info>>,
+    /// The payer's token account to receive the initial buy, only required by the initial buy.
+    #[account(init, payer = payer,
+        associated_token::mint = token_mint,
+        associated_token::authority = payer,
+    )]
+    pub token_recipient: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    /// The payer's quote token account, only required by the initial buy if the market is quoted in a token.
+    #[account(mut, token::authority = payer)]
+    pub quote_token_payer: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    /// The fee recipient's quote token account,
+    /// only required by the initial buy if the market is quoted in a token.
+    #[account(mut, token::authority = config.fee_recipient)]
+    pub fee_recipient_quote_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
 }
 
 #[derive(Accounts)]
@@ -97,6 +127,102 @@ pub struct InitializeTransferHookMarket<'info> {
     pub associated_token_program: Program<'info, AssociatedToken>,
     pub system_program: Program<'info, System>,
     pub rent: Sysvar<'info, Rent>,
+
+    /// The quote token mint, if the market is quoted in a token instead of SOL.
+    pub quote_mint: Option<Box<InterfaceAccount<'info, Mint>>>,
+    pub quote_token_program: Option<Interface<'info, TokenInterface>>,
+
+    /// CHECK: Only used to receive fees, only required by the initial buy.
+    #[account(mut, address = config.fee_recipient @ Error::FeeRecipientMismatch)]
+    pub fee_recipient: Option<UncheckedAccount<'info>>,
+    /// The payer's token account to receive the initial buy, only required by the initial buy.
+    #[account(init, payer = payer,
+        associated_token::mint = token_mint,
+        associated_token::authority = payer,
+    )]
+    pub token_recipient: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    /// The payer's quote token account, only required by the initial buy if the market is quoted in a token.
+    #[account(mut, token::authority = payer)]
+    pub quote_token_payer: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    /// The fee recipient's quote token account,
+    /// only required by the initial buy if the market is quoted in a token.
+    #[account(mut, token::authority = config.fee_recipient)]
+    pub fee_recipient_quote_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+}
+
+#[derive(Accounts)]
+#[instruction(args: InitializeMarketArgs)]
+pub struct InitializeLegacyTokenMarket<'info> {
+    #[account(seeds = [CONFIG.as_bytes()], bump)]
+    pub config: Box<Account<'info, Config>>,
+
+    #[account(init, payer = payer,
+        mint::authority = market,
+        mint::decimals = DECIMALS,
+    )]
+    pub token_mint: Box<InterfaceAccount<'info, Mint>>,
+    /// The vault that holds the token.
+    #[account(init, payer = payer,
+        associated_token::mint = token_mint,
+        associated_token::authority = market,
+    )]
+    pub token_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(init, payer = payer, space = Market::LEN,
+        seeds = [
+            MARKET_SEED.as_bytes(),
+            args.symbol.as_bytes(),
+            config.key().as_ref()
+        ],
+        bump
+    )]
+    pub market: Box<Account<'info, Market>>,
+    /// CHECK: Initialized by the handler.
+    /// The vault that holds the native token(SOL).
+    #[account(mut)]
+    pub native_vault: UncheckedAccount<'info>,
+    /// CHECK: Initialized by the token metadata program.
+    /// The Metaplex metadata account of the token mint.
+    #[account(mut,
+        seeds = [
+            b"metadata",
+            token_metadata_program.key().as_ref(),
+            token_mint.key().as_ref()
+        ],
+        bump,
+        seeds::program = token_metadata_program.key()
+    )]
+    pub metadata: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(address = token::ID)]
+    pub token_program: Interface<'info, TokenInterface>,
+    pub token_metadata_program: Program<'info, Metadata>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+
+    /// The quote token mint, if the market is quoted in a token instead of SOL.
+    pub quote_mint: Option<Box<InterfaceAccount<'info, Mint>>>,
+    pub quote_token_program: Option<Interface<'info, TokenInterface>>,
+
+    /// CHECK: Only used to receive fees, only required by the initial buy.
+    #[account(mut, address = config.fee_recipient @ Error::FeeRecipientMismatch)]
+    pub fee_recipient: Option<UncheckedAccount<'info>>,
+    /// The payer's token account to receive the initial buy, only required by the initial buy.
+    #[account(init, payer = payer,
+        associated_token::mint = token_mint,
+        associated_token::authority = payer,
+    )]
+    pub token_recipient: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    /// The payer's quote token account, only required by the initial buy if the market is quoted in a token.
+    #[account(mut, token::authority = payer)]
+    pub quote_token_payer: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    /// The fee recipient's quote token account,
+    /// only required by the initial buy if the market is quoted in a token.
+    #[account(mut, token::authority = config.fee_recipient)]
+    pub fee_recipient_quote_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize)]
@@ -104,127 +230,339 @@ pub struct InitializeMarketArgs {
     pub name: String,
     pub symbol: String,
     pub uri: String,
+    /// Buys tokens for the payer right after the market is created.
+    ///
+    /// For the transfer hook market, the `ExtraAccountMetaList` is initialized by this instruction
+    /// and the transfer hook accounts must be passed through the remaining accounts.
+    pub initial_buy: Option<InitialBuyArgs>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct InitialBuyArgs {
+    /// The amount of the native (or quote) token to pay, not including fees.
+    pub pay_amount: u64,
+    /// The minimum amount of the token to receive.
+    pub min_receive: u64,
+}
+
+/// The accounts shared by the instructions that initialize a market.
+struct MarketAccounts<'b, 'info> {
+    config: &'b Account<'info, Config>,
+    market: &'b mut Account<'info, Market>,
+    token_mint: &'b InterfaceAccount<'info, Mint>,
+    token_vault: &'b InterfaceAccount<'info, TokenAccount>,
+    native_vault: &'b UncheckedAccount<'info>,
+    payer: &'b Signer<'info>,
+    token_program: &'b Interface<'info, TokenInterface>,
+    associated_token_program: &'b Program<'info, AssociatedToken>,
+    system_program: &'b Program<'info, System>,
+    rent: &'b Sysvar<'info, Rent>,
+    quote_mint: Option<&'b InterfaceAccount<'info, Mint>>,
+    quote_token_program: Option<&'b Interface<'info, TokenInterface>>,
+    fee_recipient: Option<&'b UncheckedAccount<'info>>,
+    token_recipient: Option<&'b InterfaceAccount<'info, TokenAccount>>,
+    quote_token_payer: Option<&'b InterfaceAccount<'info, TokenAccount>>,
+    fee_recipient_quote_account: Option<&'b InterfaceAccount<'info, TokenAccount>>,
 }
 
 pub fn handler_initialize_market<'a, 'b, 'c, 'info>(
     ctx: Context<'a, 'b, 'c, 'info, InitializeMarket<'info>>,
     args: InitializeMarketArgs,
 ) -> Result<()> {
-    Market::check_symbol(&args.symbol)?;
-
-    // Step 1: Initialize the market.
-    ctx.accounts.market.initialize(
-        &ctx.accounts.config,
-        &ctx.accounts.token_mint,
-        &ctx.accounts.token_vault,
-        args.symbol.clone(),
+    let accounts = ctx.accounts;
+    initialize_market(
+        MarketAccounts {
+            config: &accounts.config,
+            market: &mut accounts.market,
+            token_mint: &accounts.token_mint,
+            token_vault: &accounts.token_vault,
+            native_vault: &accounts.native_vault,
+            payer: &accounts.payer,
+            token_program: &accounts.token_program,
+            associated_token_program: &accounts.associated_token_program,
+            system_program: &accounts.system_program,
+            rent: &accounts.rent,
+            quote_mint: accounts.quote_mint.as_deref(),
+            quote_token_program: accounts.quote_token_program.as_ref(),
+            fee_recipient: accounts.fee_recipient.as_ref(),
+            token_recipient: accounts.token_recipient.as_deref(),
+            quote_token_payer: accounts.quote_token_payer.as_deref(),
+            fee_recipient_quote_account: accounts.fee_recipient_quote_account.as_deref(),
+        },
+        args,
         ctx.bumps.market,
         false,
-    );
-    // Step 2: Create the native vault.
-    ctx.accounts.market.create_native_vault(
-        &ctx.accounts.payer,
-        &ctx.accounts.native_vault,
-        &ctx.program_id,
-        &ctx.accounts.system_program,
-        &ctx.accounts.rent,
-    )?;
-
-    // Step 3: Initialize the token metadata.
-    initialize_token_metadata(
-        &ctx.accounts.market,
-        &ctx.accounts.payer,
-        &ctx.accounts.token_mint,
-        &ctx.accounts.token_program,
-        &ctx.accounts.rent,
-        &ctx.accounts.system_program,
-        args.name,
-        args.symbol,
-        args.uri,
-    )?;
-    // Step 4: Mint the total supply to the market vault.
-    ctx.accounts.market.mint_total_supply_to_market_vault(
-        &ctx.accounts.market,
-        &ctx.accounts.token_mint,
-        &ctx.accounts.token_vault,
-        &ctx.accounts.token_program,
-    )?;
-    // Step 5: Revoke the mint authority.
-    let market_account_info = ctx.accounts.market.to_account_info().clone();
-    ctx.accounts.market.revoke_mint_authority(
-        market_account_info,
-        &ctx.accounts.token_mint,
-        &ctx.accounts.token_program,
-    )?;
-
-    Ok(())
+        ctx.remaining_accounts,
+        ctx.program_id,
+        initialize_token_metadata,
+    )
 }
 
 pub fn handler_initialize_transfer_hook_market<'a, 'b, 'c, 'info>(
     ctx: Context<'a, 'b, 'c, 'info, InitializeTransferHookMarket<'info>>,
     args: InitializeMarketArgs,
+) -> Result<()> {
+    let accounts = ctx.accounts;
+    initialize_market(
+        MarketAccounts {
+            config: &accounts.config,
+            market: &mut accounts.market,
+            token_mint: &accounts.token_mint,
+            token_vault: &accounts.token_vault,
+            native_vault: &accounts.native_vault,
+            payer: &accounts.payer,
+            token_program: &accounts.token_program,
+            associated_token_program: &accounts.associated_token_program,
+            system_program: &accounts.system_program,
+            rent: &accounts.rent,
+            quote_mint: accounts.quote_mint.as_deref(),
+            quote_token_program: accounts.quote_token_program.as_ref(),
+            fee_recipient: accounts.fee_recipient.as_ref(),
+            token_recipient: accounts.token_recipient.as_deref(),
+            quote_token_payer: accounts.quote_token_payer.as_deref(),
+            fee_recipient_quote_account: accounts.fee_recipient_quote_account.as_deref(),
+        },
+        args,
+        ctx.bumps.market,
+        true,
+        ctx.remaining_accounts,
+        ctx.program_id,
+        initialize_token_metadata,
+    )
+}
+
+pub fn handler_initialize_legacy_token_market<'a, 'b, 'c, 'info>(
+    ctx: Context<'a, 'b, 'c, 'info, InitializeLegacyTokenMarket<'info>>,
+    args: InitializeMarketArgs,
+) -> Result<()> {
+    let accounts = ctx.accounts;
+    let metadata = &accounts.metadata;
+    let token_metadata_program = &accounts.token_metadata_program;
+    initialize_market(
+        MarketAccounts {
+            config: &accounts.config,
+            market: &mut accounts.market,
+            token_mint: &accounts.token_mint,
+            token_vault: &accounts.token_vault,
+            native_vault: &accounts.native_vault,
+            payer: &accounts.payer,
+            token_program: &accounts.token_program,
+            associated_token_program: &accounts.associated_token_program,
+            system_program: &accounts.system_program,
+            rent: &accounts.rent,
+            quote_mint: accounts.quote_mint.as_deref(),
+            quote_token_program: accounts.quote_token_program.as_ref(),
+            fee_recipient: accounts.fee_recipient.as_ref(),
+            token_recipient: accounts.token_recipient.as_deref(),
+            quote_token_payer: accounts.quote_token_payer.as_deref(),
+            fee_recipient_quote_account: accounts.fee_recipient_quote_account.as_deref(),
+        },
+        args,
+        ctx.bumps.market,
+        false,
+        ctx.remaining_accounts,
+        ctx.program_id,
+        // The Metaplex metadata instead of the Token-2022 metadata extension.
+        |accounts, name, symbol, uri| {
+            create_metadata_accounts_v3(
+                CpiContext::new(
+                    token_metadata_program.to_account_info(),
+                    CreateMetadataAccountsV3 {
+                        metadata: metadata.to_account_info(),
+                        mint: accounts.token_mint.to_account_info(),
+                        mint_authority: accounts.market.to_account_info(),
+                        payer: accounts.payer.to_account_info(),
+                        update_authority: accounts.market.to_account_info(),
+                        system_program: accounts.system_program.to_account_info(),
+                        rent: accounts.rent.to_account_info(),
+                    },
+                )
+                .with_signer(&[&accounts.market.seeds()]),
+                DataV2 {
+                    name,
+                    symbol,
+                    uri,
+                    seller_fee_basis_points: 0,
+                    creators: None,
+                    collection: None,
+                    uses: None,
+                },
+                true, // is_mutable
+                true, // update_authority_is_signer
+                None, // collection_details
+            )
+        },
+    )
+}
+
+/// Initializes the market, its vaults and its token, then executes the initial buy if any.
+///
+/// `initialize_metadata` initializes the metadata of the token, while the market is still its mint authority.
+fn initialize_market<'b, 'c, 'info>(
+    mut accounts: MarketAccounts<'b, 'info>,
+    args: InitializeMarketArgs,
+    bump: u8,
+    transfer_hook_enabled: bool,
+    remaining_accounts: &'c [AccountInfo<'info>],
+    program_id: &Pubkey,
+    initialize_metadata: impl FnOnce(&MarketAccounts<'b, 'info>, String, String, String) -> Result<()>,
 ) -> Result<()> {
     Market::check_symbol(&args.symbol)?;
 
     // Step 1: Initialize the market.
-    ctx.accounts.market.initialize(
-        &ctx.accounts.config,
-        &ctx.accounts.token_mint,
-        &ctx.accounts.token_vault,
+    accounts.market.initialize(
+        accounts.config,
+        accounts.token_mint,
+        accounts.token_vault,
         args.symbol.clone(),
-        ctx.bumps.market,
-        true,
+        bump,
+        transfer_hook_enabled,
     );
-    // Step 2: Create the native vault.
-    ctx.accounts.market.create_native_vault(
-        &ctx.accounts.payer,
-        &ctx.accounts.native_vault,
-        &ctx.program_id,
-        &ctx.accounts.system_program,
-        &ctx.accounts.rent,
-    )?;
+    accounts.market.creator = accounts.payer.key();
+    // Step 2: Create the native vault, or the quote vault if the market is quoted in a token.
+    if let Some(quote_mint) = accounts.quote_mint {
+        Market::create_quote_vault(
+            accounts.market,
+            accounts.payer,
+            accounts.native_vault,
+            quote_mint,
+            accounts.quote_token_program.ok_or(Error::QuoteTokenProgramIsMissing)?,
+            accounts.associated_token_program,
+            accounts.system_program,
+        )?;
+    } else {
+        accounts.market.create_native_vault(
+            accounts.payer,
+            accounts.native_vault,
+            program_id,
+            accounts.system_program,
+            accounts.rent,
+        )?;
+    }
 
     // Step 3: Initialize the token metadata.
-    initialize_token_metadata(
-        &ctx.accounts.market,
-        &ctx.accounts.payer,
-        &ctx.accounts.token_mint,
-        &ctx.accounts.token_program,
-        &ctx.accounts.rent,
-        &ctx.accounts.system_program,
-        args.name,
-        args.symbol,
-        args.uri,
-    )?;
+    initialize_metadata(&accounts, args.name, args.symbol, args.uri)?;
     // Step 4: Mint the total supply to the market vault.
-    ctx.accounts.market.mint_total_supply_to_market_vault(
-        &ctx.accounts.market,
-        &ctx.accounts.token_mint,
-        &ctx.accounts.token_vault,
-        &ctx.accounts.token_program,
+    accounts.market.mint_total_supply_to_market_vault(
+        accounts.market,
+        accounts.token_mint,
+        accounts.token_vault,
+        accounts.token_program,
     )?;
     // Step 5: Revoke the mint authority.
-    let market_account_info = ctx.accounts.market.to_account_info().clone();
-    ctx.accounts.market.revoke_mint_authority(
-        market_account_info,
-        &ctx.accounts.token_mint,
-        &ctx.accounts.token_program,
-    )?;
+    let market_account_info = accounts.market.to_account_info().clone();
+    accounts
+        .market
+        .revoke_mint_authority(market_account_info, accounts.token_mint, accounts.token_program)?;
+    // Step 6: Execute the initial buy.
+    if let Some(initial_buy) = args.initial_buy {
+        let hook = TransferHookAccounts::load(accounts.market, &mut remaining_accounts.iter(), program_id)?;
+        if transfer_hook_enabled {
+            initialize_account_meta_list(
+                hook.extra_account_meta_list
+                    .ok_or(Error::ExtraAccountMetaListAccountIsMissing)?,
+                hook.hooks_program.ok_or(Error::HooksProgramIsMissing)?,
+                accounts.token_mint,
+                accounts.payer,
+                accounts.system_program,
+                accounts.rent,
+                accounts.market.symbol.clone(),
+            )?;
+        }
+        execute_initial_buy(&mut accounts, initial_buy, hook)?;
+    }
 
     Ok(())
 }
 
-fn initialize_token_metadata<'info>(
-    market: &Account<'info, Market>,
+fn execute_initial_buy<'c, 'info>(
+    accounts: &mut MarketAccounts<'_, 'info>,
+    args: InitialBuyArgs,
+    hook: TransferHookAccounts<'c, 'info>,
+) -> Result<()> {
+    require!(accounts.market.symbol != SYMBOL_BURN, Error::CannotUseThisInstruction);
+    let quote = QuoteAccounts::load(
+        accounts.market,
+        accounts.quote_mint,
+        accounts.quote_token_payer,
+        accounts.fee_recipient_quote_account,
+        accounts.quote_token_program,
+    )?;
+
+    // The transfer hook reads the market, so it must be written before transferring the token.
+    accounts.market.exit(&crate::ID)?;
+    let market_account_info = accounts.market.to_account_info();
+    accounts.market.buy_token_exact_in(BuyTokenExactInArgs {
+        pay_amount: args.pay_amount,
+        min_receive: args.min_receive,
+        guard: TradeGuard::default(),
+        config: accounts.config,
+        market: market_account_info,
+        native_vault: accounts.native_vault,
+        fee_recipient: accounts.fee_recipient.ok_or(Error::FeeRecipientIsMissing)?,
+        token_vault: accounts.token_vault,
+        token_recipient: accounts.token_recipient.ok_or(Error::TokenRecipientIsMissing)?,
+        token_mint: accounts.token_mint,
+        payer: accounts.payer,
+        token_program: accounts.token_program,
+        system_program: accounts.system_program,
+
+        extra_account_meta_list: hook.extra_account_meta_list,
+        hooks_program: hook.hooks_program,
+        burn_program: hook.burn_program,
+
+        quote,
+    })
+}
+
+/// Initializes the `ExtraAccountMetaList` of the hooks program,
+/// so that the token can be transferred in the same instruction.
+fn initialize_account_meta_list<'info>(
+    extra_account_meta_list: &AccountInfo<'info>,
+    hooks_program: &AccountInfo<'info>,
+    token_mint: &InterfaceAccount<'info, Mint>,
     payer: &Signer<'info>,
-    token_mint: &Box<InterfaceAccount<'info, Mint>>,
-    token_program: &Interface<'info, TokenInterface>,
-    rent: &Sysvar<'info, Rent>,
     system_program: &Program<'info, System>,
-    name: String,
+    rent: &Sysvar<'info, Rent>,
     symbol: String,
-    uri: String,
 ) -> Result<()> {
+    let mut data = INITIALIZE_ACCOUNT_META_LIST_DISCRIMINATOR.to_vec();
+    symbol.serialize(&mut data)?;
+    let ix = Instruction {
+        program_id: HOOKS_PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new_readonly(token_mint.key(), false),
+            AccountMeta::new(extra_account_meta_list.key(), false),
+            AccountMeta::new(payer.key(), true),
+            AccountMeta::new_readonly(system_program.key(), false),
+            AccountMeta::new_readonly(rent.key(), false),
+        ],
+        data,
+    };
+    invoke(
+        &ix,
+        &[
+            token_mint.to_account_info(),
+            extra_account_meta_list.clone(),
+            payer.to_account_info(),
+            system_program.to_account_info(),
+            rent.to_account_info(),
+            hooks_program.clone(),
+        ],
+    )?;
+    Ok(())
+}
+
+fn initialize_token_metadata(accounts: &MarketAccounts, name: String, symbol: String, uri: String) -> Result<()> {
+    let MarketAccounts {
+        market,
+        payer,
+        token_mint,
+        token_program,
+        rent,
+        system_program,
+        ..
+    } = accounts;
     let token_metadata = TokenMetadata {
         name: name.clone(),
         symbol: symbol.clone(),
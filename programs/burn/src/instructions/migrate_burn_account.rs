@@ -0,0 +1,64 @@
+use crate::constants::{BURN_ACCOUNT_SEED, CONFIG, MARKET_SEED};
+use crate::errors::Error;
+use crate::state::*;
+use anchor_lang::{prelude::*, system_program, Discriminator};
+
+#[derive(Accounts)]
+pub struct MigrateBurnAccount<'info> {
+    #[account(seeds = [CONFIG.as_bytes()], bump)]
+    pub config: Box<Account<'info, Config>>,
+    #[account(
+        seeds = [
+            MARKET_SEED.as_bytes(),
+            market.symbol.as_bytes(),
+            config.key().as_ref()
+        ],
+        bump,
+        has_one = config @ Error::ConfigAccountMismatch,
+    )]
+    pub market: Box<Account<'info, Market>>,
+    /// CHECK: Checked by the seeds, deserialized with the legacy layout by the handler.
+    #[account(mut, owner = crate::ID, seeds = [
+        BURN_ACCOUNT_SEED.as_bytes(),
+        owner.key().as_ref(),
+        market.burn_account_scope(&market.key()).as_ref()
+    ], bump)]
+    pub burn_account: UncheckedAccount<'info>,
+    /// CHECK: Only used to derive the burn account.
+    pub owner: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Extends a burn account created before the burn stats were added, the stats start at zero.
+pub fn handler(ctx: Context<MigrateBurnAccount>) -> Result<()> {
+    let burn_account = ctx.accounts.burn_account.to_account_info();
+    {
+        let data = burn_account.try_borrow_data()?;
+        require!(data.len() == BurnAccount::LEGACY_LEN, Error::BurnAccountAlreadyMigrated);
+        require!(
+            data[..8] == BurnAccount::DISCRIMINATOR,
+            ErrorCode::AccountDiscriminatorMismatch
+        );
+    }
+
+    // The payer tops up the rent of the extended layout.
+    let rent = Rent::get()?.minimum_balance(BurnAccount::LEN);
+    let top_up = rent.saturating_sub(burn_account.lamports());
+    if top_up > 0 {
+        system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: ctx.accounts.payer.to_account_info(),
+                    to: burn_account.clone(),
+                },
+            ),
+            top_up,
+        )?;
+    }
+    // zero-initialized, so that the new fields decode as zero
+    burn_account.realloc(BurnAccount::LEN, true)?;
+    Ok(())
+}
@@ -0,0 +1,64 @@
+use crate::constants::{CONFIG, MARKET_SEED};
+use crate::errors::Error;
+use crate::state::*;
+use anchor_lang::{prelude::*, system_program, Discriminator};
+
+#[derive(Accounts)]
+pub struct MigrateMarket<'info> {
+    #[account(seeds = [CONFIG.as_bytes()], bump)]
+    pub config: Box<Account<'info, Config>>,
+    /// CHECK: Deserialized with the legacy layout by the handler.
+    #[account(mut, owner = crate::ID)]
+    pub market: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(ctx: Context<MigrateMarket>) -> Result<()> {
+    let market_info = ctx.accounts.market.to_account_info();
+
+    let legacy = {
+        let data = market_info.try_borrow_data()?;
+        require!(data.len() < Market::LEN, Error::MarketAlreadyMigrated);
+        require!(
+            data.len() >= 8 && data[..8] == Market::DISCRIMINATOR,
+            ErrorCode::AccountDiscriminatorMismatch
+        );
+        LegacyMarket::deserialize(&mut &data[8..])?
+    };
+    require_keys_eq!(legacy.config, ctx.accounts.config.key(), Error::ConfigAccountMismatch);
+    let market_actual = Pubkey::create_program_address(
+        &[
+            MARKET_SEED.as_bytes(),
+            legacy.symbol.as_bytes(),
+            legacy.config.as_ref(),
+            legacy.bump.as_ref(),
+        ],
+        ctx.program_id,
+    )
+    .map_err(|_| Error::MarketAccountMismatch)?;
+    require_keys_eq!(market_actual, market_info.key(), Error::MarketAccountMismatch);
+
+    // The payer tops up the rent of the extended layout.
+    let rent = Rent::get()?.minimum_balance(Market::LEN);
+    let top_up = rent.saturating_sub(market_info.lamports());
+    if top_up > 0 {
+        system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: ctx.accounts.payer.to_account_info(),
+                    to: market_info.clone(),
+                },
+            ),
+            top_up,
+        )?;
+    }
+    market_info.realloc(Market::LEN, true)?;
+
+    let market = legacy.migrate();
+    msg!("Migrated market: {}, symbol: {}", market_info.key(), market.symbol);
+    let mut data = market_info.try_borrow_mut_data()?;
+    market.try_serialize(&mut &mut data[..])
+}
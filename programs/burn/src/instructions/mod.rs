@@ -1,25 +1,59 @@
 #![allow(ambiguous_glob_reexports)]
 
+pub mod batch_buy_burn;
 pub mod buy_burn;
 pub mod buy_burn_exact_in;
+pub mod buy_burn_with_voucher;
 pub mod buy_token;
 pub mod buy_token_exact_in;
+pub mod buy_token_exact_in_wsol;
+pub mod buy_token_wsol;
+pub mod close_burn_account;
 pub mod create_burn_account;
+pub mod create_burn_leaderboard;
+pub mod create_buy_burn_schedule;
 pub mod initialize_config;
 pub mod initialize_market;
+pub mod migrate_burn_account;
+pub mod migrate_market;
+pub mod reopen_burn_account;
 pub mod sell_token;
+pub mod sell_token_wsol;
+pub mod set_buy_burn_schedule;
 pub mod set_config_authority;
 pub mod set_fee_recipient;
+pub mod set_market_buy_burn_enabled;
+pub mod skim_market;
+pub mod swap_markets;
+pub mod update_market_metadata;
 pub mod use_funds_buy_burn;
+pub mod verify_market;
 
+pub use batch_buy_burn::*;
 pub use buy_burn::*;
 pub use buy_burn_exact_in::*;
+pub use buy_burn_with_voucher::*;
 pub use buy_token::*;
 pub use buy_token_exact_in::*;
+pub use buy_token_exact_in_wsol::*;
+pub use buy_token_wsol::*;
+pub use close_burn_account::*;
 pub use create_burn_account::*;
+pub use create_burn_leaderboard::*;
+pub use create_buy_burn_schedule::*;
 pub use initialize_config::*;
 pub use initialize_market::*;
+pub use migrate_burn_account::*;
+pub use migrate_market::*;
+pub use reopen_burn_account::*;
 pub use sell_token::*;
+pub use sell_token_wsol::*;
+pub use set_buy_burn_schedule::*;
 pub use set_config_authority::*;
 pub use set_fee_recipient::*;
+pub use set_market_buy_burn_enabled::*;
+pub use skim_market::*;
+pub use swap_markets::*;
+pub use update_market_metadata::*;
 pub use use_funds_buy_burn::*;
+pub use verify_market::*;
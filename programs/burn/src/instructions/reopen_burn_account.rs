@@ -0,0 +1,58 @@
+use crate::constants::{BURN_ACCOUNT_SEED, CONFIG, MARKET_SEED};
+use crate::errors::Error;
+use crate::state::{BurnAccount, ClosedBurnAccount, Config, Market};
+use anchor_lang::{prelude::*, system_program};
+
+#[derive(Accounts)]
+pub struct ReopenBurnAccount<'info> {
+    #[account(seeds = [CONFIG.as_bytes()], bump)]
+    pub config: Box<Account<'info, Config>>,
+    #[account(
+        seeds = [
+            MARKET_SEED.as_bytes(),
+            market.symbol.as_bytes(),
+            config.key().as_ref()
+        ],
+        bump,
+        has_one = config @ Error::ConfigAccountMismatch,
+    )]
+    pub market: Box<Account<'info, Market>>,
+    /// CHECK: Deserialized as a [ClosedBurnAccount] and replaced by a burn account by the handler.
+    #[account(mut, owner = crate::ID, seeds = [
+        BURN_ACCOUNT_SEED.as_bytes(),
+        owner.key().as_ref(),
+        market.burn_account_scope(&market.key()).as_ref()
+    ], bump)]
+    pub burn_account: UncheckedAccount<'info>,
+    /// CHECK:
+    pub owner: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Re-creates a closed burn account from the state it was closed with,
+/// so that its nonce and buy amount continue instead of starting over.
+pub fn handler(ctx: Context<ReopenBurnAccount>) -> Result<()> {
+    let burn_account_info = ctx.accounts.burn_account.to_account_info();
+    let closed = ClosedBurnAccount::try_deserialize(&mut &burn_account_info.try_borrow_data()?[..])?;
+
+    // The payer tops up the rent of the burn account.
+    let rent = Rent::get()?.minimum_balance(BurnAccount::LEN);
+    let top_up = rent.saturating_sub(burn_account_info.lamports());
+    if top_up > 0 {
+        system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: ctx.accounts.payer.to_account_info(),
+                    to: burn_account_info.clone(),
+                },
+            ),
+            top_up,
+        )?;
+    }
+    burn_account_info.realloc(BurnAccount::LEN, true)?;
+    let mut data = burn_account_info.try_borrow_mut_data()?;
+    closed.reopen().try_serialize(&mut &mut data[..])
+}
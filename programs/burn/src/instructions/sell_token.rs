@@ -1,8 +1,7 @@
-use crate::constants::HOOKS_PROGRAM_ID;
 use crate::state::*;
+use crate::trade_guard::TradeGuard;
 use crate::{constants::CONFIG, errors::Error};
 use anchor_lang::prelude::*;
-use anchor_spl::token_2022::spl_token_2022;
 use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
 
 #[derive(Accounts)]
@@ -16,6 +15,7 @@ pub struct SellToken<'info> {
     #[account(mut,
         has_one = config @ Error::ConfigAccountMismatch,
         has_one = token_vault @ Error::TokenVaultAccountMismatch,
+        has_one = token_program @ Error::TokenProgramAccountMismatch,
         has_one = native_vault @ Error::NativeVaultAccountMismatch,
         has_one = token_mint @ Error::TokenMintAccountMismatch,
     )]
@@ -38,15 +38,24 @@ pub struct SellToken<'info> {
     pub token_payer: Box<InterfaceAccount<'info, TokenAccount>>,
     pub token_mint: Box<InterfaceAccount<'info, Mint>>,
     pub payer: Signer<'info>,
-    #[account(address = spl_token_2022::ID)]
     pub token_program: Interface<'info, TokenInterface>,
     pub system_program: Program<'info, System>,
+
+    /// The quote token mint, required if the market is quoted in a token.
+    #[account(address = market.quote_mint @ Error::QuoteMintAccountMismatch)]
+    pub quote_mint: Option<Box<InterfaceAccount<'info, Mint>>>,
+    /// The fee recipient's quote token account, required if the market is quoted in a token.
+    #[account(mut, token::mint = market.quote_mint, token::authority = fee_recipient)]
+    pub fee_recipient_quote_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    pub quote_token_program: Option<Interface<'info, TokenInterface>>,
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize)]
 pub struct SellTokenArgs {
     pub sell_amount: u64,
     pub min_receive: u64,
+    /// Optional deadline and limit price of the trade.
+    pub guard: TradeGuard,
 }
 
 pub fn handler<'a, 'b, 'c, 'info>(
@@ -55,42 +64,37 @@ pub fn handler<'a, 'b, 'c, 'info>(
 ) -> Result<()> {
     let accounts = ctx.accounts;
     let market_account_info = accounts.market.to_account_info().clone();
-    let mut extra_account_meta_list: Option<&'c AccountInfo<'info>> = None;
-    let mut hooks_program: Option<&'c AccountInfo<'info>> = None;
-    let mut burn_program: Option<AccountInfo<'info>> = None;
+    let hook = TransferHookAccounts::load(&accounts.market, &mut ctx.remaining_accounts.iter(), ctx.program_id)?;
 
-    if accounts.market.transfer_hook_enabled {
-        let mut iter = ctx.remaining_accounts.iter();
-        extra_account_meta_list = Some(iter.next().ok_or(Error::ExtraAccountMetaListAccountIsMissing)?);
-        hooks_program = Some(iter.next().ok_or(Error::HooksProgramIsMissing)?);
-        require!(
-            &hooks_program.unwrap().key() == &HOOKS_PROGRAM_ID,
-            Error::HooksProgramIsIncorrect
-        );
-        burn_program = Some(iter.next().map(|a| a.clone()).ok_or(Error::BurnProgramIsMissing)?);
-        require!(
-            &burn_program.as_ref().unwrap().key() == ctx.program_id,
-            Error::BurnProgramIsIncorrect
-        );
-    }
+    let quote = QuoteAccounts::load(
+        &accounts.market,
+        accounts.quote_mint.as_deref(),
+        None,
+        accounts.fee_recipient_quote_account.as_deref(),
+        accounts.quote_token_program.as_ref(),
+    )?;
 
     accounts.market.sell_token(crate::state::SellTokenArgs {
         sell_amount: args.sell_amount,
         config: &accounts.config,
         market: market_account_info,
         min_receive: args.min_receive,
+        guard: args.guard,
         native_vault: &accounts.native_vault,
         fee_recipient: &accounts.fee_recipient,
         token_vault: &accounts.token_vault,
-        native_recipient: &accounts.native_recipient,
+        native_recipient: accounts.native_recipient.to_account_info(),
         token_payer: &accounts.token_payer,
         token_mint: &accounts.token_mint,
         payer: &accounts.payer,
         token_program: &accounts.token_program,
         system_program: &accounts.system_program,
 
-        extra_account_meta_list,
-        hooks_program,
-        burn_program,
-    })
+        extra_account_meta_list: hook.extra_account_meta_list,
+        hooks_program: hook.hooks_program,
+        burn_program: hook.burn_program,
+
+        quote,
+    })?;
+    Ok(())
 }
@@ -0,0 +1,84 @@
+use crate::state::*;
+use crate::wsol;
+use crate::{constants::CONFIG, errors::Error};
+use anchor_lang::prelude::*;
+use anchor_spl::token::{spl_token::native_mint, Token};
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+/// Same as [crate::instructions::SellToken], but receives wrapped SOL.
+#[derive(Accounts)]
+pub struct SellTokenWsol<'info> {
+    #[account(
+        has_one = fee_recipient @ Error::FeeRecipientMismatch,
+        seeds = [CONFIG.as_bytes()],
+        bump
+    )]
+    pub config: Box<Account<'info, Config>>,
+    #[account(mut,
+        has_one = config @ Error::ConfigAccountMismatch,
+        has_one = native_vault @ Error::NativeVaultAccountMismatch,
+        has_one = token_mint @ Error::TokenMintAccountMismatch,
+        has_one = token_vault @ Error::TokenVaultAccountMismatch,
+        has_one = token_program @ Error::TokenProgramAccountMismatch,
+        constraint = !market.is_quoted_in_token() @ Error::CannotUseThisInstruction,
+    )]
+    pub market: Box<Account<'info, Market>>,
+    /// CHECK: Already checked in the market.
+    #[account(mut)]
+    pub native_vault: UncheckedAccount<'info>,
+    /// CHECK: Only used to receive fees.
+    #[account(mut)]
+    pub fee_recipient: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub token_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    /// The wrapped SOL account to receive native tokens.
+    #[account(mut, token::mint = wsol_mint)]
+    pub wsol_recipient: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(mut,
+        token::mint = market.token_mint,
+        token::authority = payer,
+    )]
+    pub token_payer: Box<InterfaceAccount<'info, TokenAccount>>,
+    pub token_mint: Box<InterfaceAccount<'info, Mint>>,
+    pub payer: Signer<'info>,
+    #[account(address = native_mint::ID)]
+    pub wsol_mint: Box<InterfaceAccount<'info, Mint>>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub wsol_token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler<'a, 'b, 'c, 'info>(
+    ctx: Context<'a, 'b, 'c, 'info, SellTokenWsol<'info>>,
+    args: super::sell_token::SellTokenArgs,
+) -> Result<()> {
+    let accounts = ctx.accounts;
+    let market_account_info = accounts.market.to_account_info().clone();
+    let hook = TransferHookAccounts::load(&accounts.market, &mut ctx.remaining_accounts.iter(), ctx.program_id)?;
+
+    accounts.market.sell_token(crate::state::SellTokenArgs {
+        sell_amount: args.sell_amount,
+        config: &accounts.config,
+        market: market_account_info,
+        min_receive: args.min_receive,
+        guard: args.guard,
+        native_vault: &accounts.native_vault,
+        fee_recipient: &accounts.fee_recipient,
+        token_vault: &accounts.token_vault,
+        native_recipient: accounts.wsol_recipient.to_account_info(),
+        token_payer: &accounts.token_payer,
+        token_mint: &accounts.token_mint,
+        payer: &accounts.payer,
+        token_program: &accounts.token_program,
+        system_program: &accounts.system_program,
+
+        extra_account_meta_list: hook.extra_account_meta_list,
+        hooks_program: hook.hooks_program,
+        burn_program: hook.burn_program,
+
+        quote: None,
+    })?;
+
+    // wrap the received lamports
+    wsol::sync(&accounts.wsol_recipient, &accounts.wsol_token_program)
+}
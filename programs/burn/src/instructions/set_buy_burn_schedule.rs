@@ -0,0 +1,25 @@
+use crate::constants::{BUY_BURN_SCHEDULE_SEED, CONFIG};
+use crate::errors::Error;
+use crate::instructions::BuyBurnScheduleArgs;
+use crate::state::*;
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct SetBuyBurnSchedule<'info> {
+    #[account(has_one = authority @ Error::NotConfigAuthority, seeds = [CONFIG.as_bytes()], bump)]
+    pub config: Box<Account<'info, Config>>,
+    #[account(mut, seeds = [BUY_BURN_SCHEDULE_SEED.as_bytes(), schedule.market.as_ref()], bump)]
+    pub schedule: Box<Account<'info, BuyBurnSchedule>>,
+    pub authority: Signer<'info>,
+}
+
+pub fn handler(ctx: Context<SetBuyBurnSchedule>, args: BuyBurnScheduleArgs) -> Result<()> {
+    ctx.accounts.schedule.configure(
+        args.max_amount_per_window,
+        args.max_amount_per_call,
+        args.max_price,
+        args.window_slots,
+        args.min_interval_slots,
+        args.permissionless,
+    )
+}
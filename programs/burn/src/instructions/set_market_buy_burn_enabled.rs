@@ -0,0 +1,26 @@
+use crate::constants::{CONFIG, MARKET_SEED};
+use crate::errors::Error;
+use crate::state::*;
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct SetMarketBuyBurnEnabled<'info> {
+    #[account(has_one = authority @ Error::NotConfigAuthority, seeds = [CONFIG.as_bytes()], bump)]
+    pub config: Box<Account<'info, Config>>,
+    #[account(mut,
+        seeds = [
+            MARKET_SEED.as_bytes(),
+            market.symbol.as_bytes(),
+            config.key().as_ref()
+        ],
+        bump,
+        has_one = config @ Error::ConfigAccountMismatch,
+    )]
+    pub market: Box<Account<'info, Market>>,
+    pub authority: Signer<'info>,
+}
+
+pub fn handler(ctx: Context<SetMarketBuyBurnEnabled>, enabled: bool) -> Result<()> {
+    ctx.accounts.market.buy_burn_enabled = enabled;
+    Ok(())
+}
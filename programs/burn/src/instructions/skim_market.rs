@@ -0,0 +1,100 @@
+use crate::constants::*;
+use crate::errors::Error;
+use crate::state::*;
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+#[derive(Accounts)]
+pub struct SkimMarket<'info> {
+    #[account(
+        has_one = fee_recipient @ Error::FeeRecipientMismatch,
+        seeds = [CONFIG.as_bytes()],
+        bump
+    )]
+    pub config: Box<Account<'info, Config>>,
+    #[account(mut,
+        seeds = [
+            MARKET_SEED.as_bytes(),
+            market.symbol.as_bytes(),
+            config.key().as_ref()
+        ],
+        bump,
+        has_one = config @ Error::ConfigAccountMismatch,
+        has_one = native_vault @ Error::NativeVaultAccountMismatch,
+        has_one = token_mint @ Error::TokenMintAccountMismatch,
+        has_one = token_vault @ Error::TokenVaultAccountMismatch,
+        has_one = token_program @ Error::TokenProgramAccountMismatch,
+    )]
+    pub market: Box<Account<'info, Market>>,
+    /// CHECK: Already checked in the market.
+    #[account(mut)]
+    pub native_vault: UncheckedAccount<'info>,
+    /// CHECK: Only used to receive the surplus.
+    #[account(mut)]
+    pub fee_recipient: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub token_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(mut)]
+    pub token_mint: Box<InterfaceAccount<'info, Mint>>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+
+    /// The quote token mint, required if the market is quoted in a token.
+    #[account(address = market.quote_mint @ Error::QuoteMintAccountMismatch)]
+    pub quote_mint: Option<Box<InterfaceAccount<'info, Mint>>>,
+    /// The fee recipient's quote token account, required if the market is quoted in a token.
+    #[account(mut, token::mint = market.quote_mint, token::authority = fee_recipient)]
+    pub fee_recipient_quote_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    pub quote_token_program: Option<Interface<'info, TokenInterface>>,
+}
+
+/// Sends the native (or quote) tokens above the curve reserve to the fee recipient,
+/// and burns the tokens above the remaining supply in the token vault.
+///
+/// The native surplus of a market with buy-burn enabled is kept for `use_funds_buy_burn`.
+pub fn handler(ctx: Context<SkimMarket>) -> Result<()> {
+    let accounts = ctx.accounts;
+
+    let native_surplus = if accounts.market.buy_burn_enabled {
+        0
+    } else {
+        accounts
+            .market
+            .native_vault_balance(&accounts.native_vault, &Rent::get()?)?
+            // the tokens held by the black hole are counted as sold back, which keeps more in the vault
+            .saturating_sub(accounts.market.required_reserve(0)?)
+    };
+    if native_surplus > 0 {
+        let quote = QuoteAccounts::load(
+            &accounts.market,
+            accounts.quote_mint.as_deref(),
+            None,
+            accounts.fee_recipient_quote_account.as_deref(),
+            accounts.quote_token_program.as_ref(),
+        )?;
+        accounts.market.transfer_native_from_vault(
+            accounts.market.to_account_info(),
+            &accounts.native_vault,
+            fee_destination(&accounts.fee_recipient, quote.as_ref()),
+            native_surplus,
+            &accounts.system_program,
+            quote.as_ref(),
+        )?;
+    }
+
+    let token_surplus = accounts
+        .token_vault
+        .amount
+        .saturating_sub(accounts.market.remaining_supply);
+    let market_account_info = accounts.market.to_account_info();
+    accounts.market.burn_token_from_vault(
+        token_surplus,
+        market_account_info,
+        &accounts.token_vault,
+        &accounts.token_mint,
+        &accounts.token_program,
+    )?;
+
+    msg!("native_surplus:{},token_surplus:{}", native_surplus, token_surplus);
+    Ok(())
+}
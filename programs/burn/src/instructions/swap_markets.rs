@@ -0,0 +1,179 @@
+use crate::math::swap_math;
+use crate::state::*;
+use crate::trade_guard::TradeGuard;
+use crate::{constants::*, errors::Error};
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+#[derive(Accounts)]
+pub struct SwapMarkets<'info> {
+    #[account(
+        has_one = fee_recipient @ Error::FeeRecipientMismatch,
+        seeds = [CONFIG.as_bytes()],
+        bump
+    )]
+    pub config: Box<Account<'info, Config>>,
+    /// CHECK: Only used to receive fees.
+    #[account(mut)]
+    pub fee_recipient: UncheckedAccount<'info>,
+
+    #[account(mut,
+        has_one = config @ Error::ConfigAccountMismatch,
+        constraint = sell_market.key() != buy_market.key() @ Error::SameMarket,
+        constraint = sell_market.quote_mint == buy_market.quote_mint @ Error::QuoteMintAccountMismatch,
+    )]
+    pub sell_market: Box<Account<'info, Market>>,
+    /// CHECK: Already checked in the market.
+    #[account(mut, address = sell_market.native_vault @ Error::NativeVaultAccountMismatch)]
+    pub sell_native_vault: UncheckedAccount<'info>,
+    #[account(mut, address = sell_market.token_vault @ Error::TokenVaultAccountMismatch)]
+    pub sell_token_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(address = sell_market.token_mint @ Error::TokenMintAccountMismatch)]
+    pub sell_token_mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(address = sell_market.token_program @ Error::TokenProgramAccountMismatch)]
+    pub sell_token_program: Interface<'info, TokenInterface>,
+    #[account(mut,
+        token::mint = sell_market.token_mint,
+        token::authority = payer,
+    )]
+    pub token_payer: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(mut, has_one = config @ Error::ConfigAccountMismatch)]
+    pub buy_market: Box<Account<'info, Market>>,
+    /// CHECK: Already checked in the market.
+    #[account(mut, address = buy_market.native_vault @ Error::NativeVaultAccountMismatch)]
+    pub buy_native_vault: UncheckedAccount<'info>,
+    #[account(mut, address = buy_market.token_vault @ Error::TokenVaultAccountMismatch)]
+    pub buy_token_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(address = buy_market.token_mint @ Error::TokenMintAccountMismatch)]
+    pub buy_token_mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(address = buy_market.token_program @ Error::TokenProgramAccountMismatch)]
+    pub buy_token_program: Interface<'info, TokenInterface>,
+    #[account(mut,
+        constraint = token_recipient.mint == buy_market.token_mint.key() @ Error::TokenMintAccountMismatch
+    )]
+    pub token_recipient: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+
+    /// The quote token mint, required if the markets are quoted in a token.
+    #[account(address = sell_market.quote_mint @ Error::QuoteMintAccountMismatch)]
+    pub quote_mint: Option<Box<InterfaceAccount<'info, Mint>>>,
+    /// The payer's quote token account, receives the proceeds of the sell and pays the buy,
+    /// required if the markets are quoted in a token.
+    #[account(mut, token::mint = sell_market.quote_mint, token::authority = payer)]
+    pub quote_token_payer: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    /// The fee recipient's quote token account, required if the markets are quoted in a token.
+    #[account(mut, token::mint = sell_market.quote_mint, token::authority = fee_recipient)]
+    pub fee_recipient_quote_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    pub quote_token_program: Option<Interface<'info, TokenInterface>>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct SwapMarketsArgs {
+    /// The amount of the token to sell on the sell market.
+    pub sell_amount: u64,
+    /// The minimum amount of the token to receive from the buy market.
+    pub min_receive: u64,
+    /// Optional deadline of the swap and limit price of the buy.
+    pub guard: TradeGuard,
+    /// The worst acceptable average price of the sell, see [TradeGuard::limit_price].
+    pub sell_limit_price: Option<u64>,
+}
+
+/// Sells on the sell market and spends all the proceeds, fees included, buying on the buy market.
+///
+/// The transfer hook accounts of the sell market, then of the buy market,
+/// are passed through the remaining accounts if their transfer hook is enabled.
+pub fn handler<'a, 'b, 'c, 'info>(
+    ctx: Context<'a, 'b, 'c, 'info, SwapMarkets<'info>>,
+    args: SwapMarketsArgs,
+) -> Result<()> {
+    let accounts = ctx.accounts;
+    if accounts.buy_market.symbol == SYMBOL_BURN {
+        require!(
+            accounts.buy_market.free_transfer_allowed,
+            Error::CannotUseThisInstruction
+        );
+    }
+    let sell_guard = TradeGuard {
+        deadline: args.guard.deadline,
+        limit_price: args.sell_limit_price,
+    };
+
+    let mut remaining_accounts = ctx.remaining_accounts.iter();
+    let sell_hook = TransferHookAccounts::load(&accounts.sell_market, &mut remaining_accounts, ctx.program_id)?;
+    let buy_hook = TransferHookAccounts::load(&accounts.buy_market, &mut remaining_accounts, ctx.program_id)?;
+
+    let sell_quote = QuoteAccounts::load(
+        &accounts.sell_market,
+        accounts.quote_mint.as_deref(),
+        accounts.quote_token_payer.as_deref(),
+        accounts.fee_recipient_quote_account.as_deref(),
+        accounts.quote_token_program.as_ref(),
+    )?;
+    let native_recipient = match &sell_quote {
+        Some(quote) => quote
+            .quote_token_payer
+            .ok_or(Error::QuoteTokenPayerIsMissing)?
+            .to_account_info(),
+        None => accounts.payer.to_account_info(),
+    };
+
+    let sell_market_account_info = accounts.sell_market.to_account_info();
+    let proceeds = accounts.sell_market.sell_token(SellTokenArgs {
+        sell_amount: args.sell_amount,
+        min_receive: 0,
+        guard: sell_guard,
+        config: &accounts.config,
+        market: sell_market_account_info,
+        native_vault: &accounts.sell_native_vault,
+        fee_recipient: &accounts.fee_recipient,
+        token_vault: &accounts.sell_token_vault,
+        token_mint: &accounts.sell_token_mint,
+        native_recipient,
+        token_payer: &accounts.token_payer,
+        payer: &accounts.payer,
+        token_program: &accounts.sell_token_program,
+        system_program: &accounts.system_program,
+
+        extra_account_meta_list: sell_hook.extra_account_meta_list,
+        hooks_program: sell_hook.hooks_program,
+        burn_program: sell_hook.burn_program,
+
+        quote: sell_quote,
+    })?;
+
+    let (pay_amount, _) = swap_math::split_pay_amount(proceeds)?;
+    let buy_quote = QuoteAccounts::load(
+        &accounts.buy_market,
+        accounts.quote_mint.as_deref(),
+        accounts.quote_token_payer.as_deref(),
+        accounts.fee_recipient_quote_account.as_deref(),
+        accounts.quote_token_program.as_ref(),
+    )?;
+    let buy_market_account_info = accounts.buy_market.to_account_info();
+    accounts.buy_market.buy_token_exact_in(BuyTokenExactInArgs {
+        pay_amount,
+        min_receive: args.min_receive,
+        guard: args.guard,
+        config: &accounts.config,
+        market: buy_market_account_info,
+        native_vault: &accounts.buy_native_vault,
+        fee_recipient: &accounts.fee_recipient,
+        token_vault: &accounts.buy_token_vault,
+        token_recipient: &accounts.token_recipient,
+        token_mint: &accounts.buy_token_mint,
+        payer: &accounts.payer,
+        token_program: &accounts.buy_token_program,
+        system_program: &accounts.system_program,
+
+        extra_account_meta_list: buy_hook.extra_account_meta_list,
+        hooks_program: buy_hook.hooks_program,
+        burn_program: buy_hook.burn_program,
+
+        quote: buy_quote,
+    })
+}
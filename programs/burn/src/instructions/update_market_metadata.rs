@@ -0,0 +1,223 @@
+use crate::constants::CONFIG;
+use crate::errors::Error;
+use crate::state::*;
+use anchor_lang::solana_program::program::invoke_signed;
+use anchor_lang::{prelude::*, system_program};
+use anchor_spl::metadata::mpl_token_metadata::types::DataV2;
+use anchor_spl::metadata::{update_metadata_accounts_v2, Metadata, MetadataAccount, UpdateMetadataAccountsV2};
+use anchor_spl::token;
+use anchor_spl::token_2022::spl_token_2022;
+use anchor_spl::token_2022::spl_token_2022::extension::{BaseStateWithExtensions, StateWithExtensions};
+use anchor_spl::token_interface::spl_pod::optional_keys::OptionalNonZeroPubkey;
+use anchor_spl::token_interface::{
+    token_metadata_update_authority, token_metadata_update_field, Mint, TokenInterface, TokenMetadataUpdateAuthority,
+    TokenMetadataUpdateField,
+};
+use spl_token_metadata_interface::state::{Field, TokenMetadata};
+use spl_type_length_value::variable_len_pack::VariableLenPack;
+
+#[derive(Accounts)]
+pub struct UpdateMarketMetadata<'info> {
+    #[account(seeds = [CONFIG.as_bytes()], bump)]
+    pub config: Box<Account<'info, Config>>,
+    #[account(
+        has_one = config @ Error::ConfigAccountMismatch,
+        has_one = token_mint @ Error::TokenMintAccountMismatch,
+        has_one = token_program @ Error::TokenProgramAccountMismatch,
+        constraint = authority.key() == config.authority || authority.key() == market.creator @ Error::NotMetadataAuthority,
+    )]
+    pub market: Box<Account<'info, Market>>,
+    #[account(mut)]
+    pub token_mint: Box<InterfaceAccount<'info, Mint>>,
+    /// CHECK: The Metaplex metadata account, only required if the mint is owned by the SPL Token program.
+    /// Only the metadata whose update authority is the market can be updated.
+    #[account(mut)]
+    pub metadata: Option<UncheckedAccount<'info>>,
+    /// The config authority or the market creator.
+    pub authority: Signer<'info>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub token_metadata_program: Option<Program<'info, Metadata>>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct MetadataField {
+    pub key: String,
+    pub value: String,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct UpdateMarketMetadataArgs {
+    pub name: Option<String>,
+    pub uri: Option<String>,
+    /// Key/value pairs to set in the additional metadata, e.g. website, socials or description.
+    ///
+    /// Not supported by markets whose mint is owned by the SPL Token program.
+    pub additional_metadata: Vec<MetadataField>,
+    /// Keys to remove from the additional metadata.
+    pub remove_keys: Vec<String>,
+    /// Renounces the update authority after the update, the metadata can no longer be updated.
+    pub renounce_update_authority: bool,
+}
+
+pub fn handler(ctx: Context<UpdateMarketMetadata>, args: UpdateMarketMetadataArgs) -> Result<()> {
+    if ctx.accounts.market.token_program == token::ID {
+        update_metaplex_metadata(ctx.accounts, args)
+    } else {
+        update_token_metadata(ctx.accounts, args)
+    }
+}
+
+fn update_token_metadata(accounts: &UpdateMarketMetadata, args: UpdateMarketMetadataArgs) -> Result<()> {
+    let mut fields: Vec<(Field, String)> = vec![];
+    if let Some(name) = args.name {
+        fields.push((Field::Name, name));
+    }
+    if let Some(uri) = args.uri {
+        fields.push((Field::Uri, uri));
+    }
+    for field in args.additional_metadata {
+        fields.push((Field::Key(field.key), field.value));
+    }
+
+    // Step 1: Top up the rent for the new size of the metadata.
+    let token_mint = accounts.token_mint.to_account_info();
+    let data_len = {
+        let data = token_mint.try_borrow_data()?;
+        let mint = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&data)?;
+        let mut token_metadata = mint.get_variable_len_extension::<TokenMetadata>()?;
+        let current_metadata_len = token_metadata.get_packed_len()?;
+        for (field, value) in fields.iter() {
+            token_metadata.update(field.clone(), value.clone());
+        }
+        for key in args.remove_keys.iter() {
+            token_metadata.remove_key(key);
+        }
+        data.len() - current_metadata_len + token_metadata.get_packed_len()?
+    };
+    let lamports = accounts
+        .rent
+        .minimum_balance(data_len)
+        .saturating_sub(token_mint.lamports());
+    if lamports > 0 {
+        system_program::transfer(
+            CpiContext::new(
+                accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: accounts.payer.to_account_info(),
+                    to: token_mint.clone(),
+                },
+            ),
+            lamports,
+        )?;
+    }
+
+    // Step 2: Update the fields.
+    let seeds = accounts.market.seeds();
+    for (field, value) in fields {
+        token_metadata_update_field(
+            CpiContext::new(
+                accounts.token_program.to_account_info(),
+                TokenMetadataUpdateField {
+                    token_program_id: accounts.token_program.to_account_info(),
+                    metadata: token_mint.clone(),
+                    update_authority: accounts.market.to_account_info(),
+                },
+            )
+            .with_signer(&[&seeds]),
+            field,
+            value,
+        )?;
+    }
+
+    // Step 3: Remove the keys.
+    for key in args.remove_keys {
+        let ix = spl_token_metadata_interface::instruction::remove_key(
+            &accounts.token_program.key(),
+            &token_mint.key(),
+            &accounts.market.key(),
+            key,
+            true, // idempotent
+        );
+        invoke_signed(
+            &ix,
+            &[
+                accounts.token_program.to_account_info(),
+                token_mint.clone(),
+                accounts.market.to_account_info(),
+            ],
+            &[&seeds],
+        )?;
+    }
+
+    // Step 4: Renounce the update authority.
+    if args.renounce_update_authority {
+        token_metadata_update_authority(
+            CpiContext::new(
+                accounts.token_program.to_account_info(),
+                TokenMetadataUpdateAuthority {
+                    token_program_id: accounts.token_program.to_account_info(),
+                    metadata: token_mint.clone(),
+                    current_authority: accounts.market.to_account_info(),
+                    new_authority: accounts.market.to_account_info(),
+                },
+            )
+            .with_signer(&[&seeds]),
+            OptionalNonZeroPubkey::default(),
+        )?;
+    }
+
+    Ok(())
+}
+
+fn update_metaplex_metadata(accounts: &UpdateMarketMetadata, args: UpdateMarketMetadataArgs) -> Result<()> {
+    require!(
+        args.additional_metadata.is_empty() && args.remove_keys.is_empty(),
+        Error::AdditionalMetadataNotSupported
+    );
+    let metadata = accounts.metadata.as_ref().ok_or(Error::MetadataAccountIsMissing)?;
+    let token_metadata_program = accounts
+        .token_metadata_program
+        .as_ref()
+        .ok_or(Error::TokenMetadataProgramIsMissing)?;
+
+    let current = MetadataAccount::try_deserialize(&mut &metadata.try_borrow_data()?[..])?;
+    require!(
+        current.mint == accounts.token_mint.key(),
+        Error::MetadataAccountMismatch
+    );
+
+    // Metaplex pads the stored strings with null characters.
+    let data = DataV2 {
+        name: args
+            .name
+            .unwrap_or_else(|| current.name.trim_end_matches('\0').to_string()),
+        symbol: current.symbol.trim_end_matches('\0').to_string(),
+        uri: args
+            .uri
+            .unwrap_or_else(|| current.uri.trim_end_matches('\0').to_string()),
+        seller_fee_basis_points: current.seller_fee_basis_points,
+        creators: current.creators.clone(),
+        collection: current.collection.clone(),
+        uses: current.uses.clone(),
+    };
+
+    update_metadata_accounts_v2(
+        CpiContext::new(
+            token_metadata_program.to_account_info(),
+            UpdateMetadataAccountsV2 {
+                metadata: metadata.to_account_info(),
+                update_authority: accounts.market.to_account_info(),
+            },
+        )
+        .with_signer(&[&accounts.market.seeds()]),
+        None,
+        Some(data),
+        None,
+        // Metaplex does not support removing the update authority, so the metadata is made immutable instead.
+        args.renounce_update_authority.then_some(false),
+    )
+}
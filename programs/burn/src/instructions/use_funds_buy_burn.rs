@@ -1,16 +1,14 @@
 use crate::math::swap_math;
 use crate::program::Burn;
 use crate::state::*;
+use crate::trade_guard::TradeGuard;
 use crate::{constants::*, errors::Error};
 use anchor_lang::prelude::*;
-use anchor_lang::system_program;
-use anchor_spl::token_2022::spl_token_2022;
 use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
 
 #[derive(Accounts)]
 pub struct UseFundsBuyBurn<'info> {
     #[account(
-        has_one = buy_burn_authority @ Error::BuyBurnAuthorityMismatch,
         has_one = fee_recipient @ Error::FeeRecipientMismatch,
         seeds = [CONFIG.as_bytes()],
         bump
@@ -19,14 +17,17 @@ pub struct UseFundsBuyBurn<'info> {
     #[account(mut,
         seeds = [
             MARKET_SEED.as_bytes(),
-            SYMBOL_BURN.as_bytes(),
+            market.symbol.as_bytes(),
             config.key().as_ref()
         ],
         bump,
+        constraint = market.buy_burn_enabled @ Error::BuyBurnNotEnabled,
+        constraint = market.buy_burn_scheduled == schedule.is_some() @ Error::BuyBurnScheduleMismatch,
         has_one = config @ Error::ConfigAccountMismatch,
         has_one = native_vault @ Error::NativeVaultAccountMismatch,
         has_one = token_mint @ Error::TokenMintAccountMismatch,
         has_one = token_vault @ Error::TokenVaultAccountMismatch,
+        has_one = token_program @ Error::TokenProgramAccountMismatch,
     )]
     pub market: Box<Account<'info, Market>>,
     /// CHECK: Already checked in the market.
@@ -37,91 +38,159 @@ pub struct UseFundsBuyBurn<'info> {
     pub fee_recipient: UncheckedAccount<'info>,
     #[account(mut)]
     pub token_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    /// The black hole token account, receives the bought tokens unless they are burned.
     #[account(mut,
         constraint = token_recipient.mint == market.token_mint.key() @ Error::TokenMintAccountMismatch,
         constraint = token_recipient.owner == BLACK_HOLE @ Error::MustBlackHoleOwner,
     )]
     pub token_recipient: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(mut)]
     pub token_mint: Box<InterfaceAccount<'info, Mint>>,
-    pub buy_burn_authority: Signer<'info>,
-    #[account(address = spl_token_2022::ID)]
+    /// Not required if the schedule is permissionless.
+    #[account(address = config.buy_burn_authority @ Error::BuyBurnAuthorityMismatch)]
+    pub buy_burn_authority: Option<Signer<'info>>,
     pub token_program: Interface<'info, TokenInterface>,
     pub system_program: Program<'info, System>,
     pub rent: Sysvar<'info, Rent>,
 
-    /// CHECK: ExtraAccountMetaList Account, MUST use these exact seeds
-    pub extra_account_meta_list: AccountInfo<'info>,
+    /// CHECK: ExtraAccountMetaList Account, MUST use these exact seeds, required if the transfer hook is enabled.
+    pub extra_account_meta_list: Option<AccountInfo<'info>>,
     #[account(address = HOOKS_PROGRAM_ID)]
-    /// CHECK:
-    pub hooks_program: AccountInfo<'info>,
+    /// CHECK: Required if the transfer hook is enabled.
+    pub hooks_program: Option<AccountInfo<'info>>,
     pub burn_program: Program<'info, Burn>,
+
+    /// The quote token mint, required if the market is quoted in a token.
+    #[account(address = market.quote_mint @ Error::QuoteMintAccountMismatch)]
+    pub quote_mint: Option<Box<InterfaceAccount<'info, Mint>>>,
+    /// The fee recipient's quote token account, required if the market is quoted in a token.
+    #[account(mut, token::mint = market.quote_mint, token::authority = fee_recipient)]
+    pub fee_recipient_quote_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    pub quote_token_program: Option<Interface<'info, TokenInterface>>,
+
+    /// The schedule of the market, required if `market.buy_burn_scheduled` is `true`.
+    #[account(mut, seeds = [BUY_BURN_SCHEDULE_SEED.as_bytes(), market.key().as_ref()], bump)]
+    pub schedule: Option<Box<Account<'info, BuyBurnSchedule>>>,
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize)]
 pub struct UseFundsBuyBurnArgs {
+    /// Ignored if the buy-burn authority doesn't sign: a permissionless crank always spends
+    /// all the funds available to the call, so that it can't be wasted on tiny buys.
     pub max_buy_amount: u64,
+    /// Burns the bought tokens from the token vault instead of transferring them to the black hole.
+    pub burn: bool,
+    /// Optional deadline and limit price of the trade.
+    pub guard: TradeGuard,
 }
 
 pub fn handler(ctx: Context<UseFundsBuyBurn>, args: UseFundsBuyBurnArgs) -> Result<()> {
-    require!(args.max_buy_amount > 0, Error::AmountCannotBeZero);
-
     let accounts = ctx.accounts;
-    let available_native_tokens = accounts.market.change_free_transfer_allowed_for_burn(
+    let max_buy_amount = if accounts.buy_burn_authority.is_some() {
+        require!(args.max_buy_amount > 0, Error::AmountCannotBeZero);
+        args.max_buy_amount
+    } else {
+        require!(
+            accounts.schedule.as_ref().is_some_and(|s| s.permissionless),
+            Error::BuyBurnAuthorityIsMissing
+        );
+        u64::MAX
+    };
+
+    let mut available_native_tokens = accounts.market.change_free_transfer_allowed_for_burn(
         &accounts.token_recipient,
         &accounts.native_vault,
         &accounts.rent,
     )?;
+    let clock = Clock::get()?;
+    args.guard.check_deadline(&clock)?;
+    if let Some(schedule) = &accounts.schedule {
+        available_native_tokens = available_native_tokens.min(schedule.available(&clock)?);
+    }
     // -1 to avoid selling the last token
-    let mut buy_amount = (accounts.market.remaining_supply - 1).min(args.max_buy_amount);
+    let mut buy_amount = (accounts.market.remaining_supply - 1).min(max_buy_amount);
     if available_native_tokens == 0 || buy_amount == 0 {
         return Ok(());
     }
 
     let mut pay_amount = swap_math::compute_swap(buy_amount, accounts.market.remaining_supply, true)?;
+    pay_amount = swap_math::native_to_quote_amount(pay_amount, accounts.market.quote_decimals, true)?;
     let mut fee = swap_math::compute_fee(pay_amount);
 
     if (pay_amount as u128 + fee as u128) > (available_native_tokens as u128) {
         // buy token exact in
         (pay_amount, fee) = swap_math::split_pay_amount(available_native_tokens)?;
-        buy_amount = swap_math::compute_buy_token_exact_in(pay_amount, accounts.market.remaining_supply)?;
+        buy_amount = swap_math::compute_buy_token_exact_in(
+            swap_math::quote_to_native_amount(pay_amount, accounts.market.quote_decimals)?,
+            accounts.market.remaining_supply,
+        )?;
         if buy_amount == 0 {
             return Ok(());
         }
     }
 
+    args.guard.check_buy_price(buy_amount, pay_amount + fee)?;
+
+    let hook = TransferHookAccounts::from_accounts(
+        &accounts.market,
+        accounts.extra_account_meta_list.as_ref(),
+        accounts.hooks_program.as_ref(),
+        &accounts.burn_program,
+    )?;
+    let quote = QuoteAccounts::load(
+        &accounts.market,
+        accounts.quote_mint.as_deref(),
+        None,
+        accounts.fee_recipient_quote_account.as_deref(),
+        accounts.quote_token_program.as_ref(),
+    )?;
+
+    if let Some(schedule) = &mut accounts.schedule {
+        // bounds the price a crank can be made to buy at, e.g. after the price is pushed up in the same slot
+        schedule.check_price(buy_amount, pay_amount + fee)?;
+        schedule.record(pay_amount + fee, &clock);
+    }
     accounts.market.remaining_supply -= buy_amount;
     // transfer fee to recipient
-    system_program::transfer(
-        CpiContext::new(
-            accounts.system_program.to_account_info(),
-            system_program::Transfer {
-                from: accounts.native_vault.to_account_info(),
-                to: accounts.fee_recipient.to_account_info(),
-            },
-        )
-        .with_signer(&[&accounts.market.native_vault_seeds()]),
+    accounts.market.transfer_native_from_vault(
+        accounts.market.to_account_info(),
+        &accounts.native_vault,
+        fee_destination(&accounts.fee_recipient, quote.as_ref()),
         fee,
+        &accounts.system_program,
+        quote.as_ref(),
     )?;
-    // transfer token to black hole
     let market_account_info = accounts.market.to_account_info().clone();
-    accounts.market.transfer_token_to_recipient(
-        buy_amount,
-        &accounts.config,
-        market_account_info,
-        &accounts.token_vault,
-        &accounts.token_recipient,
-        &accounts.token_mint,
-        &accounts.token_program,
-        Some(&accounts.extra_account_meta_list),
-        Some(&accounts.hooks_program),
-        Some(accounts.burn_program.to_account_info()),
-    )?;
+    if args.burn {
+        accounts.market.burn_token_from_vault(
+            buy_amount,
+            market_account_info,
+            &accounts.token_vault,
+            &accounts.token_mint,
+            &accounts.token_program,
+        )?;
+    } else {
+        // transfer token to black hole
+        accounts.market.transfer_token_to_recipient(
+            buy_amount,
+            &accounts.config,
+            market_account_info,
+            &accounts.token_vault,
+            &accounts.token_recipient,
+            &accounts.token_mint,
+            &accounts.token_program,
+            hook.extra_account_meta_list,
+            hook.hooks_program,
+            hook.burn_program,
+        )?;
+    }
     msg!(
-        "buy_amount:{},pay_amount:{},fee:{},remaining_supply:{}",
+        "buy_amount:{},pay_amount:{},fee:{},remaining_supply:{},total_burned:{}",
         buy_amount,
         pay_amount,
         fee,
         accounts.market.remaining_supply,
+        accounts.market.total_burned,
     );
 
     Ok(())
@@ -0,0 +1,72 @@
+use crate::constants::BLACK_HOLE;
+use crate::errors::Error;
+use crate::state::*;
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount};
+
+#[derive(Accounts)]
+pub struct VerifyMarket<'info> {
+    #[account(
+        has_one = native_vault @ Error::NativeVaultAccountMismatch,
+        has_one = token_mint @ Error::TokenMintAccountMismatch,
+        has_one = token_vault @ Error::TokenVaultAccountMismatch,
+    )]
+    pub market: Box<Account<'info, Market>>,
+    /// CHECK: Already checked in the market.
+    pub native_vault: UncheckedAccount<'info>,
+    pub token_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    pub token_mint: Box<InterfaceAccount<'info, Mint>>,
+    /// The black hole's token account, whose tokens are not required in the reserve.
+    /// Without it, they are counted as sold back, which reports a buy-burn market as short of reserve.
+    #[account(token::mint = token_mint, token::authority = BLACK_HOLE)]
+    pub black_hole: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct VerifyMarketArgs {
+    /// Fails with `MarketUnhealthy` instead of returning the report if the market is not healthy.
+    pub require_healthy: bool,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct MarketReport {
+    pub remaining_supply: u64,
+    pub token_vault_amount: u64,
+    /// The balance of the native vault above the rent-exempt minimum, in quote tokens if quoted in a token.
+    pub native_vault_balance: u64,
+    /// The balance required to buy back all the sold tokens, except the ones held by the black hole or burned.
+    pub required_reserve: u64,
+    /// `token_vault_amount == remaining_supply`
+    pub token_vault_matches: bool,
+    /// `native_vault_balance >= required_reserve`
+    pub reserve_sufficient: bool,
+    pub mint_authority_revoked: bool,
+    /// Whether all the checks above pass.
+    pub healthy: bool,
+}
+
+pub fn handler(ctx: Context<VerifyMarket>, args: VerifyMarketArgs) -> Result<MarketReport> {
+    let accounts = ctx.accounts;
+    let market = &accounts.market;
+
+    let native_vault_balance = market.native_vault_balance(&accounts.native_vault, &Rent::get()?)?;
+    let black_hole_amount = accounts.black_hole.as_ref().map_or(0, |black_hole| black_hole.amount);
+    let required_reserve = market.required_reserve(black_hole_amount)?;
+    let token_vault_matches = accounts.token_vault.amount == market.remaining_supply;
+    let reserve_sufficient = native_vault_balance >= required_reserve;
+    let mint_authority_revoked = accounts.token_mint.mint_authority.is_none();
+
+    let report = MarketReport {
+        remaining_supply: market.remaining_supply,
+        token_vault_amount: accounts.token_vault.amount,
+        native_vault_balance,
+        required_reserve,
+        token_vault_matches,
+        reserve_sufficient,
+        mint_authority_revoked,
+        healthy: token_vault_matches && reserve_sufficient && mint_authority_revoked,
+    };
+    msg!("{:?}", report);
+    require!(!args.require_healthy || report.healthy, Error::MarketUnhealthy);
+    Ok(report)
+}
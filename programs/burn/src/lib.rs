@@ -5,6 +5,9 @@ pub mod errors;
 pub mod instructions;
 pub mod math;
 pub mod state;
+pub mod trade_guard;
+pub mod voucher;
+pub mod wsol;
 
 use crate::instructions::*;
 
@@ -33,6 +36,11 @@ pub mod burn {
         set_fee_recipient::handler(ctx, fee_recipient)
     }
 
+    /// Enables or disables buy-burn on a market, signed by the config authority.
+    pub fn set_market_buy_burn_enabled(ctx: Context<SetMarketBuyBurnEnabled>, enabled: bool) -> Result<()> {
+        set_market_buy_burn_enabled::handler(ctx, enabled)
+    }
+
     /// Initializes a new market.
     pub fn initialize_market<'a, 'b, 'c, 'info>(
         ctx: Context<'a, 'b, 'c, 'info, InitializeMarket<'info>>,
@@ -48,6 +56,25 @@ pub mod burn {
         initialize_market::handler_initialize_transfer_hook_market(ctx, args)
     }
 
+    /// Initializes a new market whose mint is owned by the SPL Token program,
+    /// with Metaplex metadata instead of the Token-2022 metadata extension.
+    pub fn initialize_legacy_token_market<'a, 'b, 'c, 'info>(
+        ctx: Context<'a, 'b, 'c, 'info, InitializeLegacyTokenMarket<'info>>,
+        args: InitializeMarketArgs,
+    ) -> Result<()> {
+        initialize_market::handler_initialize_legacy_token_market(ctx, args)
+    }
+
+    /// Migrates a market created before the market was versioned to the current layout, paid by the payer.
+    pub fn migrate_market(ctx: Context<MigrateMarket>) -> Result<()> {
+        migrate_market::handler(ctx)
+    }
+
+    /// Updates the token metadata of a market, signed by the config authority or the market creator.
+    pub fn update_market_metadata(ctx: Context<UpdateMarketMetadata>, args: UpdateMarketMetadataArgs) -> Result<()> {
+        update_market_metadata::handler(ctx, args)
+    }
+
     pub fn buy_token<'a, 'b, 'c, 'info>(
         ctx: Context<'a, 'b, 'c, 'info, BuyToken<'info>>,
         args: buy_token::BuyTokenArgs,
@@ -69,10 +96,66 @@ pub mod burn {
         sell_token::handler(ctx, args)
     }
 
+    /// Sells on one market and buys on another market of the same config with the proceeds.
+    pub fn swap_markets<'a, 'b, 'c, 'info>(
+        ctx: Context<'a, 'b, 'c, 'info, SwapMarkets<'info>>,
+        args: SwapMarketsArgs,
+    ) -> Result<()> {
+        swap_markets::handler(ctx, args)
+    }
+
+    /// Same as `buy_token`, but pays with wrapped SOL.
+    pub fn buy_token_wsol<'a, 'b, 'c, 'info>(
+        ctx: Context<'a, 'b, 'c, 'info, BuyTokenWsol<'info>>,
+        args: buy_token::BuyTokenArgs,
+    ) -> Result<()> {
+        buy_token_wsol::handler(ctx, args)
+    }
+
+    /// Same as `buy_token_exact_in`, but pays with wrapped SOL.
+    pub fn buy_token_exact_in_wsol<'a, 'b, 'c, 'info>(
+        ctx: Context<'a, 'b, 'c, 'info, BuyTokenExactInWsol<'info>>,
+        args: buy_token_exact_in::BuyTokenExactInArgs,
+    ) -> Result<()> {
+        buy_token_exact_in_wsol::handler(ctx, args)
+    }
+
+    /// Same as `sell_token`, but receives wrapped SOL.
+    pub fn sell_token_wsol<'a, 'b, 'c, 'info>(
+        ctx: Context<'a, 'b, 'c, 'info, SellTokenWsol<'info>>,
+        args: sell_token::SellTokenArgs,
+    ) -> Result<()> {
+        sell_token_wsol::handler(ctx, args)
+    }
+
     pub fn create_burn_account(ctx: Context<CreateBurnAccount>) -> Result<()> {
         create_burn_account::handler(ctx)
     }
 
+    /// Closes a burn account, signed by the owner and co-signed or vouched by the buy-burn authority.
+    ///
+    /// The account is kept as a `ClosedBurnAccount` tombstone so that it can not be created again
+    /// and replay old vouchers: the rent of the tombstone (about 0.00114 SOL) stays locked in it for good,
+    /// only the rest of the rent is refunded to the owner.
+    pub fn close_burn_account(ctx: Context<CloseBurnAccount>, args: CloseBurnAccountArgs) -> Result<()> {
+        close_burn_account::handler(ctx, args)
+    }
+
+    /// Re-creates a closed burn account with the state it was closed with, paid by the payer.
+    pub fn reopen_burn_account(ctx: Context<ReopenBurnAccount>) -> Result<()> {
+        reopen_burn_account::handler(ctx)
+    }
+
+    /// Extends a burn account created before the burn stats were added to the current layout, paid by the payer.
+    pub fn migrate_burn_account(ctx: Context<MigrateBurnAccount>) -> Result<()> {
+        migrate_burn_account::handler(ctx)
+    }
+
+    /// Creates the burn leaderboard of a market, updated by buy-burn instructions sending the tokens to the black hole.
+    pub fn create_burn_leaderboard(ctx: Context<CreateBurnLeaderboard>) -> Result<()> {
+        create_burn_leaderboard::handler(ctx)
+    }
+
     pub fn buy_burn<'a, 'b, 'c, 'info>(
         ctx: Context<'a, 'b, 'c, 'info, BuyBurn<'info>>,
         args: buy_burn::BuyBurnArgs,
@@ -87,7 +170,48 @@ pub mod burn {
         buy_burn_exact_in::handler(ctx, args)
     }
 
+    /// Same as `buy_burn`, but authorized by a voucher signed by the buy-burn authority
+    /// and verified through the Ed25519 program instruction right before this one.
+    pub fn buy_burn_with_voucher<'a, 'b, 'c, 'info>(
+        ctx: Context<'a, 'b, 'c, 'info, BuyBurnWithVoucher<'info>>,
+        args: buy_burn_with_voucher::BuyBurnWithVoucherArgs,
+    ) -> Result<()> {
+        buy_burn_with_voucher::handler(ctx, args)
+    }
+
+    /// Buys for many burn accounts at once, authorized by a single voucher signed by the buy-burn authority.
+    /// The burn accounts are passed through the remaining accounts.
+    pub fn batch_buy_burn<'a, 'b, 'c: 'info, 'info>(
+        ctx: Context<'a, 'b, 'c, 'info, BatchBuyBurn<'info>>,
+        args: batch_buy_burn::BatchBuyBurnArgs,
+    ) -> Result<()> {
+        batch_buy_burn::handler(ctx, args)
+    }
+
+    /// Creates the schedule that limits `use_funds_buy_burn` on a market, signed by the config authority.
+    pub fn create_buy_burn_schedule(ctx: Context<CreateBuyBurnSchedule>, args: BuyBurnScheduleArgs) -> Result<()> {
+        create_buy_burn_schedule::handler(ctx, args)
+    }
+
+    /// Updates the schedule of `use_funds_buy_burn`, signed by the config authority.
+    pub fn set_buy_burn_schedule(ctx: Context<SetBuyBurnSchedule>, args: BuyBurnScheduleArgs) -> Result<()> {
+        set_buy_burn_schedule::handler(ctx, args)
+    }
+
+    /// Buys the token with the surplus of the market and sends it to the black hole (or burns it).
+    /// Permissionless if the schedule of the market allows it, in which case the funds available
+    /// to the call are spent at once, at an average price up to the max price of the schedule.
     pub fn use_funds_buy_burn<'info>(ctx: Context<UseFundsBuyBurn<'info>>, args: UseFundsBuyBurnArgs) -> Result<()> {
         use_funds_buy_burn::handler(ctx, args)
     }
+
+    /// Checks the invariants of a market and returns the report through the return data.
+    pub fn verify_market(ctx: Context<VerifyMarket>, args: VerifyMarketArgs) -> Result<MarketReport> {
+        verify_market::handler(ctx, args)
+    }
+
+    /// Sends the surplus of a market over its curve reserves to the fee recipient and burns the stray tokens.
+    pub fn skim_market(ctx: Context<SkimMarket>) -> Result<()> {
+        skim_market::handler(ctx)
+    }
 }
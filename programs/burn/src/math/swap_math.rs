@@ -1,4 +1,7 @@
-use crate::{constants::MAX_TOKEN_SUPPLY, errors::Error as MyError};
+use crate::{
+    constants::{MAX_TOKEN_SUPPLY, NATIVE_DECIMALS},
+    errors::Error as MyError,
+};
 use anchor_lang::error::Error;
 
 use super::{math::ceil_div, token_math};
@@ -141,6 +144,35 @@ pub fn split_pay_amount(max_pay_amount: u64) -> Result<(u64, u64), Error> {
 pub fn compute_fee(amount: u64) -> u64 {
     ceil_div(amount as u128, 100) as u64 // 1%
 }
+
+/// Converts a native amount computed on the curve into an amount of the quote token.
+///
+/// The curve is denominated in lamports, so a quote token with [NATIVE_DECIMALS] decimals
+/// is converted one-to-one. The caller MUST ensure that `quote_decimals` does not exceed
+/// `constants::MAX_QUOTE_DECIMALS`.
+pub fn native_to_quote_amount(native_amount: u64, quote_decimals: u8, round_up: bool) -> Result<u64, Error> {
+    let quote_amount = if quote_decimals >= NATIVE_DECIMALS {
+        native_amount as u128 * 10u128.pow((quote_decimals - NATIVE_DECIMALS) as u32)
+    } else {
+        let divisor = 10u128.pow((NATIVE_DECIMALS - quote_decimals) as u32);
+        if round_up {
+            ceil_div(native_amount as u128, divisor)
+        } else {
+            native_amount as u128 / divisor
+        }
+    };
+    u64::try_from(quote_amount).map_err(|_| MyError::TooMuchNativeTokenRequired.into())
+}
+
+/// Converts an amount of the quote token into the native amount used by the curve, rounding down.
+pub fn quote_to_native_amount(quote_amount: u64, quote_decimals: u8) -> Result<u64, Error> {
+    let native_amount = if quote_decimals >= NATIVE_DECIMALS {
+        quote_amount as u128 / 10u128.pow((quote_decimals - NATIVE_DECIMALS) as u32)
+    } else {
+        quote_amount as u128 * 10u128.pow((NATIVE_DECIMALS - quote_decimals) as u32)
+    };
+    u64::try_from(native_amount).map_err(|_| MyError::TooMuchNativeTokenRequired.into())
+}
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -537,4 +569,28 @@ mod tests {
         assert_eq!(pay, 330);
         assert_eq!(fee, 3);
     }
+
+    #[test]
+    fn test_native_to_quote_amount() {
+        assert_eq!(
+            native_to_quote_amount(1_234_567_891, NATIVE_DECIMALS, true).unwrap(),
+            1_234_567_891
+        );
+        assert_eq!(native_to_quote_amount(1_234_567_891, 6, false).unwrap(), 1_234_567);
+        assert_eq!(native_to_quote_amount(1_234_567_891, 6, true).unwrap(), 1_234_568);
+        assert_eq!(native_to_quote_amount(1_234_567_000, 6, true).unwrap(), 1_234_567);
+        assert_eq!(native_to_quote_amount(1, 12, false).unwrap(), 1_000);
+        assert!(native_to_quote_amount(u64::MAX, 18, false).is_err());
+    }
+
+    #[test]
+    fn test_quote_to_native_amount() {
+        assert_eq!(
+            quote_to_native_amount(1_234_567_891, NATIVE_DECIMALS).unwrap(),
+            1_234_567_891
+        );
+        assert_eq!(quote_to_native_amount(1_234_567, 6).unwrap(), 1_234_567_000);
+        assert_eq!(quote_to_native_amount(1_999, 12).unwrap(), 1);
+        assert!(quote_to_native_amount(u64::MAX, 6).is_err());
+    }
 }
@@ -3,21 +3,146 @@ use anchor_lang::prelude::*;
 
 #[account]
 pub struct BurnAccount {
-    pub nonce: u32,      // 4, Nonce of the buy.
-    pub buy_amount: u64, // 8, Amount of the buy.
+    pub nonce: u32,          // 4, Nonce of the buy.
+    pub buy_amount: u64,     // 8, Amount of the buy.
+    pub total_burned: u64,   // 8, Amount of the token bought to burn.
+    pub total_spent: u64,    // 8, Amount of the native (or quote) token spent, including fees.
+    pub last_burn_time: i64, // 8, Unix timestamp of the last buy-burn.
 }
 
 impl BurnAccount {
-    pub const LEN: usize = 8 + 4 + 8;
+    pub const LEN: usize = 8 + 4 + 8 + 8 + 8 + 8;
+    /// The size of the burn accounts created before the burn stats were added, migrated by `migrate_burn_account`.
+    pub const LEGACY_LEN: usize = 8 + 4 + 8;
 
+    /// Records a buy-burn whose tokens were sent to the `BLACK_HOLE`, the only ones counted as burned.
+    pub fn record_burn(&mut self, burned: u64, spent: u64, now: i64) {
+        self.total_burned = self.total_burned.saturating_add(burned);
+        self.total_spent = self.total_spent.saturating_add(spent);
+        self.last_burn_time = now;
+    }
+
+    /// Only the cumulative buy amount has to increase, so updates prepared concurrently can land in any order
+    /// and a replayed update fails. The nonce is kept as the highest one seen.
     pub fn update_buy_amount(&mut self, next_nonce: u32, next_buy_amount: u64) -> Result<u64> {
-        require!(self.nonce + 1 == next_nonce, Error::NonceUnexpected);
         require!(self.buy_amount < next_buy_amount, Error::BuyAmountUnexpected);
 
         let delta = next_buy_amount - self.buy_amount;
-        self.nonce = next_nonce;
+        self.nonce = self.nonce.max(next_nonce);
         self.buy_amount = next_buy_amount;
         msg!("delta:{}", delta);
         Ok(delta)
     }
 }
+
+/// What is left of a burn account closed by `close_burn_account`.
+///
+/// It keeps the address in use, so that the burn account can not be created again from zero
+/// and replay the vouchers of its old buy amounts, and `reopen_burn_account` resumes from it.
+#[account]
+pub struct ClosedBurnAccount {
+    pub nonce: u32,        // 4
+    pub buy_amount: u64,   // 8
+    pub total_burned: u64, // 8
+    pub total_spent: u64,  // 8
+}
+
+impl ClosedBurnAccount {
+    pub const LEN: usize = 8 + 4 + 8 + 8 + 8;
+
+    pub fn close(burn_account: &BurnAccount) -> Self {
+        Self {
+            nonce: burn_account.nonce,
+            buy_amount: burn_account.buy_amount,
+            total_burned: burn_account.total_burned,
+            total_spent: burn_account.total_spent,
+        }
+    }
+
+    pub fn reopen(&self) -> BurnAccount {
+        BurnAccount {
+            nonce: self.nonce,
+            buy_amount: self.buy_amount,
+            total_burned: self.total_burned,
+            total_spent: self.total_spent,
+            last_burn_time: 0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::Discriminator;
+
+    #[test]
+    fn test_migrated_legacy_burn_account() {
+        let mut data = BurnAccount::DISCRIMINATOR.to_vec();
+        7u32.serialize(&mut data).unwrap();
+        500u64.serialize(&mut data).unwrap();
+        assert_eq!(data.len(), BurnAccount::LEGACY_LEN);
+        assert!(BurnAccount::try_deserialize(&mut data.as_slice()).is_err());
+
+        // extended with zeros by `migrate_burn_account`
+        data.resize(BurnAccount::LEN, 0);
+        let burn_account = BurnAccount::try_deserialize(&mut data.as_slice()).unwrap();
+        assert_eq!(burn_account.nonce, 7);
+        assert_eq!(burn_account.buy_amount, 500);
+        assert_eq!(burn_account.total_burned, 0);
+        assert_eq!(burn_account.total_spent, 0);
+        assert_eq!(burn_account.last_burn_time, 0);
+    }
+
+    #[test]
+    fn test_closed_burn_account() {
+        let mut burn_account = BurnAccount {
+            nonce: 3,
+            buy_amount: 450,
+            total_burned: 450,
+            total_spent: 1_000,
+            last_burn_time: 1_700_000_000,
+        };
+        let mut data = vec![];
+        ClosedBurnAccount::close(&burn_account)
+            .try_serialize(&mut data)
+            .unwrap();
+        assert_eq!(data.len(), ClosedBurnAccount::LEN);
+        // neither a burn account nor a legacy one to migrate
+        assert_ne!(ClosedBurnAccount::LEN, BurnAccount::LEGACY_LEN);
+        assert!(BurnAccount::try_deserialize(&mut data.as_slice()).is_err());
+
+        let mut reopened = ClosedBurnAccount::try_deserialize(&mut data.as_slice())
+            .unwrap()
+            .reopen();
+        assert_eq!(reopened.nonce, 3);
+        assert_eq!(reopened.buy_amount, 450);
+        assert_eq!(reopened.total_burned, 450);
+        assert_eq!(reopened.total_spent, 1_000);
+        assert_eq!(reopened.last_burn_time, 0);
+        // the old buy amounts can not be replayed
+        assert!(reopened.update_buy_amount(3, 450).is_err());
+        assert!(burn_account.update_buy_amount(3, 450).is_err());
+    }
+
+    #[test]
+    fn test_update_buy_amount() {
+        let mut burn_account = BurnAccount {
+            nonce: 0,
+            buy_amount: 0,
+            total_burned: 0,
+            total_spent: 0,
+            last_burn_time: 0,
+        };
+        // updates landing out of order
+        assert_eq!(burn_account.update_buy_amount(2, 300).unwrap(), 300);
+        assert!(burn_account.update_buy_amount(1, 100).is_err());
+        // replayed update
+        assert!(burn_account.update_buy_amount(2, 300).is_err());
+        assert_eq!(burn_account.update_buy_amount(3, 450).unwrap(), 150);
+        assert_eq!(burn_account.nonce, 3);
+
+        burn_account.nonce = u32::MAX;
+        assert_eq!(burn_account.update_buy_amount(0, 500).unwrap(), 50);
+        assert_eq!(burn_account.nonce, u32::MAX);
+    }
+}
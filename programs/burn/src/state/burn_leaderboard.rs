@@ -0,0 +1,97 @@
+use crate::constants::BURN_LEADERBOARD_SIZE;
+use anchor_lang::prelude::*;
+use std::cmp::Reverse;
+
+/// The top burners of a market, sorted by `total_burned` in descending order.
+///
+/// Best-effort: it is only updated by the buy-burns it is passed to,
+/// so an owner's entry may lag behind its burn account until its next buy-burn with the leaderboard.
+#[account]
+pub struct BurnLeaderboard {
+    pub market: Pubkey,                     // 32
+    pub entries: Vec<BurnLeaderboardEntry>, // 4 + BURN_LEADERBOARD_SIZE * 40
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BurnLeaderboardEntry {
+    pub owner: Pubkey,     // 32
+    pub total_burned: u64, // 8
+}
+
+impl BurnLeaderboard {
+    pub const LEN: usize = 8 + 32 + 4 + BURN_LEADERBOARD_SIZE * (32 + 8);
+
+    /// Updates the entry of the owner with its new `total_burned`,
+    /// dropping the last entry if the leaderboard is full.
+    pub fn update(&mut self, owner: Pubkey, total_burned: u64) {
+        if let Some(entry) = self.entries.iter_mut().find(|entry| entry.owner == owner) {
+            entry.total_burned = total_burned;
+        } else if self.entries.len() < BURN_LEADERBOARD_SIZE {
+            self.entries.push(BurnLeaderboardEntry { owner, total_burned });
+        } else if self.entries.last().is_some_and(|last| last.total_burned < total_burned) {
+            *self.entries.last_mut().unwrap() = BurnLeaderboardEntry { owner, total_burned };
+        } else {
+            return;
+        }
+        // stable sort keeps the earlier burner ahead on ties
+        self.entries.sort_by_key(|entry| Reverse(entry.total_burned));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn setup_leaderboard() -> BurnLeaderboard {
+        BurnLeaderboard {
+            market: Pubkey::new_unique(),
+            entries: vec![],
+        }
+    }
+
+    #[test]
+    fn test_update_inserts_sorted() {
+        let mut l = setup_leaderboard();
+        let (a, b, c) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        l.update(a, 10);
+        l.update(b, 30);
+        l.update(c, 20);
+        let owners: Vec<Pubkey> = l.entries.iter().map(|e| e.owner).collect();
+        assert_eq!(owners, vec![b, c, a]);
+    }
+
+    #[test]
+    fn test_update_existing_owner() {
+        let mut l = setup_leaderboard();
+        let (a, b) = (Pubkey::new_unique(), Pubkey::new_unique());
+        l.update(a, 10);
+        l.update(b, 20);
+        l.update(a, 30);
+        assert_eq!(l.entries.len(), 2);
+        assert_eq!(
+            l.entries[0],
+            BurnLeaderboardEntry {
+                owner: a,
+                total_burned: 30
+            }
+        );
+    }
+
+    #[test]
+    fn test_update_when_full() {
+        let mut l = setup_leaderboard();
+        for i in 0..BURN_LEADERBOARD_SIZE {
+            l.update(Pubkey::new_unique(), 10 + i as u64);
+        }
+        let lowest = Pubkey::new_unique();
+        l.update(lowest, 10);
+        assert_eq!(l.entries.len(), BURN_LEADERBOARD_SIZE);
+        assert!(l.entries.iter().all(|e| e.owner != lowest));
+
+        let highest = Pubkey::new_unique();
+        l.update(highest, 100);
+        assert_eq!(l.entries.len(), BURN_LEADERBOARD_SIZE);
+        assert_eq!(l.entries[0].owner, highest);
+        assert_eq!(l.entries.last().unwrap().total_burned, 11);
+    }
+}
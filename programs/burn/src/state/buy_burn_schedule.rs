@@ -0,0 +1,176 @@
+use crate::errors::Error;
+use crate::trade_guard::TradeGuard;
+use anchor_lang::prelude::*;
+
+/// Limits how fast `use_funds_buy_burn` can spend the funds of a market.
+#[account]
+pub struct BuyBurnSchedule {
+    pub market: Pubkey, // 32
+    /// Max amount of the native (or quote) token to spend per window, including fees.
+    pub max_amount_per_window: u64, // 8
+    /// Max amount of the native (or quote) token to spend per buy-burn, including fees.
+    pub max_amount_per_call: u64, // 8
+    /// Max average price of a buy-burn, in the native (or quote) token's smallest unit per whole token,
+    /// including fees, `0` means no limit. Required if the schedule is permissionless.
+    pub max_price: u64, // 8
+    /// Length of the window in slots, `0` means the window is an epoch.
+    pub window_slots: u64, // 8
+    /// Min slots between two buy-burns.
+    pub min_interval_slots: u64, // 8
+    /// Whether anyone can call `use_funds_buy_burn`, not only the buy-burn authority.
+    pub permissionless: bool, // 1
+    /// The epoch or the index of the slot window of `spent_in_window`.
+    pub window: u64, // 8
+    pub spent_in_window: u64, // 8
+    /// Slot of the last buy-burn, `0` if never.
+    pub last_slot: u64, // 8
+}
+
+impl BuyBurnSchedule {
+    pub const LEN: usize = 8 + 32 + 8 * 5 + 1 + 8 * 3;
+
+    /// Fails if the spend per buy-burn is not capped, or if a permissionless schedule has no max price,
+    /// so that anyone calling it can't make the market buy at any price.
+    pub fn configure(
+        &mut self,
+        max_amount_per_window: u64,
+        max_amount_per_call: u64,
+        max_price: u64,
+        window_slots: u64,
+        min_interval_slots: u64,
+        permissionless: bool,
+    ) -> Result<()> {
+        require!(max_amount_per_call > 0, Error::AmountCannotBeZero);
+        require!(!permissionless || max_price > 0, Error::AmountCannotBeZero);
+        self.max_amount_per_window = max_amount_per_window;
+        self.max_amount_per_call = max_amount_per_call;
+        self.max_price = max_price;
+        self.window_slots = window_slots;
+        self.min_interval_slots = min_interval_slots;
+        self.permissionless = permissionless;
+        Ok(())
+    }
+
+    fn window_at(&self, clock: &Clock) -> u64 {
+        clock.slot.checked_div(self.window_slots).unwrap_or(clock.epoch)
+    }
+
+    /// Returns the amount that can be spent by the next buy-burn, capped by what is left in the current window,
+    /// fails if the min interval since the last buy-burn has not passed.
+    pub fn available(&self, clock: &Clock) -> Result<u64> {
+        require!(
+            self.last_slot == 0 || clock.slot >= self.last_slot.saturating_add(self.min_interval_slots),
+            Error::BuyBurnTooEarly
+        );
+        let available = if self.window_at(clock) != self.window {
+            self.max_amount_per_window
+        } else {
+            self.max_amount_per_window.saturating_sub(self.spent_in_window)
+        };
+        Ok(available.min(self.max_amount_per_call))
+    }
+
+    /// Checks the average price of buying `token_amount` for `spent`, including fees.
+    pub fn check_price(&self, token_amount: u64, spent: u64) -> Result<()> {
+        TradeGuard {
+            deadline: None,
+            limit_price: (self.max_price > 0).then_some(self.max_price),
+        }
+        .check_buy_price(token_amount, spent)
+    }
+
+    /// Records the amount spent by a buy-burn.
+    pub fn record(&mut self, spent: u64, clock: &Clock) {
+        let window = self.window_at(clock);
+        if window != self.window {
+            self.window = window;
+            self.spent_in_window = 0;
+        }
+        self.spent_in_window = self.spent_in_window.saturating_add(spent);
+        self.last_slot = clock.slot;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn setup_schedule(window_slots: u64, min_interval_slots: u64) -> BuyBurnSchedule {
+        BuyBurnSchedule {
+            market: Pubkey::new_unique(),
+            max_amount_per_window: 100,
+            max_amount_per_call: 100,
+            max_price: 0,
+            window_slots,
+            min_interval_slots,
+            permissionless: false,
+            window: 0,
+            spent_in_window: 0,
+            last_slot: 0,
+        }
+    }
+
+    fn clock(slot: u64, epoch: u64) -> Clock {
+        Clock {
+            slot,
+            epoch,
+            ..Clock::default()
+        }
+    }
+
+    #[test]
+    fn test_available_in_slot_window() {
+        let mut s = setup_schedule(1_000, 0);
+        assert_eq!(s.available(&clock(1_500, 0)).unwrap(), 100);
+        s.record(60, &clock(1_500, 0));
+        assert_eq!(s.available(&clock(1_999, 0)).unwrap(), 40);
+        s.record(40, &clock(1_999, 0));
+        assert_eq!(s.available(&clock(1_999, 0)).unwrap(), 0);
+        assert_eq!(s.available(&clock(2_000, 0)).unwrap(), 100);
+    }
+
+    #[test]
+    fn test_available_in_epoch_window() {
+        let mut s = setup_schedule(0, 0);
+        s.record(100, &clock(10, 3));
+        assert_eq!(s.available(&clock(500_000, 3)).unwrap(), 0);
+        assert_eq!(s.available(&clock(500_000, 4)).unwrap(), 100);
+    }
+
+    #[test]
+    fn test_available_before_min_interval() {
+        let mut s = setup_schedule(1_000, 10);
+        assert!(s.available(&clock(5, 0)).is_ok());
+        s.record(10, &clock(5, 0));
+        assert!(s.available(&clock(14, 0)).is_err());
+        assert_eq!(s.available(&clock(15, 0)).unwrap(), 90);
+    }
+
+    #[test]
+    fn test_available_per_call() {
+        let mut s = setup_schedule(1_000, 0);
+        s.max_amount_per_call = 30;
+        assert_eq!(s.available(&clock(1_500, 0)).unwrap(), 30);
+        s.record(80, &clock(1_500, 0));
+        assert_eq!(s.available(&clock(1_500, 0)).unwrap(), 20);
+    }
+
+    #[test]
+    fn test_check_price() {
+        let mut s = setup_schedule(1_000, 0);
+        assert!(s.check_price(1, u64::MAX).is_ok());
+        s.max_price = 2_000;
+        assert!(s.check_price(3_000_000, 6_000).is_ok());
+        assert!(s.check_price(3_000_000, 6_001).is_err());
+    }
+
+    #[test]
+    fn test_configure() {
+        let mut s = setup_schedule(1_000, 0);
+        assert!(s.configure(100, 0, 0, 1_000, 0, false).is_err());
+        assert!(s.configure(100, 10, 0, 1_000, 0, true).is_err());
+        assert!(s.configure(100, 10, 0, 1_000, 0, false).is_ok());
+        assert!(s.configure(100, 10, 2_000, 1_000, 0, true).is_ok());
+        assert_eq!(s.max_price, 2_000);
+    }
+}
@@ -1,12 +1,18 @@
 use crate::constants::{
-    DECIMALS, FREE_TRANSFER_THRESHOLD, MARKET_SEED, MARKET_VAULT_SEED, SYMBOL_BURN, SYMBOL_MAX_LEN, SYMBOL_MIN_LEN,
+    DECIMALS, FREE_TRANSFER_THRESHOLD, HOOKS_PROGRAM_ID, MARKET_RESERVED_SPACE, MARKET_SEED, MARKET_VAULT_SEED,
+    MARKET_VERSION, MAX_QUOTE_DECIMALS, NATIVE_DECIMALS, SYMBOL_BURN, SYMBOL_MAX_LEN, SYMBOL_MIN_LEN,
 };
 use crate::math::swap_math;
 use crate::state::*;
+use crate::trade_guard::TradeGuard;
 use crate::{constants::MAX_TOKEN_SUPPLY, errors::Error};
 use anchor_lang::solana_program::program::{invoke, invoke_signed};
 use anchor_lang::{prelude::*, system_program};
-use anchor_spl::token_2022::spl_token_2022;
+use anchor_spl::associated_token::{self, get_associated_token_address_with_program_id, AssociatedToken};
+use anchor_spl::token_2022::spl_token_2022::{
+    self,
+    extension::{BaseStateWithExtensions, ExtensionType, StateWithExtensions},
+};
 use anchor_spl::token_interface::{self, Mint, TokenAccount, TokenInterface};
 use spl_transfer_hook_interface::onchain::add_extra_accounts_for_execute_cpi;
 
@@ -16,7 +22,8 @@ pub struct Market {
     pub token_mint: Pubkey, // 32
     /// The vault that holds the token.
     pub token_vault: Pubkey, // 32
-    /// The vault that holds the native token(SOL).
+    /// The vault that holds the native token(SOL), or the market's quote token account
+    /// if the market is quoted in a token.
     pub native_vault: Pubkey, // 32
     pub remaining_supply: u64, // 8
     pub symbol: String,     // 4 + 10
@@ -25,11 +32,168 @@ pub struct Market {
     pub transfer_hook_enabled: bool, // 1
     /// Whether the market is allowed to transfer tokens.
     pub free_transfer_allowed: bool, // 1
+    /// The token program that owns the mint, either SPL Token or Token-2022.
+    pub token_program: Pubkey, // 32
+    /// The mint of the quote token, `Pubkey::default()` if the market is quoted in SOL.
+    pub quote_mint: Pubkey, // 32
+    /// Decimals of the quote token, `NATIVE_DECIMALS` if the market is quoted in SOL.
+    pub quote_decimals: u8, // 1
+    /// The account that created the market, allowed to update the token metadata.
+    pub creator: Pubkey, // 32
+    /// Whether buy-burn is enabled on the market by the config authority.
+    pub buy_burn_enabled: bool, // 1
+    /// The amount of the token burned from the token vault.
+    pub total_burned: u64, // 8
+    /// Whether `use_funds_buy_burn` is limited by a `BuyBurnSchedule`.
+    pub buy_burn_scheduled: bool, // 1
+    /// The layout version of the market, markets created before the version was added
+    /// are migrated by `migrate_market`.
+    pub version: u8, // 1
+}
+
+/// The layout of the markets created before the market was versioned.
+#[derive(AnchorDeserialize)]
+pub struct LegacyMarket {
+    pub config: Pubkey,
+    pub token_mint: Pubkey,
+    pub token_vault: Pubkey,
+    pub native_vault: Pubkey,
+    pub remaining_supply: u64,
+    pub symbol: String,
+    pub bump: [u8; 1],
+    pub native_vault_bump: [u8; 1],
+    pub transfer_hook_enabled: bool,
+    pub free_transfer_allowed: bool,
+}
+
+impl LegacyMarket {
+    pub const LEN: usize = 8 + 32 * 4 + 8 + (4 + 10) + 4;
+
+    /// Converts the legacy market to the current layout.
+    ///
+    /// The legacy markets were all created with a Token-2022 mint, quoted in SOL and without a creator,
+    /// only the BURN market has buy-burn enabled.
+    pub fn migrate(self) -> Market {
+        Market {
+            config: self.config,
+            token_mint: self.token_mint,
+            token_vault: self.token_vault,
+            native_vault: self.native_vault,
+            remaining_supply: self.remaining_supply,
+            buy_burn_enabled: self.symbol == SYMBOL_BURN,
+            symbol: self.symbol,
+            bump: self.bump,
+            native_vault_bump: self.native_vault_bump,
+            transfer_hook_enabled: self.transfer_hook_enabled,
+            free_transfer_allowed: self.free_transfer_allowed,
+            token_program: spl_token_2022::ID,
+            quote_mint: Pubkey::default(),
+            quote_decimals: NATIVE_DECIMALS,
+            creator: Pubkey::default(),
+            total_burned: 0,
+            buy_burn_scheduled: false,
+            version: MARKET_VERSION,
+        }
+    }
+}
+
+/// Accounts used to move the quote token of a market that is not quoted in SOL.
+pub struct QuoteAccounts<'b, 'info> {
+    pub quote_mint: &'b InterfaceAccount<'info, Mint>,
+    /// The payer's quote token account, only required when paying.
+    pub quote_token_payer: Option<&'b InterfaceAccount<'info, TokenAccount>>,
+    pub fee_recipient_quote_account: &'b InterfaceAccount<'info, TokenAccount>,
+    pub quote_token_program: &'b Interface<'info, TokenInterface>,
+}
+
+impl<'b, 'info> QuoteAccounts<'b, 'info> {
+    /// Collects the quote accounts of the market, returns `None` if the market is quoted in SOL.
+    pub fn load(
+        market: &Market,
+        quote_mint: Option<&'b InterfaceAccount<'info, Mint>>,
+        quote_token_payer: Option<&'b InterfaceAccount<'info, TokenAccount>>,
+        fee_recipient_quote_account: Option<&'b InterfaceAccount<'info, TokenAccount>>,
+        quote_token_program: Option<&'b Interface<'info, TokenInterface>>,
+    ) -> Result<Option<Self>> {
+        if !market.is_quoted_in_token() {
+            return Ok(None);
+        }
+        let quote_mint = quote_mint.ok_or(Error::QuoteMintIsMissing)?;
+        require!(quote_mint.key() == market.quote_mint, Error::QuoteMintAccountMismatch);
+        Ok(Some(Self {
+            quote_mint,
+            quote_token_payer,
+            fee_recipient_quote_account: fee_recipient_quote_account.ok_or(Error::FeeRecipientQuoteAccountIsMissing)?,
+            quote_token_program: quote_token_program.ok_or(Error::QuoteTokenProgramIsMissing)?,
+        }))
+    }
+}
+
+/// Accounts required to invoke the transfer hook, passed through the remaining accounts or as named accounts.
+pub struct TransferHookAccounts<'c, 'info> {
+    pub extra_account_meta_list: Option<&'c AccountInfo<'info>>,
+    pub hooks_program: Option<&'c AccountInfo<'info>>,
+    pub burn_program: Option<AccountInfo<'info>>,
+}
+
+impl<'c, 'info> TransferHookAccounts<'c, 'info> {
+    /// Takes the transfer hook accounts from `remaining_accounts` if the market has the transfer hook enabled.
+    pub fn load(
+        market: &Market,
+        remaining_accounts: &mut impl Iterator<Item = &'c AccountInfo<'info>>,
+        program_id: &Pubkey,
+    ) -> Result<Self> {
+        if !market.transfer_hook_enabled {
+            return Ok(Self {
+                extra_account_meta_list: None,
+                hooks_program: None,
+                burn_program: None,
+            });
+        }
+
+        let extra_account_meta_list = remaining_accounts
+            .next()
+            .ok_or(Error::ExtraAccountMetaListAccountIsMissing)?;
+        let hooks_program = remaining_accounts.next().ok_or(Error::HooksProgramIsMissing)?;
+        require!(hooks_program.key() == HOOKS_PROGRAM_ID, Error::HooksProgramIsIncorrect);
+        let burn_program = remaining_accounts.next().cloned().ok_or(Error::BurnProgramIsMissing)?;
+        require!(burn_program.key() == *program_id, Error::BurnProgramIsIncorrect);
+
+        Ok(Self {
+            extra_account_meta_list: Some(extra_account_meta_list),
+            hooks_program: Some(hooks_program),
+            burn_program: Some(burn_program),
+        })
+    }
+
+    /// Takes the named transfer hook accounts, the optional ones are required if the market has the transfer hook
+    /// enabled and ignored otherwise.
+    pub fn from_accounts(
+        market: &Market,
+        extra_account_meta_list: Option<&'c AccountInfo<'info>>,
+        hooks_program: Option<&'c AccountInfo<'info>>,
+        burn_program: &AccountInfo<'info>,
+    ) -> Result<Self> {
+        if !market.transfer_hook_enabled {
+            return Ok(Self {
+                extra_account_meta_list: None,
+                hooks_program: None,
+                burn_program: None,
+            });
+        }
+
+        Ok(Self {
+            extra_account_meta_list: Some(extra_account_meta_list.ok_or(Error::ExtraAccountMetaListAccountIsMissing)?),
+            hooks_program: Some(hooks_program.ok_or(Error::HooksProgramIsMissing)?),
+            burn_program: Some(burn_program.clone()),
+        })
+    }
 }
 
 pub struct BuyTokenArgs<'b, 'c, 'info> {
     pub buy_amount: u64,
     pub max_pay: u64,
+    pub guard: TradeGuard,
     pub config: &'b Account<'info, Config>,
     pub market: AccountInfo<'info>,
     pub native_vault: &'b UncheckedAccount<'info>,
@@ -44,11 +208,14 @@ pub struct BuyTokenArgs<'b, 'c, 'info> {
     pub extra_account_meta_list: Option<&'c AccountInfo<'info>>,
     pub hooks_program: Option<&'c AccountInfo<'info>>,
     pub burn_program: Option<AccountInfo<'info>>,
+
+    pub quote: Option<QuoteAccounts<'b, 'info>>,
 }
 
 pub struct BuyTokenExactInArgs<'b, 'c, 'info> {
     pub pay_amount: u64,
     pub min_receive: u64,
+    pub guard: TradeGuard,
     pub config: &'b Account<'info, Config>,
     pub market: AccountInfo<'info>,
     pub native_vault: &'b UncheckedAccount<'info>,
@@ -63,18 +230,21 @@ pub struct BuyTokenExactInArgs<'b, 'c, 'info> {
     pub extra_account_meta_list: Option<&'c AccountInfo<'info>>,
     pub hooks_program: Option<&'c AccountInfo<'info>>,
     pub burn_program: Option<AccountInfo<'info>>,
+
+    pub quote: Option<QuoteAccounts<'b, 'info>>,
 }
 
 pub struct SellTokenArgs<'b, 'c, 'info> {
     pub sell_amount: u64,
     pub min_receive: u64,
+    pub guard: TradeGuard,
     pub config: &'b Account<'info, Config>,
     pub market: AccountInfo<'info>,
     pub native_vault: &'b UncheckedAccount<'info>,
     pub fee_recipient: &'b UncheckedAccount<'info>,
     pub token_vault: &'b InterfaceAccount<'info, TokenAccount>,
     pub token_mint: &'b InterfaceAccount<'info, Mint>,
-    pub native_recipient: &'b UncheckedAccount<'info>,
+    pub native_recipient: AccountInfo<'info>,
     pub token_payer: &'b InterfaceAccount<'info, TokenAccount>,
     pub payer: &'b Signer<'info>,
     pub token_program: &'b Interface<'info, TokenInterface>,
@@ -83,10 +253,13 @@ pub struct SellTokenArgs<'b, 'c, 'info> {
     pub extra_account_meta_list: Option<&'c AccountInfo<'info>>,
     pub hooks_program: Option<&'c AccountInfo<'info>>,
     pub burn_program: Option<AccountInfo<'info>>,
+
+    pub quote: Option<QuoteAccounts<'b, 'info>>,
 }
 
 impl Market {
-    pub const LEN: usize = 8 + 32 * 4 + 8 + (4 + 10) + 1 * 4;
+    /// Reserves `MARKET_RESERVED_SPACE` bytes, so that new fields can be appended without a realloc.
+    pub const LEN: usize = 8 + 32 * 4 + 8 + (4 + 10) + 1 * 4 + 32 + 32 + 1 + 32 + 1 + 8 + 1 + 1 + MARKET_RESERVED_SPACE;
 
     pub fn seeds(&self) -> [&[u8]; 4] {
         [
@@ -118,11 +291,16 @@ impl Market {
         self.config = config.to_account_info().key();
         self.token_mint = token_mint.to_account_info().key();
         self.token_vault = token_vault.to_account_info().key();
+        self.token_program = *token_mint.to_account_info().owner;
+        self.quote_mint = Pubkey::default();
+        self.quote_decimals = NATIVE_DECIMALS;
         self.remaining_supply = MAX_TOKEN_SUPPLY;
+        self.buy_burn_enabled = symbol == SYMBOL_BURN;
         self.symbol = symbol;
         self.bump = [bump; 1];
         self.transfer_hook_enabled = transfer_hook_enabled;
         self.free_transfer_allowed = !transfer_hook_enabled;
+        self.version = MARKET_VERSION;
     }
 
     pub fn create_native_vault<'info>(
@@ -164,6 +342,182 @@ impl Market {
         )
     }
 
+    /// Creates the market's associated token account of the quote mint as the native vault.
+    pub fn create_quote_vault<'info>(
+        market: &mut Account<'info, Market>,
+        payer: &Signer<'info>,
+        native_vault: &UncheckedAccount<'info>,
+        quote_mint: &InterfaceAccount<'info, Mint>,
+        quote_token_program: &Interface<'info, TokenInterface>,
+        associated_token_program: &Program<'info, AssociatedToken>,
+        system_program: &Program<'info, System>,
+    ) -> Result<()> {
+        require!(quote_mint.decimals <= MAX_QUOTE_DECIMALS, Error::InvalidQuoteDecimals);
+        require!(Self::is_supported_quote_mint(quote_mint)?, Error::UnsupportedQuoteMint);
+        let native_vault_actual =
+            get_associated_token_address_with_program_id(&market.key(), &quote_mint.key(), &quote_token_program.key());
+        require!(
+            native_vault_actual.as_ref() == native_vault.key().as_ref(),
+            Error::NativeVaultAccountMismatch
+        );
+
+        market.native_vault = native_vault_actual;
+        market.quote_mint = quote_mint.key();
+        market.quote_decimals = quote_mint.decimals;
+
+        associated_token::create(CpiContext::new(
+            associated_token_program.to_account_info(),
+            associated_token::Create {
+                payer: payer.to_account_info(),
+                associated_token: native_vault.to_account_info(),
+                authority: market.to_account_info(),
+                mint: quote_mint.to_account_info(),
+                system_program: system_program.to_account_info(),
+                token_program: quote_token_program.to_account_info(),
+            },
+        ))
+    }
+
+    /// A transfer fee would make the vault receive less than the curve accounts for,
+    /// and a transfer hook could make the transfers out of the vault fail.
+    fn is_supported_quote_mint(quote_mint: &InterfaceAccount<Mint>) -> Result<bool> {
+        let quote_mint = quote_mint.to_account_info();
+        if *quote_mint.owner != spl_token_2022::ID {
+            return Ok(true);
+        }
+        let data = quote_mint.try_borrow_data()?;
+        let mint = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&data)?;
+        Ok(!mint.get_extension_types()?.iter().any(|extension| {
+            matches!(
+                extension,
+                ExtensionType::TransferFeeConfig | ExtensionType::TransferHook
+            )
+        }))
+    }
+
+    /// Returns the key that scopes the burn accounts of the market.
+    ///
+    /// The BURN market keeps the burn accounts seeded by the config, which predate per-market burn accounts.
+    pub fn burn_account_scope(&self, market: &Pubkey) -> Pubkey {
+        if self.symbol == SYMBOL_BURN {
+            self.config
+        } else {
+            *market
+        }
+    }
+
+    /// Returns the amount of the native (or quote) token that the native vault must hold,
+    /// so that all the sold tokens held by users can be sold back to the curve.
+    ///
+    /// The tokens held by the black hole (`black_hole_amount`) or burned can never be sold back.
+    pub fn required_reserve(&self, black_hole_amount: u64) -> Result<u64> {
+        let already_sold = MAX_TOKEN_SUPPLY - self.remaining_supply;
+        let held_by_users = already_sold
+            .saturating_sub(black_hole_amount)
+            .saturating_sub(self.total_burned);
+        if held_by_users == 0 {
+            return Ok(0);
+        }
+        let reserve = swap_math::compute_swap(held_by_users, self.remaining_supply, false)?;
+        let reserve = swap_math::native_to_quote_amount(reserve, self.quote_decimals, true)?;
+        Ok(reserve)
+    }
+
+    /// Whether the market is quoted in a token instead of SOL.
+    pub fn is_quoted_in_token(&self) -> bool {
+        self.quote_mint != Pubkey::default()
+    }
+
+    /// Returns the balance of the native vault that is free to use,
+    /// in lamports above the rent-exempt minimum, or in quote tokens if the market is quoted in a token.
+    pub fn native_vault_balance(&self, native_vault: &AccountInfo, rent: &Rent) -> Result<u64> {
+        if self.is_quoted_in_token() {
+            let vault = TokenAccount::try_deserialize(&mut &native_vault.try_borrow_data()?[..])?;
+            Ok(vault.amount)
+        } else {
+            Ok(native_vault
+                .lamports()
+                .saturating_sub(rent.minimum_balance(native_vault.data_len())))
+        }
+    }
+
+    /// Transfers the native token (or the quote token) from the payer.
+    pub fn transfer_native_from_payer<'b, 'info>(
+        payer: &'b Signer<'info>,
+        to: AccountInfo<'info>,
+        amount: u64,
+        system_program: &'b Program<'info, System>,
+        quote: Option<&QuoteAccounts<'b, 'info>>,
+    ) -> Result<()> {
+        match quote {
+            Some(quote) => token_interface::transfer_checked(
+                CpiContext::new(
+                    quote.quote_token_program.to_account_info(),
+                    token_interface::TransferChecked {
+                        from: quote
+                            .quote_token_payer
+                            .ok_or(Error::QuoteTokenPayerIsMissing)?
+                            .to_account_info(),
+                        mint: quote.quote_mint.to_account_info(),
+                        to,
+                        authority: payer.to_account_info(),
+                    },
+                ),
+                amount,
+                quote.quote_mint.decimals,
+            ),
+            None => system_program::transfer(
+                CpiContext::new(
+                    system_program.to_account_info(),
+                    system_program::Transfer {
+                        from: payer.to_account_info(),
+                        to,
+                    },
+                ),
+                amount,
+            ),
+        }
+    }
+
+    /// Transfers the native token (or the quote token) from the native vault.
+    pub fn transfer_native_from_vault<'b, 'info>(
+        &self,
+        market: AccountInfo<'info>,
+        native_vault: &'b UncheckedAccount<'info>,
+        to: AccountInfo<'info>,
+        amount: u64,
+        system_program: &'b Program<'info, System>,
+        quote: Option<&QuoteAccounts<'b, 'info>>,
+    ) -> Result<()> {
+        match quote {
+            Some(quote) => token_interface::transfer_checked(
+                CpiContext::new(
+                    quote.quote_token_program.to_account_info(),
+                    token_interface::TransferChecked {
+                        from: native_vault.to_account_info(),
+                        mint: quote.quote_mint.to_account_info(),
+                        to,
+                        authority: market,
+                    },
+                )
+                .with_signer(&[&self.seeds()]),
+                amount,
+                quote.quote_mint.decimals,
+            ),
+            None => system_program::transfer(
+                CpiContext::new(
+                    system_program.to_account_info(),
+                    system_program::Transfer {
+                        from: native_vault.to_account_info(),
+                        to,
+                    },
+                )
+                .with_signer(&[&self.native_vault_seeds()]),
+                amount,
+            ),
+        }
+    }
+
     pub fn mint_total_supply_to_market_vault<'info>(
         &self,
         market: &Account<'info, Market>,
@@ -185,41 +539,42 @@ impl Market {
         )
     }
 
+    /// Computes the native amount and the fee to pay for buying `buy_amount` tokens.
+    pub fn compute_buy_token_pay(&self, buy_amount: u64) -> Result<(u64, u64)> {
+        require!(buy_amount > 0, Error::AmountCannotBeZero);
+        require!(buy_amount < self.remaining_supply, Error::BuyAmountTooLarge);
+
+        let native_pay_amount = swap_math::compute_swap(buy_amount, self.remaining_supply, true)?;
+        let native_pay_amount = swap_math::native_to_quote_amount(native_pay_amount, self.quote_decimals, true)?;
+        Ok((native_pay_amount, swap_math::compute_fee(native_pay_amount)))
+    }
+
     pub fn buy_token<'b, 'c, 'info>(&mut self, args: BuyTokenArgs<'b, 'c, 'info>) -> Result<()> {
-        require!(args.buy_amount > 0, Error::AmountCannotBeZero);
-        let remaining_supply = self.remaining_supply;
-        require!(args.buy_amount < remaining_supply, Error::BuyAmountTooLarge);
+        args.guard.check_deadline(&Clock::get()?)?;
+        let (native_pay_amount, fee) = self.compute_buy_token_pay(args.buy_amount)?;
         self.remaining_supply -= args.buy_amount;
         self.change_free_transfer_allowed();
 
-        let native_pay_amount = swap_math::compute_swap(args.buy_amount, remaining_supply, true)?;
-        let fee = swap_math::compute_fee(native_pay_amount);
-
         require!(
             native_pay_amount as u128 + fee as u128 <= args.max_pay as u128,
             Error::PayAmountExceedsMaxPay
         );
+        args.guard.check_buy_price(args.buy_amount, native_pay_amount + fee)?;
 
-        system_program::transfer(
-            CpiContext::new(
-                args.system_program.to_account_info(),
-                system_program::Transfer {
-                    from: args.payer.to_account_info(),
-                    to: args.native_vault.to_account_info(),
-                },
-            ),
+        Self::transfer_native_from_payer(
+            args.payer,
+            args.native_vault.to_account_info(),
             native_pay_amount,
+            args.system_program,
+            args.quote.as_ref(),
         )?;
 
-        system_program::transfer(
-            CpiContext::new(
-                args.system_program.to_account_info(),
-                system_program::Transfer {
-                    from: args.payer.to_account_info(),
-                    to: args.fee_recipient.to_account_info(),
-                },
-            ),
+        Self::transfer_native_from_payer(
+            args.payer,
+            fee_destination(args.fee_recipient, args.quote.as_ref()),
             fee,
+            args.system_program,
+            args.quote.as_ref(),
         )?;
 
         self.transfer_token_to_recipient(
@@ -247,34 +602,31 @@ impl Market {
 
     pub fn buy_token_exact_in<'b, 'c, 'info>(&mut self, args: BuyTokenExactInArgs<'b, 'c, 'info>) -> Result<()> {
         require!(args.pay_amount > 0, Error::AmountCannotBeZero);
+        args.guard.check_deadline(&Clock::get()?)?;
 
         // transfer native token to ensure the payer has enough balance
-        system_program::transfer(
-            CpiContext::new(
-                args.system_program.to_account_info(),
-                system_program::Transfer {
-                    from: args.payer.to_account_info(),
-                    to: args.native_vault.to_account_info(),
-                },
-            ),
+        Self::transfer_native_from_payer(
+            args.payer,
+            args.native_vault.to_account_info(),
             args.pay_amount,
+            args.system_program,
+            args.quote.as_ref(),
         )?;
 
-        let buy_amount = swap_math::compute_buy_token_exact_in(args.pay_amount, self.remaining_supply)?;
+        let curve_pay_amount = swap_math::quote_to_native_amount(args.pay_amount, self.quote_decimals)?;
+        let buy_amount = swap_math::compute_buy_token_exact_in(curve_pay_amount, self.remaining_supply)?;
         require!(buy_amount >= args.min_receive, Error::ReceiveAmountTooSmall);
         self.remaining_supply -= buy_amount;
         self.change_free_transfer_allowed();
 
         let fee = swap_math::compute_fee(args.pay_amount);
-        system_program::transfer(
-            CpiContext::new(
-                args.system_program.to_account_info(),
-                system_program::Transfer {
-                    from: args.payer.to_account_info(),
-                    to: args.fee_recipient.to_account_info(),
-                },
-            ),
+        args.guard.check_buy_price(buy_amount, args.pay_amount + fee)?;
+        Self::transfer_native_from_payer(
+            args.payer,
+            fee_destination(args.fee_recipient, args.quote.as_ref()),
             fee,
+            args.system_program,
+            args.quote.as_ref(),
         )?;
 
         self.transfer_token_to_recipient(
@@ -300,8 +652,10 @@ impl Market {
         Ok(())
     }
 
-    pub fn sell_token<'b, 'c, 'info>(&mut self, args: SellTokenArgs<'b, 'c, 'info>) -> Result<()> {
+    /// Returns the amount of the native (or quote) token received, not including fees.
+    pub fn sell_token<'b, 'c, 'info>(&mut self, args: SellTokenArgs<'b, 'c, 'info>) -> Result<u64> {
         require!(args.sell_amount > 0, Error::AmountCannotBeZero);
+        args.guard.check_deadline(&Clock::get()?)?;
         // transfer token to vault
         // If the transfer is successful here, it means args.sell_amount <= (MAX_TOKEN_SUPPLY - remaining_supply).
         self.transfer_token_to_vault(&args)?;
@@ -310,33 +664,29 @@ impl Market {
         self.remaining_supply += args.sell_amount;
 
         let native_receive_amount = swap_math::compute_swap(args.sell_amount, remaining_supply, false)?;
+        let native_receive_amount =
+            swap_math::native_to_quote_amount(native_receive_amount, self.quote_decimals, false)?;
         let fee = swap_math::compute_fee(native_receive_amount);
         let native_receive_amount = native_receive_amount - fee;
         require!(native_receive_amount >= args.min_receive, Error::ReceiveAmountTooSmall);
+        args.guard.check_sell_price(args.sell_amount, native_receive_amount)?;
 
-        let seeds = self.native_vault_seeds();
         // transfer native token from market
-        system_program::transfer(
-            CpiContext::new(
-                args.system_program.to_account_info(),
-                system_program::Transfer {
-                    from: args.native_vault.to_account_info(),
-                    to: args.native_recipient.to_account_info(),
-                },
-            )
-            .with_signer(&[&seeds]),
+        self.transfer_native_from_vault(
+            args.market.clone(),
+            args.native_vault,
+            args.native_recipient.clone(),
             native_receive_amount,
+            args.system_program,
+            args.quote.as_ref(),
         )?;
-        system_program::transfer(
-            CpiContext::new(
-                args.system_program.to_account_info(),
-                system_program::Transfer {
-                    from: args.native_vault.to_account_info(),
-                    to: args.fee_recipient.to_account_info(),
-                },
-            )
-            .with_signer(&[&seeds]),
+        self.transfer_native_from_vault(
+            args.market.clone(),
+            args.native_vault,
+            fee_destination(args.fee_recipient, args.quote.as_ref()),
             fee,
+            args.system_program,
+            args.quote.as_ref(),
         )?;
 
         msg!(
@@ -346,7 +696,7 @@ impl Market {
             fee,
             self.remaining_supply
         );
-        Ok(())
+        Ok(native_receive_amount)
     }
 
     pub fn revoke_mint_authority<'info>(
@@ -387,23 +737,19 @@ impl Market {
         native_vault: &UncheckedAccount<'info>,
         rent: &Sysvar<'info, Rent>,
     ) -> Result<u64> {
-        let already_sold = MAX_TOKEN_SUPPLY - self.remaining_supply;
         // The number of native tokens that can be received when all the tokens held by the user are sold
-        let mut at_least_native_tokens =
-            swap_math::compute_swap(already_sold - black_hole.amount, self.remaining_supply, false)? as u128;
-        at_least_native_tokens += rent.minimum_balance(native_vault.data_len()) as u128; // should add rent-free tokens
-
-        let available_native_tokens = if (native_vault.lamports() as u128) < at_least_native_tokens {
-            0
-        } else {
-            native_vault.lamports() - at_least_native_tokens as u64
-        };
+        let at_least_native_tokens = self.required_reserve(black_hole.amount)?;
+        // rent-free tokens are already excluded from the balance
+        let available_native_tokens = self
+            .native_vault_balance(native_vault, rent)?
+            .saturating_sub(at_least_native_tokens);
 
         if self.free_transfer_allowed == false {
             // When the remaining token is less than or equal to the threshold
             // and the available native token is less than 0.1 SOL, free transfer is allowed
-            self.free_transfer_allowed =
-                self.remaining_supply <= FREE_TRANSFER_THRESHOLD && available_native_tokens < 100_000_000 as u64;
+            self.free_transfer_allowed = self.remaining_supply <= FREE_TRANSFER_THRESHOLD
+                && available_native_tokens
+                    < swap_math::native_to_quote_amount(100_000_000, self.quote_decimals, false)?;
         }
 
         msg!(
@@ -488,6 +834,42 @@ impl Market {
         Ok(())
     }
 
+    /// Burns the token from the token vault, the amount must already be taken from the remaining supply.
+    pub fn burn_token_from_vault<'info>(
+        &mut self,
+        burn_amount: u64,
+        market: AccountInfo<'info>,
+        token_vault: &InterfaceAccount<'info, TokenAccount>,
+        token_mint: &InterfaceAccount<'info, Mint>,
+        token_program: &Interface<'info, TokenInterface>,
+    ) -> Result<()> {
+        if burn_amount == 0 {
+            return Ok(());
+        }
+
+        let ix = spl_token_2022::instruction::burn_checked(
+            &token_program.key(),
+            &token_vault.key(),
+            &token_mint.key(),
+            &market.key(),
+            &[],
+            burn_amount,
+            DECIMALS,
+        )?;
+        invoke_signed(
+            &ix,
+            &[
+                token_vault.to_account_info(),
+                token_mint.to_account_info(),
+                market,
+                token_program.to_account_info(),
+            ],
+            &[&self.seeds()],
+        )?;
+        self.total_burned += burn_amount;
+        Ok(())
+    }
+
     fn transfer_token_to_vault<'b, 'c, 'info>(&self, args: &SellTokenArgs<'b, 'c, 'info>) -> Result<()> {
         let mut ix = spl_token_2022::instruction::transfer_checked(
             &args.token_program.key(),
@@ -532,9 +914,22 @@ impl Market {
     }
 }
 
+/// Returns the account that receives fees: the fee recipient, or its quote token account
+/// if the market is quoted in a token.
+pub fn fee_destination<'b, 'info>(
+    fee_recipient: &'b UncheckedAccount<'info>,
+    quote: Option<&QuoteAccounts<'b, 'info>>,
+) -> AccountInfo<'info> {
+    match quote {
+        Some(quote) => quote.fee_recipient_quote_account.to_account_info(),
+        None => fee_recipient.to_account_info(),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use anchor_lang::Discriminator;
 
     #[test]
     fn test_check_symbol_valid() {
@@ -623,6 +1018,36 @@ mod tests {
         assert_eq!(m.free_transfer_allowed, false);
     }
 
+    #[test]
+    fn test_required_reserve() {
+        let mut m = setup_market(false);
+        assert_eq!(m.required_reserve(0).unwrap(), 0);
+
+        m.remaining_supply = MAX_TOKEN_SUPPLY / 2;
+        let reserve = swap_math::compute_swap(MAX_TOKEN_SUPPLY / 2, MAX_TOKEN_SUPPLY / 2, false).unwrap();
+        assert_eq!(m.required_reserve(0).unwrap(), reserve);
+
+        // the tokens of the black hole and the burned tokens are never sold back
+        m.total_burned = MAX_TOKEN_SUPPLY / 8;
+        let reserve_held = swap_math::compute_swap(MAX_TOKEN_SUPPLY / 4, MAX_TOKEN_SUPPLY / 2, false).unwrap();
+        assert_eq!(m.required_reserve(MAX_TOKEN_SUPPLY / 8).unwrap(), reserve_held);
+        assert_eq!(m.required_reserve(MAX_TOKEN_SUPPLY).unwrap(), 0);
+
+        m.total_burned = 0;
+        m.quote_decimals = 6;
+        assert_eq!(m.required_reserve(0).unwrap(), reserve.div_ceil(1_000));
+    }
+
+    #[test]
+    fn test_burn_account_scope() {
+        let mut m = setup_market(true);
+        let market = Pubkey::new_unique();
+        assert_eq!(m.burn_account_scope(&market), market);
+
+        m.symbol = SYMBOL_BURN.to_string();
+        assert_eq!(m.burn_account_scope(&market), m.config);
+    }
+
     fn setup_market(transfer_hook_enabled: bool) -> Market {
         Market {
             config: Pubkey::new_unique(),
@@ -635,6 +1060,60 @@ mod tests {
             native_vault_bump: [0],
             transfer_hook_enabled,
             free_transfer_allowed: transfer_hook_enabled == false,
+            token_program: spl_token_2022::ID,
+            quote_mint: Pubkey::default(),
+            quote_decimals: NATIVE_DECIMALS,
+            creator: Pubkey::new_unique(),
+            buy_burn_enabled: false,
+            total_burned: 0,
+            buy_burn_scheduled: false,
+            version: MARKET_VERSION,
         }
     }
+
+    #[test]
+    fn test_migrate_legacy_market() {
+        let legacy = LegacyMarket {
+            config: Pubkey::new_unique(),
+            token_mint: Pubkey::new_unique(),
+            token_vault: Pubkey::new_unique(),
+            native_vault: Pubkey::new_unique(),
+            remaining_supply: MAX_TOKEN_SUPPLY / 2,
+            symbol: SYMBOL_BURN.to_string(),
+            bump: [254],
+            native_vault_bump: [253],
+            transfer_hook_enabled: true,
+            free_transfer_allowed: false,
+        };
+        let mut data = Market::DISCRIMINATOR.to_vec();
+        legacy.config.serialize(&mut data).unwrap();
+        legacy.token_mint.serialize(&mut data).unwrap();
+        legacy.token_vault.serialize(&mut data).unwrap();
+        legacy.native_vault.serialize(&mut data).unwrap();
+        legacy.remaining_supply.serialize(&mut data).unwrap();
+        legacy.symbol.serialize(&mut data).unwrap();
+        data.extend_from_slice(&[254, 253, 1, 0]);
+        assert!(data.len() <= LegacyMarket::LEN);
+        // The legacy market does not deserialize with the current layout.
+        assert!(Market::try_deserialize(&mut data.as_slice()).is_err());
+
+        let market = LegacyMarket::deserialize(&mut &data[8..]).unwrap().migrate();
+        let mut migrated = Vec::with_capacity(Market::LEN);
+        market.try_serialize(&mut migrated).unwrap();
+        assert!(migrated.len() <= Market::LEN);
+
+        let market = Market::try_deserialize(&mut migrated.as_slice()).unwrap();
+        assert_eq!(market.config, legacy.config);
+        assert_eq!(market.remaining_supply, MAX_TOKEN_SUPPLY / 2);
+        assert_eq!(market.symbol, SYMBOL_BURN);
+        assert_eq!(market.bump, [254]);
+        assert_eq!(market.native_vault_bump, [253]);
+        assert!(market.transfer_hook_enabled);
+        assert!(!market.free_transfer_allowed);
+        assert_eq!(market.token_program, spl_token_2022::ID);
+        assert!(!market.is_quoted_in_token());
+        assert_eq!(market.quote_decimals, NATIVE_DECIMALS);
+        assert!(market.buy_burn_enabled);
+        assert_eq!(market.version, MARKET_VERSION);
+    }
 }
@@ -1,7 +1,11 @@
 pub mod burn_account;
+pub mod burn_leaderboard;
+pub mod buy_burn_schedule;
 pub mod config;
 pub mod market;
 
 pub use burn_account::*;
+pub use burn_leaderboard::*;
+pub use buy_burn_schedule::*;
 pub use config::*;
 pub use market::*;
@@ -0,0 +1,115 @@
+use crate::constants::DECIMALS;
+use crate::errors::Error;
+use anchor_lang::prelude::*;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Deadline {
+    UnixTimestamp(i64),
+    Slot(u64),
+}
+
+/// Optional guards of a trade against landing late or at a worse price than expected.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TradeGuard {
+    /// The trade fails with `DeadlineExceeded` if it lands after the deadline.
+    pub deadline: Option<Deadline>,
+    /// The worst acceptable average price, in the native (or quote) token's smallest unit per whole token,
+    /// including fees. The max price to pay when buying, the min price to receive when selling.
+    ///
+    /// The trade fails with `PriceLimitExceeded` if the average price is worse.
+    pub limit_price: Option<u64>,
+}
+
+impl TradeGuard {
+    pub fn check_deadline(&self, clock: &Clock) -> Result<()> {
+        let exceeded = match self.deadline {
+            Some(Deadline::UnixTimestamp(deadline)) => clock.unix_timestamp > deadline,
+            Some(Deadline::Slot(deadline)) => clock.slot > deadline,
+            None => false,
+        };
+        require!(!exceeded, Error::DeadlineExceeded);
+        Ok(())
+    }
+
+    /// Checks the average price of buying `token_amount` for `pay_amount`.
+    pub fn check_buy_price(&self, token_amount: u64, pay_amount: u64) -> Result<()> {
+        if let Some(limit_price) = self.limit_price {
+            require!(
+                Self::scaled(pay_amount) <= limit_price as u128 * token_amount as u128,
+                Error::PriceLimitExceeded
+            );
+        }
+        Ok(())
+    }
+
+    /// Checks the average price of selling `token_amount` for `receive_amount`.
+    pub fn check_sell_price(&self, token_amount: u64, receive_amount: u64) -> Result<()> {
+        if let Some(limit_price) = self.limit_price {
+            require!(
+                Self::scaled(receive_amount) >= limit_price as u128 * token_amount as u128,
+                Error::PriceLimitExceeded
+            );
+        }
+        Ok(())
+    }
+
+    fn scaled(amount: u64) -> u128 {
+        amount as u128 * 10u128.pow(DECIMALS as u32)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn clock(slot: u64, unix_timestamp: i64) -> Clock {
+        Clock {
+            slot,
+            unix_timestamp,
+            ..Clock::default()
+        }
+    }
+
+    #[test]
+    fn test_check_deadline() {
+        let guard = TradeGuard::default();
+        assert!(guard.check_deadline(&clock(100, 100)).is_ok());
+
+        let guard = TradeGuard {
+            deadline: Some(Deadline::UnixTimestamp(100)),
+            limit_price: None,
+        };
+        assert!(guard.check_deadline(&clock(1_000, 100)).is_ok());
+        assert!(guard.check_deadline(&clock(0, 101)).is_err());
+
+        let guard = TradeGuard {
+            deadline: Some(Deadline::Slot(100)),
+            limit_price: None,
+        };
+        assert!(guard.check_deadline(&clock(100, 1_000)).is_ok());
+        assert!(guard.check_deadline(&clock(101, 0)).is_err());
+    }
+
+    #[test]
+    fn test_check_buy_price() {
+        let guard = TradeGuard {
+            deadline: None,
+            limit_price: Some(2_000),
+        };
+        // 3 tokens for 6000 is exactly the limit price
+        assert!(guard.check_buy_price(3_000_000, 6_000).is_ok());
+        assert!(guard.check_buy_price(3_000_000, 6_001).is_err());
+        assert!(TradeGuard::default().check_buy_price(1, u64::MAX).is_ok());
+    }
+
+    #[test]
+    fn test_check_sell_price() {
+        let guard = TradeGuard {
+            deadline: None,
+            limit_price: Some(2_000),
+        };
+        assert!(guard.check_sell_price(3_000_000, 6_000).is_ok());
+        assert!(guard.check_sell_price(3_000_000, 5_999).is_err());
+        assert!(TradeGuard::default().check_sell_price(u64::MAX, 0).is_ok());
+    }
+}
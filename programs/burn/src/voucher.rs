@@ -0,0 +1,346 @@
+use crate::errors::Error;
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::ed25519_program;
+use anchor_lang::solana_program::sysvar::instructions::{load_current_index_checked, load_instruction_at_checked};
+
+/// Prefix of the message signed by the buy-burn authority, so that the signature can not be reused elsewhere.
+pub const VOUCHER_DOMAIN: &[u8] = b"burn:buy_burn_voucher";
+/// Prefix of the message signed by the buy-burn authority to allow closing a burn account.
+pub const CLOSE_VOUCHER_DOMAIN: &[u8] = b"burn:close_burn_account_voucher";
+/// Prefix of the message signed by the buy-burn authority for `batch_buy_burn`.
+pub const BATCH_VOUCHER_DOMAIN: &[u8] = b"burn:batch_buy_burn_voucher";
+
+/// Size of the offsets of each signature in the Ed25519 program instruction data.
+const SIGNATURE_OFFSETS_LEN: usize = 14;
+
+/// An off-chain authorization of the buy-burn authority to update a burn account.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct BuyBurnVoucher {
+    pub burn_account: Pubkey,
+    pub market: Pubkey,
+    pub next_nonce: u32,
+    pub next_buy_amount: u64,
+    /// Unix timestamp after which the voucher can no longer be used.
+    pub expiry: i64,
+}
+
+impl BuyBurnVoucher {
+    /// The message signed by the buy-burn authority.
+    pub fn message(&self) -> Result<Vec<u8>> {
+        self.message_in(VOUCHER_DOMAIN)
+    }
+
+    /// The message signed by the buy-burn authority to allow closing the burn account,
+    /// where `next_nonce` and `next_buy_amount` are the current values of the burn account.
+    pub fn close_message(&self) -> Result<Vec<u8>> {
+        self.message_in(CLOSE_VOUCHER_DOMAIN)
+    }
+
+    fn message_in(&self, domain: &[u8]) -> Result<Vec<u8>> {
+        let mut message = domain.to_vec();
+        self.serialize(&mut message)?;
+        Ok(message)
+    }
+
+    /// Verifies that the voucher is not expired and signed by `authority`
+    /// in the signature at `signature_index` of the Ed25519 program instruction at `instruction_index`.
+    pub fn verify(
+        &self,
+        instructions_sysvar: &AccountInfo,
+        instruction_index: u16,
+        signature_index: u8,
+        authority: &Pubkey,
+        now: i64,
+    ) -> Result<()> {
+        require!(now <= self.expiry, Error::VoucherExpired);
+        verify_ed25519_signature(
+            instructions_sysvar,
+            instruction_index,
+            signature_index,
+            authority,
+            &self.message()?,
+        )
+    }
+
+    /// Same as `verify`, but for the close message.
+    pub fn verify_close(
+        &self,
+        instructions_sysvar: &AccountInfo,
+        instruction_index: u16,
+        signature_index: u8,
+        authority: &Pubkey,
+        now: i64,
+    ) -> Result<()> {
+        require!(now <= self.expiry, Error::VoucherExpired);
+        verify_ed25519_signature(
+            instructions_sysvar,
+            instruction_index,
+            signature_index,
+            authority,
+            &self.close_message()?,
+        )
+    }
+}
+
+/// The update of one burn account in a [BatchBuyBurnVoucher].
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct BatchBuyBurnEntry {
+    /// The owner of the burn account, checked against the burn account address.
+    pub owner: Pubkey,
+    pub next_nonce: u32,
+    pub next_buy_amount: u64,
+}
+
+/// An off-chain authorization of the buy-burn authority to update many burn accounts in `batch_buy_burn`,
+/// signed once for the whole batch so that its size is not bounded by the signatures fitting in a transaction.
+///
+/// Unlike [BuyBurnVoucher], the payer of the batch is not the owner of the burn accounts,
+/// so the voucher is bound to the keeper that pays and to the token account that receives the tokens.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct BatchBuyBurnVoucher {
+    pub market: Pubkey,
+    pub keeper: Pubkey,
+    pub token_recipient: Pubkey,
+    /// Unix timestamp after which the voucher can no longer be used.
+    pub expiry: i64,
+    pub entries: Vec<BatchBuyBurnEntry>,
+}
+
+impl BatchBuyBurnVoucher {
+    /// The message signed by the buy-burn authority.
+    pub fn message(&self) -> Result<Vec<u8>> {
+        let mut message = BATCH_VOUCHER_DOMAIN.to_vec();
+        self.serialize(&mut message)?;
+        Ok(message)
+    }
+
+    /// Reads the voucher signed by `authority` in the first signature of the Ed25519 program instruction
+    /// at `instruction_index`, and verifies that it is not expired.
+    pub fn load(
+        instructions_sysvar: &AccountInfo,
+        instruction_index: u16,
+        authority: &Pubkey,
+        now: i64,
+    ) -> Result<Self> {
+        let message = signed_message(instructions_sysvar, instruction_index, 0, authority)?;
+        let voucher = message
+            .strip_prefix(BATCH_VOUCHER_DOMAIN)
+            .and_then(|data| Self::try_from_slice(data).ok())
+            .ok_or(Error::InvalidVoucherSignature)?;
+        require!(now <= voucher.expiry, Error::VoucherExpired);
+        Ok(voucher)
+    }
+}
+
+/// Returns the index of the instruction right before the current one,
+/// where the Ed25519 program instruction is expected.
+pub fn previous_instruction_index(instructions_sysvar: &AccountInfo) -> Result<u16> {
+    let current = load_current_index_checked(instructions_sysvar)?;
+    current.checked_sub(1).ok_or(error!(Error::InvalidVoucherSignature))
+}
+
+/// Verifies that the Ed25519 program instruction at `instruction_index` contains a signature of `message`
+/// by `signer` at `signature_index`.
+pub fn verify_ed25519_signature(
+    instructions_sysvar: &AccountInfo,
+    instruction_index: u16,
+    signature_index: u8,
+    signer: &Pubkey,
+    message: &[u8],
+) -> Result<()> {
+    let signed_message = signed_message(instructions_sysvar, instruction_index, signature_index, signer)?;
+    require!(signed_message == message, Error::InvalidVoucherSignature);
+    Ok(())
+}
+
+/// Returns the message of the signature at `signature_index` of the Ed25519 program instruction
+/// at `instruction_index`, which must be signed by `signer`.
+///
+/// The signature itself is verified by the Ed25519 program, whose instruction would fail otherwise,
+/// so only the signed public key is checked here.
+pub fn signed_message(
+    instructions_sysvar: &AccountInfo,
+    instruction_index: u16,
+    signature_index: u8,
+    signer: &Pubkey,
+) -> Result<Vec<u8>> {
+    let ix = load_instruction_at_checked(instruction_index as usize, instructions_sysvar)?;
+    require_keys_eq!(ix.program_id, ed25519_program::ID, Error::InvalidVoucherSignature);
+
+    let data = ix.data.as_slice();
+    require!(
+        data.len() >= 2 && signature_index < data[0],
+        Error::InvalidVoucherSignature
+    );
+    let start = 2 + signature_index as usize * SIGNATURE_OFFSETS_LEN;
+    let offsets = data
+        .get(start..start + SIGNATURE_OFFSETS_LEN)
+        .ok_or(Error::InvalidVoucherSignature)?;
+    let read = |i: usize| u16::from_le_bytes([offsets[i], offsets[i + 1]]) as usize;
+    let (signature_ix, public_key_offset, public_key_ix) = (read(2), read(4), read(6));
+    let (message_offset, message_size, message_ix) = (read(8), read(10), read(12));
+
+    // All the data must be in the Ed25519 program instruction itself
+    let in_this_ix = u16::MAX as usize;
+    require!(
+        signature_ix == in_this_ix && public_key_ix == in_this_ix && message_ix == in_this_ix,
+        Error::InvalidVoucherSignature
+    );
+    let signed_public_key = data
+        .get(public_key_offset..public_key_offset + 32)
+        .ok_or(Error::InvalidVoucherSignature)?;
+    require!(signed_public_key == signer.as_ref(), Error::InvalidVoucherSignature);
+    let signed_message = data
+        .get(message_offset..message_offset + message_size)
+        .ok_or(Error::InvalidVoucherSignature)?;
+    Ok(signed_message.to_vec())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::solana_program::sysvar::instructions::{self, construct_instructions_data, BorrowedInstruction};
+
+    /// Builds the Ed25519 program instruction data with all data in the instruction, signature left zeroed.
+    fn ed25519_data(signatures: &[(Pubkey, Vec<u8>)]) -> Vec<u8> {
+        let header_len = 2 + signatures.len() * SIGNATURE_OFFSETS_LEN;
+        let mut data = vec![signatures.len() as u8, 0];
+        let mut body = vec![];
+        for (public_key, message) in signatures {
+            let public_key_offset = header_len + body.len();
+            body.extend_from_slice(public_key.as_ref());
+            let signature_offset = header_len + body.len();
+            body.extend_from_slice(&[0; 64]);
+            let message_offset = header_len + body.len();
+            body.extend_from_slice(message);
+            for value in [
+                signature_offset,
+                u16::MAX as usize,
+                public_key_offset,
+                u16::MAX as usize,
+                message_offset,
+                message.len(),
+                u16::MAX as usize,
+            ] {
+                data.extend_from_slice(&(value as u16).to_le_bytes());
+            }
+        }
+        data.extend(body);
+        data
+    }
+
+    /// Calls `f` with an instructions sysvar holding a single instruction.
+    fn with_instructions_sysvar<T>(ix_program_id: Pubkey, ix_data: &[u8], f: impl FnOnce(&AccountInfo) -> T) -> T {
+        let ixs = [BorrowedInstruction {
+            program_id: &ix_program_id,
+            accounts: vec![],
+            data: ix_data,
+        }];
+        let mut sysvar_data = construct_instructions_data(&ixs);
+        let mut lamports = 0;
+        let sysvar_info = AccountInfo::new(
+            &instructions::ID,
+            false,
+            false,
+            &mut lamports,
+            &mut sysvar_data,
+            &instructions::ID,
+            false,
+            0,
+        );
+        f(&sysvar_info)
+    }
+
+    fn verify(
+        ix_program_id: Pubkey,
+        ix_data: &[u8],
+        signature_index: u8,
+        signer: &Pubkey,
+        message: &[u8],
+    ) -> Result<()> {
+        with_instructions_sysvar(ix_program_id, ix_data, |sysvar_info| {
+            verify_ed25519_signature(sysvar_info, 0, signature_index, signer, message)
+        })
+    }
+
+    fn setup_voucher() -> BuyBurnVoucher {
+        BuyBurnVoucher {
+            burn_account: Pubkey::new_unique(),
+            market: Pubkey::new_unique(),
+            next_nonce: 1,
+            next_buy_amount: 100,
+            expiry: 1_000,
+        }
+    }
+
+    #[test]
+    fn test_verify_ed25519_signature() {
+        let authority = Pubkey::new_unique();
+        let message = setup_voucher().message().unwrap();
+        let other = BuyBurnVoucher {
+            next_nonce: 2,
+            ..setup_voucher()
+        }
+        .message()
+        .unwrap();
+        let data = ed25519_data(&[(Pubkey::new_unique(), other.clone()), (authority, message.clone())]);
+
+        assert!(verify(ed25519_program::ID, &data, 1, &authority, &message).is_ok());
+        assert!(verify(ed25519_program::ID, &data, 0, &authority, &message).is_err());
+        assert!(verify(ed25519_program::ID, &data, 1, &authority, &other).is_err());
+        assert!(verify(ed25519_program::ID, &data, 2, &authority, &message).is_err());
+        assert!(verify(Pubkey::new_unique(), &data, 1, &authority, &message).is_err());
+    }
+
+    #[test]
+    fn test_voucher_message() {
+        let voucher = setup_voucher();
+        let message = voucher.message().unwrap();
+        assert!(message.starts_with(VOUCHER_DOMAIN));
+        assert_eq!(
+            BuyBurnVoucher::try_from_slice(&message[VOUCHER_DOMAIN.len()..]).unwrap(),
+            voucher
+        );
+
+        let close_message = voucher.close_message().unwrap();
+        assert!(close_message.starts_with(CLOSE_VOUCHER_DOMAIN));
+        assert_ne!(close_message, message);
+    }
+
+    #[test]
+    fn test_batch_voucher() {
+        let authority = Pubkey::new_unique();
+        let voucher = BatchBuyBurnVoucher {
+            market: Pubkey::new_unique(),
+            keeper: Pubkey::new_unique(),
+            token_recipient: Pubkey::new_unique(),
+            expiry: 1_000,
+            entries: (0..20)
+                .map(|i| BatchBuyBurnEntry {
+                    owner: Pubkey::new_unique(),
+                    next_nonce: 1,
+                    next_buy_amount: 100 * i,
+                })
+                .collect(),
+        };
+        let message = voucher.message().unwrap();
+        // A single buy-burn voucher can not be used in a batch, and the other way around.
+        assert!(message.starts_with(BATCH_VOUCHER_DOMAIN));
+        assert!(!message.starts_with(VOUCHER_DOMAIN));
+        assert!(!setup_voucher().message().unwrap().starts_with(BATCH_VOUCHER_DOMAIN));
+
+        let load = |ix_data: &[u8], now: i64| {
+            with_instructions_sysvar(ed25519_program::ID, ix_data, |sysvar_info| {
+                BatchBuyBurnVoucher::load(sysvar_info, 0, &authority, now)
+            })
+        };
+        // the whole batch is covered by a single signature
+        assert_eq!(
+            load(&ed25519_data(&[(authority, message.clone())]), 1_000).unwrap(),
+            voucher
+        );
+        assert!(load(&ed25519_data(&[(authority, message.clone())]), 1_001).is_err());
+        assert!(load(&ed25519_data(&[(Pubkey::new_unique(), message)]), 1_000).is_err());
+        assert!(load(&ed25519_data(&[(authority, setup_voucher().message().unwrap())]), 1_000).is_err());
+    }
+}
@@ -0,0 +1,52 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Token};
+use anchor_spl::token_interface::{Mint, TokenAccount};
+
+/// Unwraps `amount` of wrapped SOL from `wsol_payer` into the lamports of `payer`.
+///
+/// The wrapped SOL is moved into `wsol_temp`, a temporary token account owned by `payer`,
+/// which is then closed so that all of its lamports, including the rent, go back to `payer`.
+pub fn unwrap_to_payer<'info>(
+    payer: &Signer<'info>,
+    wsol_payer: &InterfaceAccount<'info, TokenAccount>,
+    wsol_temp: &InterfaceAccount<'info, TokenAccount>,
+    wsol_mint: &InterfaceAccount<'info, Mint>,
+    amount: u64,
+    wsol_token_program: &Program<'info, Token>,
+) -> Result<()> {
+    token::transfer_checked(
+        CpiContext::new(
+            wsol_token_program.to_account_info(),
+            token::TransferChecked {
+                from: wsol_payer.to_account_info(),
+                mint: wsol_mint.to_account_info(),
+                to: wsol_temp.to_account_info(),
+                authority: payer.to_account_info(),
+            },
+        ),
+        amount,
+        wsol_mint.decimals,
+    )?;
+
+    token::close_account(CpiContext::new(
+        wsol_token_program.to_account_info(),
+        token::CloseAccount {
+            account: wsol_temp.to_account_info(),
+            destination: payer.to_account_info(),
+            authority: payer.to_account_info(),
+        },
+    ))
+}
+
+/// Wraps the lamports already transferred to `wsol_account` by syncing its token amount.
+pub fn sync<'info>(
+    wsol_account: &InterfaceAccount<'info, TokenAccount>,
+    wsol_token_program: &Program<'info, Token>,
+) -> Result<()> {
+    token::sync_native(CpiContext::new(
+        wsol_token_program.to_account_info(),
+        token::SyncNative {
+            account: wsol_account.to_account_info(),
+        },
+    ))
+}
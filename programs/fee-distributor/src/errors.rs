@@ -11,4 +11,73 @@ pub enum Error {
     /// code = 6002
     #[msg("Missing authority")]
     MissingAuthority,
+    /// code = 6003
+    #[msg("Voucher expired")]
+    VoucherExpired,
+    /// code = 6004
+    #[msg("Voucher is not signed by the authority")]
+    InvalidVoucherSignature,
+    /// code = 6005
+    #[msg("Distribution expired")]
+    DistributionExpired,
+    /// code = 6006
+    #[msg("Distribution not expired")]
+    DistributionNotExpired,
+    /// code = 6007
+    #[msg("Invalid merkle proof")]
+    InvalidMerkleProof,
+    /// code = 6008
+    #[msg("Already claimed")]
+    AlreadyClaimed,
+    /// code = 6009
+    #[msg("Claimed amount exceeds the distribution total")]
+    DistributionOverclaimed,
+    /// code = 6010
+    #[msg("Vault id is reserved for the legacy vault")]
+    InvalidVaultId,
+    /// code = 6011
+    #[msg("Math overflow")]
+    MathOverflow,
+    /// code = 6012
+    #[msg("Insufficient staked amount")]
+    InsufficientStake,
+    /// code = 6013
+    #[msg("Vault lamports are paid out by its stake pool only")]
+    VaultHasStakePool,
+    /// code = 6014
+    #[msg("Amount exceeds the undistributed rewards of the stake pool")]
+    InsufficientUndistributedRewards,
+    /// code = 6015
+    #[msg("Emission end time must be after its start time")]
+    InvalidEmissionWindow,
+    /// code = 6016
+    #[msg("Emission weights are derived from stake")]
+    EmissionWeightFromStake,
+    /// code = 6017
+    #[msg("Stake account does not belong to the emission stake pool")]
+    StakePoolMismatch,
+    /// code = 6018
+    #[msg("Vault lamports are streamed by its emission")]
+    VaultHasEmission,
+    /// code = 6019
+    #[msg("Stake pool already weights an emission")]
+    StakePoolHasEmission,
+    /// code = 6020
+    #[msg("Missing the emission share of the staker")]
+    MissingEmissionShare,
+    /// code = 6021
+    #[msg("Vault balance would fall below its rent-exempt reserve")]
+    InsufficientVaultBalance,
+    /// code = 6022
+    #[msg("Signer is not the claim delegate")]
+    InvalidDelegate,
+    /// code = 6023
+    #[msg("Recipient is not approved by the owner")]
+    RecipientNotApproved,
+    /// code = 6024
+    #[msg("Invalid vesting schedule")]
+    InvalidVestingSchedule,
+    /// code = 6025
+    #[msg("Claim has unreleased amount")]
+    ClaimNotReleased,
 }
@@ -0,0 +1,31 @@
+use crate::state::Distribution;
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct ClaimDistribution<'info> {
+    #[account(mut)]
+    pub distribution: Account<'info, Distribution>,
+    /// CHECK: The owner of the leaf, which receives the claimed lamports.
+    #[account(mut)]
+    pub owner: UncheckedAccount<'info>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct ClaimDistributionArgs {
+    pub index: u32,
+    pub amount: u64,
+    pub proof: Vec<[u8; 32]>,
+}
+
+/// Claims the amount of a leaf for its owner, anyone can submit the proof.
+pub fn handler(ctx: Context<ClaimDistribution>, args: ClaimDistributionArgs) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    ctx.accounts
+        .distribution
+        .claim(args.index, &ctx.accounts.owner.key(), args.amount, &args.proof, now)?;
+
+    ctx.accounts.distribution.sub_lamports(args.amount)?;
+    ctx.accounts.owner.add_lamports(args.amount)?;
+    msg!("claimed:{}", args.amount);
+    Ok(())
+}
@@ -0,0 +1,24 @@
+use crate::state::{Emission, EmissionShare, Vault};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct ClaimEmission<'info> {
+    #[account(mut, has_one = vault)]
+    pub emission: Account<'info, Emission>,
+    #[account(mut)]
+    pub vault: Account<'info, Vault>,
+    #[account(mut, has_one = emission, has_one = owner)]
+    pub share: Account<'info, EmissionShare>,
+    #[account(mut)]
+    pub owner: Signer<'info>,
+}
+
+pub fn handler(ctx: Context<ClaimEmission>) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    let accounts = ctx.accounts;
+    let amount = accounts.emission.claim(&mut accounts.share, now)?;
+
+    Vault::transfer(&mut accounts.vault, &accounts.owner.to_account_info(), amount)?;
+    msg!("claimed:{}", amount);
+    Ok(())
+}
@@ -0,0 +1,25 @@
+use crate::state::{StakeAccount, StakePool, Vault};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct ClaimStakeRewards<'info> {
+    #[account(mut, has_one = vault)]
+    pub pool: Account<'info, StakePool>,
+    #[account(mut)]
+    pub vault: Account<'info, Vault>,
+    #[account(mut, has_one = pool, has_one = owner)]
+    pub stake: Account<'info, StakeAccount>,
+    #[account(mut)]
+    pub owner: Signer<'info>,
+}
+
+pub fn handler(ctx: Context<ClaimStakeRewards>) -> Result<()> {
+    let available = Vault::available_lamports(&ctx.accounts.vault)?;
+    let accounts = ctx.accounts;
+    accounts.pool.update(available)?;
+    let amount = accounts.pool.claim(&mut accounts.stake)?;
+
+    Vault::transfer_stake_rewards(&mut accounts.vault, &accounts.owner.to_account_info(), amount)?;
+    msg!("claimed:{}", amount);
+    Ok(())
+}
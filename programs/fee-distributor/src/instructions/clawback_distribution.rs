@@ -0,0 +1,28 @@
+use crate::errors::Error;
+use crate::state::{Distribution, Vault};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct ClawbackDistribution<'info> {
+    #[account(mut, has_one = authority @ Error::MissingAuthority)]
+    pub vault: Account<'info, Vault>,
+    #[account(mut, close = authority, has_one = vault,
+        constraint = Clock::get()?.unix_timestamp > distribution.expiry @ Error::DistributionNotExpired
+    )]
+    pub distribution: Account<'info, Distribution>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+}
+
+/// Closes an expired distribution, its unclaimed amount goes back to the vault
+/// and is no longer counted as claimed, the rent goes back to the authority who paid it.
+pub fn handler(ctx: Context<ClawbackDistribution>) -> Result<()> {
+    let accounts = ctx.accounts;
+    let unclaimed = accounts.distribution.total_amount - accounts.distribution.claimed_amount;
+    accounts.distribution.sub_lamports(unclaimed)?;
+    accounts.vault.add_lamports(unclaimed)?;
+    accounts.vault.total_claimed = accounts.vault.total_claimed.saturating_sub(unclaimed);
+
+    msg!("epoch:{} clawback:{}", accounts.distribution.epoch, unclaimed);
+    Ok(())
+}
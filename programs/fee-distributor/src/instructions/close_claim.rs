@@ -0,0 +1,72 @@
+use crate::errors::Error;
+use crate::state::{Claim, ClosedClaim, Vault};
+use crate::voucher::CloseClaimVoucher;
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::sysvar;
+
+#[derive(Accounts)]
+pub struct CloseClaim<'info> {
+    pub vault: Account<'info, Vault>,
+    /// CHECK: Deserialized as a claim and replaced by a [ClosedClaim] by the handler.
+    #[account(mut, owner = crate::ID,
+        seeds = [
+            b"claim",
+            vault.key().as_ref(),
+            owner.key().as_ref()
+        ], bump)]
+    pub claim: UncheckedAccount<'info>,
+    pub owner: Signer<'info>,
+    /// Co-signs the close, not required if a voucher is provided.
+    #[account(address = vault.authority @ Error::MissingAuthority)]
+    pub authority: Option<Signer<'info>>,
+    /// CHECK: Only used to receive the rent.
+    #[account(mut)]
+    pub recipient: UncheckedAccount<'info>,
+    /// CHECK: Checked by the address constraint, required if a voucher is provided.
+    #[account(address = sysvar::instructions::ID)]
+    pub instructions_sysvar: Option<UncheckedAccount<'info>>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct CloseClaimArgs {
+    /// The expiry of the close voucher signed by the vault authority, if it does not co-sign.
+    pub voucher_expiry: Option<i64>,
+}
+
+/// The claim is shrunk to a [ClosedClaim] rather than deleted,
+/// so the rent of the tombstone stays locked and only the rest is refunded.
+pub fn handler(ctx: Context<CloseClaim>, args: CloseClaimArgs) -> Result<()> {
+    let accounts = ctx.accounts;
+    let claim_info = accounts.claim.to_account_info();
+    let claim = Claim::try_deserialize(&mut &claim_info.try_borrow_data()?[..])?;
+    require!(claim.unreleased == 0, Error::ClaimNotReleased);
+    if accounts.authority.is_none() {
+        let expiry = args.voucher_expiry.ok_or(Error::MissingAuthority)?;
+        let instructions_sysvar = accounts.instructions_sysvar.as_ref().ok_or(Error::MissingAuthority)?;
+        let voucher = CloseClaimVoucher {
+            claim: claim_info.key(),
+            nonce: claim.nonce,
+            claimed: claim.claimed,
+            expiry,
+        };
+        voucher.verify(
+            instructions_sysvar,
+            &accounts.vault.authority,
+            Clock::get()?.unix_timestamp,
+        )?;
+    }
+
+    msg!("nonce:{},claimed:{}", claim.nonce, claim.claimed);
+    claim_info.realloc(ClosedClaim::LEN, false)?;
+    let refund = claim_info
+        .lamports()
+        .saturating_sub(Rent::get()?.minimum_balance(ClosedClaim::LEN));
+    claim_info.sub_lamports(refund)?;
+    accounts.recipient.add_lamports(refund)?;
+    let closed = ClosedClaim {
+        nonce: claim.nonce,
+        claimed: claim.claimed,
+    };
+    let mut data = claim_info.try_borrow_mut_data()?;
+    closed.try_serialize(&mut &mut data[..])
+}
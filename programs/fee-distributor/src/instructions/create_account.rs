@@ -1,11 +1,13 @@
-use crate::state::Claim;
+use crate::state::{Claim, Vault};
 use anchor_lang::prelude::*;
 
 #[derive(Accounts)]
 pub struct CreateAccount<'info> {
+    pub vault: Account<'info, Vault>,
     #[account(init, payer = payer, space = Claim::LEN,
     seeds = [
-        b"owner",
+        b"claim",
+        vault.key().as_ref(),
         owner.key().as_ref(),
     ], bump
     )]
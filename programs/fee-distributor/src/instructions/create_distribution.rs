@@ -0,0 +1,48 @@
+use crate::errors::Error;
+use crate::state::{Distribution, Vault};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+#[instruction(args: CreateDistributionArgs)]
+pub struct CreateDistribution<'info> {
+    #[account(mut, has_one = authority @ Error::MissingAuthority)]
+    pub vault: Account<'info, Vault>,
+    #[account(init, payer = authority, space = Distribution::space(args.num_nodes),
+        seeds = [
+            b"distribution",
+            vault.key().as_ref(),
+            args.epoch.to_le_bytes().as_ref()
+        ], bump
+    )]
+    pub distribution: Account<'info, Distribution>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct CreateDistributionArgs {
+    pub epoch: u64,
+    pub merkle_root: [u8; 32],
+    pub total_amount: u64,
+    pub num_nodes: u32,
+    pub expiry: i64,
+}
+
+/// Creates the distribution of an epoch and moves its total amount from the vault into it.
+pub fn handler(ctx: Context<CreateDistribution>, args: CreateDistributionArgs) -> Result<()> {
+    ctx.accounts.distribution.initialize(
+        ctx.accounts.vault.key(),
+        args.epoch,
+        args.merkle_root,
+        args.total_amount,
+        args.expiry,
+        args.num_nodes,
+    );
+
+    Vault::transfer(
+        &mut ctx.accounts.vault,
+        &ctx.accounts.distribution.to_account_info(),
+        args.total_amount,
+    )
+}
@@ -0,0 +1,49 @@
+use crate::errors::Error;
+use crate::state::{Emission, StakePool, Vault};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct CreateEmission<'info> {
+    #[account(mut, has_one = authority @ Error::MissingAuthority,
+        constraint = !vault.has_stake_pool @ Error::VaultHasStakePool
+    )]
+    pub vault: Account<'info, Vault>,
+    #[account(init, payer = authority, space = Emission::LEN,
+        seeds = [
+            b"emission",
+            vault.key().as_ref()
+        ], bump
+    )]
+    pub emission: Account<'info, Emission>,
+    /// Derives the weights from the staked amounts in this pool instead of the authority.
+    #[account(mut, constraint = !stake_pool.has_emission @ Error::StakePoolHasEmission)]
+    pub stake_pool: Option<Account<'info, StakePool>>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct CreateEmissionArgs {
+    /// Lamports emitted per second.
+    pub rate: u64,
+    pub start_time: i64,
+    pub end_time: i64,
+}
+
+/// The emission pays its claims from the vault, which therefore cannot have a stake pool (see `Vault::transfer`).
+/// A stake pool weights a single emission, whose shares are then updated by every stake and unstake.
+pub fn handler(ctx: Context<CreateEmission>, args: CreateEmissionArgs) -> Result<()> {
+    let accounts = ctx.accounts;
+    accounts.vault.has_emission = true;
+    if let Some(stake_pool) = &mut accounts.stake_pool {
+        stake_pool.has_emission = true;
+    }
+    accounts.emission.initialize(
+        accounts.vault.key(),
+        accounts.stake_pool.as_ref().map(|pool| pool.key()),
+        args.rate,
+        args.start_time,
+        args.end_time,
+    )
+}
@@ -0,0 +1,26 @@
+use crate::state::{Emission, EmissionShare};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct CreateEmissionShare<'info> {
+    pub emission: Account<'info, Emission>,
+    #[account(init, payer = payer, space = EmissionShare::LEN,
+    seeds = [
+        b"emission_share",
+        emission.key().as_ref(),
+        owner.key().as_ref(),
+    ], bump
+    )]
+    pub share: Account<'info, EmissionShare>,
+    /// CHECK:
+    pub owner: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(ctx: Context<CreateEmissionShare>) -> Result<()> {
+    ctx.accounts.share.emission = ctx.accounts.emission.key();
+    ctx.accounts.share.owner = ctx.accounts.owner.key();
+    Ok(())
+}
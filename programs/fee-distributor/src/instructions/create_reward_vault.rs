@@ -0,0 +1,33 @@
+use crate::state::{Vault, REWARD_AUTHORITY_SEED};
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+#[derive(Accounts)]
+pub struct CreateRewardVault<'info> {
+    pub vault: Account<'info, Vault>,
+    pub mint: InterfaceAccount<'info, Mint>,
+    #[account(init, payer = payer,
+        token::mint = mint,
+        token::authority = reward_authority,
+        token::token_program = token_program,
+        seeds = [
+            b"reward_tokens",
+            vault.key().as_ref(),
+            mint.key().as_ref()
+        ], bump
+    )]
+    pub vault_token_account: InterfaceAccount<'info, TokenAccount>,
+    /// CHECK: Only used as the owner of the reward tokens.
+    #[account(seeds = [REWARD_AUTHORITY_SEED], bump)]
+    pub reward_authority: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+/// The token accounts of all vaults share the reward authority as owner, allowlisted by the transfer hook
+/// of the burn tokens so that they can be deposited and claimed.
+pub fn handler(_ctx: Context<CreateRewardVault>) -> Result<()> {
+    Ok(())
+}
@@ -0,0 +1,26 @@
+use crate::state::{StakeAccount, StakePool};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct CreateStakeAccount<'info> {
+    pub pool: Account<'info, StakePool>,
+    #[account(init, payer = payer, space = StakeAccount::LEN,
+    seeds = [
+        b"stake",
+        pool.key().as_ref(),
+        owner.key().as_ref(),
+    ], bump
+    )]
+    pub stake: Account<'info, StakeAccount>,
+    /// CHECK:
+    pub owner: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(ctx: Context<CreateStakeAccount>) -> Result<()> {
+    ctx.accounts.stake.pool = ctx.accounts.pool.key();
+    ctx.accounts.stake.owner = ctx.accounts.owner.key();
+    Ok(())
+}
@@ -0,0 +1,49 @@
+use crate::errors::Error;
+use crate::state::{StakePool, Vault, STAKE_AUTHORITY_SEED};
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+#[derive(Accounts)]
+pub struct CreateStakePool<'info> {
+    #[account(mut, has_one = authority @ Error::MissingAuthority,
+        constraint = !vault.has_emission @ Error::VaultHasEmission
+    )]
+    pub vault: Account<'info, Vault>,
+    #[account(init, payer = authority, space = StakePool::LEN,
+        seeds = [
+            b"stake_pool",
+            vault.key().as_ref()
+        ], bump
+    )]
+    pub pool: Account<'info, StakePool>,
+    pub mint: InterfaceAccount<'info, Mint>,
+    #[account(init, payer = authority,
+        token::mint = mint,
+        token::authority = stake_authority,
+        token::token_program = token_program,
+        seeds = [
+            b"stake_pool_tokens",
+            pool.key().as_ref()
+        ], bump
+    )]
+    pub pool_token_account: InterfaceAccount<'info, TokenAccount>,
+    /// CHECK: Only used as the owner of the staked tokens.
+    #[account(seeds = [STAKE_AUTHORITY_SEED], bump)]
+    pub stake_authority: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+/// From now on, the lamports of the vault are paid out by the pool only,
+/// those already in the vault are undistributed rewards.
+pub fn handler(ctx: Context<CreateStakePool>) -> Result<()> {
+    let available = Vault::available_lamports(&ctx.accounts.vault)?;
+    ctx.accounts.vault.has_stake_pool = true;
+    let pool = &mut ctx.accounts.pool;
+    pool.vault = ctx.accounts.vault.key();
+    pool.mint = ctx.accounts.mint.key();
+    pool.token_account = ctx.accounts.pool_token_account.key();
+    pool.update(available)
+}
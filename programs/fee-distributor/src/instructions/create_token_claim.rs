@@ -0,0 +1,28 @@
+use crate::state::{TokenClaim, Vault};
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::Mint;
+
+#[derive(Accounts)]
+pub struct CreateTokenClaim<'info> {
+    pub vault: Account<'info, Vault>,
+    #[account(init, payer = payer, space = TokenClaim::LEN,
+    seeds = [
+        b"token_claim",
+        vault.key().as_ref(),
+        owner.key().as_ref(),
+        mint.key().as_ref(),
+    ], bump
+    )]
+    pub token_claim: Account<'info, TokenClaim>,
+    /// CHECK:
+    pub owner: UncheckedAccount<'info>,
+    pub mint: InterfaceAccount<'info, Mint>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(ctx: Context<CreateTokenClaim>) -> Result<()> {
+    ctx.accounts.token_claim.mint = ctx.accounts.mint.key();
+    Ok(())
+}
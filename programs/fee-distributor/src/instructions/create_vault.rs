@@ -0,0 +1,30 @@
+use crate::errors::Error;
+use crate::state::{Vault, LEGACY_VAULT_ID};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+#[instruction(args: CreateVaultArgs)]
+pub struct CreateVault<'info> {
+    #[account(init, payer = payer, space = Vault::LEN,
+        seeds = [
+            b"vault",
+            args.id.to_le_bytes().as_ref()
+        ], bump
+    )]
+    pub vault: Account<'info, Vault>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct CreateVaultArgs {
+    pub id: u64,
+    pub authority: Pubkey,
+}
+
+pub fn handler(ctx: Context<CreateVault>, args: CreateVaultArgs) -> Result<()> {
+    require!(args.id != LEGACY_VAULT_ID, Error::InvalidVaultId);
+    ctx.accounts.vault.initialize(args.id, args.authority, ctx.bumps.vault);
+    Ok(())
+}
@@ -0,0 +1,34 @@
+use crate::state::Vault;
+use anchor_lang::prelude::*;
+use anchor_lang::system_program::{transfer, Transfer};
+
+#[derive(Accounts)]
+pub struct Deposit<'info> {
+    #[account(mut)]
+    pub vault: Account<'info, Vault>,
+    #[account(mut)]
+    pub depositor: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct DepositArgs {
+    pub amount: u64,
+}
+
+pub fn handler(ctx: Context<Deposit>, args: DepositArgs) -> Result<()> {
+    transfer(
+        CpiContext::new(
+            ctx.accounts.system_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.depositor.to_account_info(),
+                to: ctx.accounts.vault.to_account_info(),
+            },
+        ),
+        args.amount,
+    )?;
+
+    let vault = &mut ctx.accounts.vault;
+    vault.total_deposited = vault.total_deposited.saturating_add(args.amount);
+    Ok(())
+}
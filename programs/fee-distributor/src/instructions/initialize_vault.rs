@@ -1,4 +1,4 @@
-use crate::state::Vault;
+use crate::state::{Vault, LEGACY_VAULT_ID};
 use anchor_lang::prelude::*;
 
 #[derive(Accounts)]
@@ -20,6 +20,8 @@ pub struct InitializeVaultArgs {
 }
 
 pub fn handler(ctx: Context<InitializeVault>, args: InitializeVaultArgs) -> Result<()> {
-    ctx.accounts.vault.initialize(args.authority, &ctx.program_id);
+    ctx.accounts
+        .vault
+        .initialize(LEGACY_VAULT_ID, args.authority, ctx.bumps.vault);
     Ok(())
 }
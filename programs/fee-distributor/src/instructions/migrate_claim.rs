@@ -0,0 +1,36 @@
+use crate::state::{Claim, Vault};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct MigrateClaim<'info> {
+    #[account(seeds = [b"vault"], bump)]
+    pub vault: Account<'info, Vault>,
+    /// The claim seeded by the owner only, created before claims were scoped by vault.
+    #[account(mut, close = payer,
+    seeds = [
+        b"owner",
+        owner.key().as_ref(),
+    ], bump
+    )]
+    pub legacy_claim: Account<'info, Claim>,
+    #[account(init, payer = payer, space = Claim::LEN,
+    seeds = [
+        b"claim",
+        vault.key().as_ref(),
+        owner.key().as_ref(),
+    ], bump
+    )]
+    pub claim: Account<'info, Claim>,
+    /// Signs so that nobody else can move the claim and take the rent of the legacy account.
+    pub owner: Signer<'info>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(ctx: Context<MigrateClaim>) -> Result<()> {
+    let accounts = ctx.accounts;
+    accounts.claim.nonce = accounts.legacy_claim.nonce;
+    accounts.claim.claimed = accounts.legacy_claim.claimed;
+    Ok(())
+}
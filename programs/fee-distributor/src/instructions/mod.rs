@@ -1,9 +1,67 @@
 #![allow(ambiguous_glob_reexports)]
 
+pub mod claim_distribution;
+pub mod claim_emission;
+pub mod claim_stake_rewards;
+pub mod clawback_distribution;
+pub mod close_claim;
 pub mod create_account;
+pub mod create_distribution;
+pub mod create_emission;
+pub mod create_emission_share;
+pub mod create_reward_vault;
+pub mod create_stake_account;
+pub mod create_stake_pool;
+pub mod create_token_claim;
+pub mod create_vault;
+pub mod deposit;
 pub mod initialize_vault;
+pub mod migrate_claim;
+pub mod release_claim;
+pub mod reopen_claim;
+pub mod revoke_claim;
+pub mod set_claim_delegate;
+pub mod set_claim_vesting;
+pub mod set_emission;
+pub mod set_emission_weight;
+pub mod set_vault_authority;
+pub mod stake;
+pub mod sync_emission_weight;
+pub mod unstake;
 pub mod update_claim;
+pub mod update_claim_as_delegate;
+pub mod update_token_claim;
+pub mod withdraw;
 
+pub use claim_distribution::*;
+pub use claim_emission::*;
+pub use claim_stake_rewards::*;
+pub use clawback_distribution::*;
+pub use close_claim::*;
 pub use create_account::*;
+pub use create_distribution::*;
+pub use create_emission::*;
+pub use create_emission_share::*;
+pub use create_reward_vault::*;
+pub use create_stake_account::*;
+pub use create_stake_pool::*;
+pub use create_token_claim::*;
+pub use create_vault::*;
+pub use deposit::*;
 pub use initialize_vault::*;
+pub use migrate_claim::*;
+pub use release_claim::*;
+pub use reopen_claim::*;
+pub use revoke_claim::*;
+pub use set_claim_delegate::*;
+pub use set_claim_vesting::*;
+pub use set_emission::*;
+pub use set_emission_weight::*;
+pub use set_vault_authority::*;
+pub use stake::*;
+pub use sync_emission_weight::*;
+pub use unstake::*;
 pub use update_claim::*;
+pub use update_claim_as_delegate::*;
+pub use update_token_claim::*;
+pub use withdraw::*;
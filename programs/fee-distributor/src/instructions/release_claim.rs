@@ -0,0 +1,32 @@
+use crate::state::{Claim, Vault};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct ReleaseClaim<'info> {
+    #[account(mut)]
+    pub vault: Account<'info, Vault>,
+    #[account(mut,
+        seeds = [
+            b"claim",
+            vault.key().as_ref(),
+            owner.key().as_ref()
+        ], bump)]
+    pub claim: Account<'info, Claim>,
+    pub owner: Signer<'info>,
+    /// CHECK:
+    #[account(mut)]
+    pub recipient: UncheckedAccount<'info>,
+}
+
+/// Pays the amount vested since the last claim, without the authority.
+pub fn handler(ctx: Context<ReleaseClaim>) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    let amount = ctx.accounts.claim.release(now);
+    msg!("released:{}", amount);
+
+    Vault::transfer(
+        &mut ctx.accounts.vault,
+        &ctx.accounts.recipient.to_account_info(),
+        amount,
+    )
+}
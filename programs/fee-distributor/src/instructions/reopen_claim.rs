@@ -0,0 +1,47 @@
+use crate::state::{Claim, ClosedClaim, Vault};
+use anchor_lang::{prelude::*, system_program};
+
+#[derive(Accounts)]
+pub struct ReopenClaim<'info> {
+    pub vault: Account<'info, Vault>,
+    /// CHECK: Deserialized as a [ClosedClaim] and replaced by a claim by the handler.
+    #[account(mut, owner = crate::ID,
+    seeds = [
+        b"claim",
+        vault.key().as_ref(),
+        owner.key().as_ref(),
+    ], bump
+    )]
+    pub claim: UncheckedAccount<'info>,
+    /// CHECK:
+    pub owner: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Re-creates a closed claim from the state it was closed with,
+/// so that its nonce and claimed amount continue instead of starting over.
+pub fn handler(ctx: Context<ReopenClaim>) -> Result<()> {
+    let claim_info = ctx.accounts.claim.to_account_info();
+    let closed = ClosedClaim::try_deserialize(&mut &claim_info.try_borrow_data()?[..])?;
+
+    // The payer tops up the rent of the claim.
+    let rent = Rent::get()?.minimum_balance(Claim::LEN);
+    let top_up = rent.saturating_sub(claim_info.lamports());
+    if top_up > 0 {
+        system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: ctx.accounts.payer.to_account_info(),
+                    to: claim_info.clone(),
+                },
+            ),
+            top_up,
+        )?;
+    }
+    claim_info.realloc(Claim::LEN, true)?;
+    let mut data = claim_info.try_borrow_mut_data()?;
+    closed.reopen().try_serialize(&mut &mut data[..])
+}
@@ -0,0 +1,27 @@
+use crate::errors::Error;
+use crate::state::{Claim, Vault};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct RevokeClaim<'info> {
+    #[account(has_one = authority @ Error::MissingAuthority)]
+    pub vault: Account<'info, Vault>,
+    #[account(mut,
+        seeds = [
+            b"claim",
+            vault.key().as_ref(),
+            owner.key().as_ref()
+        ], bump)]
+    pub claim: Account<'info, Claim>,
+    /// CHECK: Only used to derive the claim.
+    pub owner: UncheckedAccount<'info>,
+    pub authority: Signer<'info>,
+}
+
+/// Revokes the unvested amount of a claim, which stays in the vault.
+pub fn handler(ctx: Context<RevokeClaim>) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    let revoked = ctx.accounts.claim.revoke(now);
+    msg!("revoked:{}", revoked);
+    Ok(())
+}
@@ -0,0 +1,34 @@
+use crate::state::{Claim, Vault};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct SetClaimDelegate<'info> {
+    pub vault: Account<'info, Vault>,
+    #[account(mut,
+        realloc = Claim::LEN,
+        realloc::payer = owner,
+        realloc::zero = false,
+        seeds = [
+            b"claim",
+            vault.key().as_ref(),
+            owner.key().as_ref()
+        ], bump)]
+    pub claim: Account<'info, Claim>,
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct SetClaimDelegateArgs {
+    /// `None` removes the delegate.
+    pub delegate: Option<Pubkey>,
+    /// `None` sends the claims signed by the delegate to the owner.
+    pub recipient: Option<Pubkey>,
+}
+
+pub fn handler(ctx: Context<SetClaimDelegate>, args: SetClaimDelegateArgs) -> Result<()> {
+    ctx.accounts.claim.delegate = args.delegate;
+    ctx.accounts.claim.recipient = args.recipient;
+    Ok(())
+}
@@ -0,0 +1,38 @@
+use crate::errors::Error;
+use crate::state::{Claim, Vault, VestingSchedule};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct SetClaimVesting<'info> {
+    #[account(has_one = authority @ Error::MissingAuthority)]
+    pub vault: Account<'info, Vault>,
+    #[account(mut,
+        realloc = Claim::LEN,
+        realloc::payer = authority,
+        realloc::zero = false,
+        seeds = [
+            b"claim",
+            vault.key().as_ref(),
+            owner.key().as_ref()
+        ], bump)]
+    pub claim: Account<'info, Claim>,
+    /// CHECK: Only used to derive the claim.
+    pub owner: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct SetClaimVestingArgs {
+    /// `None` makes the unreleased amount releasable at once.
+    pub vesting: Option<VestingSchedule>,
+}
+
+pub fn handler(ctx: Context<SetClaimVesting>, args: SetClaimVestingArgs) -> Result<()> {
+    if let Some(vesting) = &args.vesting {
+        vesting.validate()?;
+    }
+    ctx.accounts.claim.vesting = args.vesting;
+    Ok(())
+}
@@ -0,0 +1,23 @@
+use crate::errors::Error;
+use crate::state::{Emission, Vault};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct SetEmission<'info> {
+    #[account(has_one = authority @ Error::MissingAuthority)]
+    pub vault: Account<'info, Vault>,
+    #[account(mut, has_one = vault)]
+    pub emission: Account<'info, Emission>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct SetEmissionArgs {
+    pub rate: u64,
+    pub end_time: i64,
+}
+
+pub fn handler(ctx: Context<SetEmission>, args: SetEmissionArgs) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    ctx.accounts.emission.configure(args.rate, args.end_time, now)
+}
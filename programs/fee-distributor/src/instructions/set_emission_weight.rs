@@ -0,0 +1,27 @@
+use crate::errors::Error;
+use crate::state::{Emission, EmissionShare, Vault};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct SetEmissionWeight<'info> {
+    #[account(has_one = authority @ Error::MissingAuthority)]
+    pub vault: Account<'info, Vault>,
+    #[account(mut, has_one = vault,
+        constraint = emission.stake_pool.is_none() @ Error::EmissionWeightFromStake
+    )]
+    pub emission: Account<'info, Emission>,
+    #[account(mut, has_one = emission)]
+    pub share: Account<'info, EmissionShare>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct SetEmissionWeightArgs {
+    pub weight: u64,
+}
+
+pub fn handler(ctx: Context<SetEmissionWeight>, args: SetEmissionWeightArgs) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    let accounts = ctx.accounts;
+    accounts.emission.set_weight(&mut accounts.share, args.weight, now)
+}
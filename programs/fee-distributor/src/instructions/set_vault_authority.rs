@@ -0,0 +1,20 @@
+use crate::errors::Error;
+use crate::state::Vault;
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct SetVaultAuthority<'info> {
+    #[account(mut, has_one = authority @ Error::MissingAuthority)]
+    pub vault: Account<'info, Vault>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct SetVaultAuthorityArgs {
+    pub new_authority: Pubkey,
+}
+
+pub fn handler(ctx: Context<SetVaultAuthority>, args: SetVaultAuthorityArgs) -> Result<()> {
+    ctx.accounts.vault.authority = args.new_authority;
+    Ok(())
+}
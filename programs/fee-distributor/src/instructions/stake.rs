@@ -0,0 +1,63 @@
+use crate::state::{Emission, EmissionShare, StakeAccount, StakePool, Vault};
+use anchor_lang::prelude::*;
+use anchor_spl::token_2022::spl_token_2022::onchain::invoke_transfer_checked;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+#[derive(Accounts)]
+pub struct Stake<'info> {
+    #[account(mut, has_one = vault)]
+    pub pool: Box<Account<'info, StakePool>>,
+    pub vault: Account<'info, Vault>,
+    #[account(mut, has_one = pool, has_one = owner)]
+    pub stake: Box<Account<'info, StakeAccount>>,
+    pub owner: Signer<'info>,
+    #[account(address = pool.mint)]
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(mut,
+        token::mint = mint,
+        token::authority = owner,
+        token::token_program = token_program,
+    )]
+    pub owner_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(mut, address = pool.token_account)]
+    pub pool_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    pub token_program: Interface<'info, TokenInterface>,
+    /// The emission weighted by the pool and the share of the owner, required if the pool has an emission.
+    #[account(mut)]
+    pub emission: Option<Box<Account<'info, Emission>>>,
+    #[account(mut)]
+    pub emission_share: Option<Box<Account<'info, EmissionShare>>>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct StakeArgs {
+    pub amount: u64,
+}
+
+/// Stakes tokens into the pool, the transfer hook accounts of the mint are passed through the remaining accounts.
+pub fn handler<'a, 'b, 'c, 'info>(ctx: Context<'a, 'b, 'c, 'info, Stake<'info>>, args: StakeArgs) -> Result<()> {
+    let available = Vault::available_lamports(&ctx.accounts.vault)?;
+    let accounts = ctx.accounts;
+    accounts.pool.update(available)?;
+    accounts.pool.stake(&mut accounts.stake, args.amount)?;
+    Emission::sync_stake(
+        &accounts.pool,
+        &accounts.stake,
+        accounts.emission.as_deref_mut(),
+        accounts.emission_share.as_deref_mut(),
+        Clock::get()?.unix_timestamp,
+    )?;
+
+    invoke_transfer_checked(
+        &accounts.token_program.key(),
+        accounts.owner_token_account.to_account_info(),
+        accounts.mint.to_account_info(),
+        accounts.pool_token_account.to_account_info(),
+        accounts.owner.to_account_info(),
+        ctx.remaining_accounts,
+        args.amount,
+        accounts.mint.decimals,
+        &[],
+    )?;
+    Ok(())
+}
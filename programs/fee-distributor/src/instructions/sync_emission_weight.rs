@@ -0,0 +1,24 @@
+use crate::errors::Error;
+use crate::state::{Emission, EmissionShare, StakeAccount};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct SyncEmissionWeight<'info> {
+    #[account(mut)]
+    pub emission: Account<'info, Emission>,
+    #[account(mut, has_one = emission,
+        constraint = share.owner == stake.owner @ Error::StakePoolMismatch
+    )]
+    pub share: Account<'info, EmissionShare>,
+    #[account(constraint = Some(stake.pool) == emission.stake_pool @ Error::StakePoolMismatch)]
+    pub stake: Account<'info, StakeAccount>,
+}
+
+/// Sets the weight of a share to the staked amount of its owner, anyone can sync a share.
+pub fn handler(ctx: Context<SyncEmissionWeight>) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    let accounts = ctx.accounts;
+    accounts
+        .emission
+        .set_weight(&mut accounts.share, accounts.stake.amount, now)
+}
@@ -10,7 +10,8 @@ pub struct UpdateClaim<'info> {
 
     #[account(mut,
         seeds = [
-            b"owner", 
+            b"claim",
+            vault.key().as_ref(),
             owner.key().as_ref()
         ], bump)]
     pub claim: Account<'info, Claim>,
@@ -29,9 +30,15 @@ pub struct UpdateClaimArgs {
 }
 
 pub fn handler(ctx: Context<UpdateClaim>, args: UpdateClaimArgs) -> Result<()> {
-    let delta = ctx.accounts.claim.update_claim(args.next_nonce, args.next_claimed)?;
+    let now = Clock::get()?.unix_timestamp;
+    let delta = ctx
+        .accounts
+        .claim
+        .update_claim(args.next_nonce, args.next_claimed, now)?;
 
-    ctx.accounts
-        .vault
-        .transfer(&ctx.accounts.vault, &ctx.accounts.recipient, delta)
+    Vault::transfer(
+        &mut ctx.accounts.vault,
+        &ctx.accounts.recipient.to_account_info(),
+        delta,
+    )
 }
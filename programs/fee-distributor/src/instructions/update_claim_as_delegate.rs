@@ -0,0 +1,44 @@
+use crate::errors::Error;
+use crate::instructions::UpdateClaimArgs;
+use crate::state::claim::Claim;
+use crate::state::vault::Vault;
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct UpdateClaimAsDelegate<'info> {
+    #[account(mut, has_one = authority @ Error::MissingAuthority)]
+    pub vault: Account<'info, Vault>,
+
+    #[account(mut,
+        seeds = [
+            b"claim",
+            vault.key().as_ref(),
+            owner.key().as_ref()
+        ], bump)]
+    pub claim: Account<'info, Claim>,
+    pub authority: Signer<'info>,
+    /// CHECK: Only used to derive the claim.
+    pub owner: UncheckedAccount<'info>,
+    #[account(constraint = claim.delegate == Some(delegate.key()) @ Error::InvalidDelegate)]
+    pub delegate: Signer<'info>,
+    /// CHECK: Checked against the recipient approved by the owner.
+    #[account(mut,
+        constraint = recipient.key() == claim.approved_recipient(&owner.key()) @ Error::RecipientNotApproved
+    )]
+    pub recipient: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(ctx: Context<UpdateClaimAsDelegate>, args: UpdateClaimArgs) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    let delta = ctx
+        .accounts
+        .claim
+        .update_claim(args.next_nonce, args.next_claimed, now)?;
+
+    Vault::transfer(
+        &mut ctx.accounts.vault,
+        &ctx.accounts.recipient.to_account_info(),
+        delta,
+    )
+}
@@ -0,0 +1,67 @@
+use crate::errors::Error;
+use crate::instructions::UpdateClaimArgs;
+use crate::state::{TokenClaim, Vault, REWARD_AUTHORITY_SEED};
+use anchor_lang::prelude::*;
+use anchor_spl::token_2022::spl_token_2022::onchain::invoke_transfer_checked;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+#[derive(Accounts)]
+pub struct UpdateTokenClaim<'info> {
+    #[account(has_one = authority @ Error::MissingAuthority)]
+    pub vault: Account<'info, Vault>,
+
+    #[account(mut,
+        seeds = [
+            b"token_claim",
+            vault.key().as_ref(),
+            owner.key().as_ref(),
+            mint.key().as_ref(),
+        ], bump)]
+    pub token_claim: Account<'info, TokenClaim>,
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(mut,
+        seeds = [
+            b"reward_tokens",
+            vault.key().as_ref(),
+            mint.key().as_ref()
+        ], bump
+    )]
+    pub vault_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    /// The owner's associated token account.
+    #[account(mut,
+        associated_token::mint = mint,
+        associated_token::authority = owner,
+        associated_token::token_program = token_program,
+    )]
+    pub recipient: Box<InterfaceAccount<'info, TokenAccount>>,
+    /// CHECK: Only used to sign the transfer of the reward tokens.
+    #[account(seeds = [REWARD_AUTHORITY_SEED], bump)]
+    pub reward_authority: UncheckedAccount<'info>,
+    pub authority: Signer<'info>,
+    pub owner: Signer<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+/// Claims reward tokens, the transfer hook accounts of the mint are passed through the remaining accounts.
+pub fn handler<'a, 'b, 'c, 'info>(
+    ctx: Context<'a, 'b, 'c, 'info, UpdateTokenClaim<'info>>,
+    args: UpdateClaimArgs,
+) -> Result<()> {
+    let delta = ctx
+        .accounts
+        .token_claim
+        .update_claim(args.next_nonce, args.next_claimed)?;
+
+    invoke_transfer_checked(
+        &ctx.accounts.token_program.key(),
+        ctx.accounts.vault_token_account.to_account_info(),
+        ctx.accounts.mint.to_account_info(),
+        ctx.accounts.recipient.to_account_info(),
+        ctx.accounts.reward_authority.to_account_info(),
+        ctx.remaining_accounts,
+        delta,
+        ctx.accounts.mint.decimals,
+        &[&[REWARD_AUTHORITY_SEED, &[ctx.bumps.reward_authority]]],
+    )?;
+    Ok(())
+}
@@ -0,0 +1,40 @@
+use crate::errors::Error;
+use crate::state::{StakePool, Vault};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct Withdraw<'info> {
+    #[account(mut, has_one = authority @ Error::MissingAuthority)]
+    pub vault: Account<'info, Vault>,
+    pub authority: Signer<'info>,
+    /// CHECK:
+    #[account(mut)]
+    pub recipient: UncheckedAccount<'info>,
+    /// The stake pool of the vault, required if the vault has one.
+    #[account(mut, has_one = vault,
+        seeds = [
+            b"stake_pool",
+            vault.key().as_ref()
+        ], bump
+    )]
+    pub pool: Option<Account<'info, StakePool>>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct WithdrawArgs {
+    pub amount: u64,
+}
+
+/// Only the undistributed rewards of the stake pool can be withdrawn from a vault with a pool.
+pub fn handler(ctx: Context<Withdraw>, args: WithdrawArgs) -> Result<()> {
+    let accounts = ctx.accounts;
+    let recipient = accounts.recipient.to_account_info();
+    match &mut accounts.pool {
+        Some(pool) => {
+            pool.update(Vault::available_lamports(&accounts.vault)?)?;
+            pool.withdraw_undistributed(args.amount)?;
+            Vault::withdraw_undistributed(&accounts.vault, &recipient, args.amount)
+        }
+        None => Vault::withdraw(&accounts.vault, &recipient, args.amount),
+    }
+}
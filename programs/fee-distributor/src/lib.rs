@@ -4,7 +4,9 @@ declare_id!("burnfZzJfuR8b8yMRGgZfLAq7P2eCuMdRgGooQjPjua");
 
 pub mod errors;
 pub mod instructions;
+pub mod merkle;
 pub mod state;
+pub mod voucher;
 
 use crate::instructions::*;
 
@@ -16,6 +18,32 @@ pub mod fee_distributor {
         initialize_vault::handler(ctx, args)
     }
 
+    /// Creates a vault keyed by `id`, with its own authority.
+    pub fn create_vault(ctx: Context<CreateVault>, args: CreateVaultArgs) -> Result<()> {
+        create_vault::handler(ctx, args)
+    }
+
+    /// Transfers the authority of a vault, signed by its current authority.
+    pub fn set_vault_authority(ctx: Context<SetVaultAuthority>, args: SetVaultAuthorityArgs) -> Result<()> {
+        set_vault_authority::handler(ctx, args)
+    }
+
+    /// Moves a claim created before claims were scoped by vault to the legacy vault, signed by the owner.
+    pub fn migrate_claim(ctx: Context<MigrateClaim>) -> Result<()> {
+        migrate_claim::handler(ctx)
+    }
+
+    /// Deposits lamports into a vault, recorded in its `total_deposited`.
+    pub fn deposit(ctx: Context<Deposit>, args: DepositArgs) -> Result<()> {
+        deposit::handler(ctx, args)
+    }
+
+    /// Withdraws lamports from a vault above its rent-exempt minimum, signed by the vault authority.
+    /// Only the undistributed rewards of the stake pool can be withdrawn from a vault with a pool.
+    pub fn withdraw(ctx: Context<Withdraw>, args: WithdrawArgs) -> Result<()> {
+        withdraw::handler(ctx, args)
+    }
+
     pub fn create_account(ctx: Context<CreateAccount>) -> Result<()> {
         create_account::handler(ctx)
     }
@@ -23,4 +51,134 @@ pub mod fee_distributor {
     pub fn update_claim(ctx: Context<UpdateClaim>, args: UpdateClaimArgs) -> Result<()> {
         update_claim::handler(ctx, args)
     }
+
+    /// Sets the delegate of a claim and the recipient of the claims it signs, signed by the owner.
+    pub fn set_claim_delegate(ctx: Context<SetClaimDelegate>, args: SetClaimDelegateArgs) -> Result<()> {
+        set_claim_delegate::handler(ctx, args)
+    }
+
+    /// Same as `update_claim`, signed by the claim delegate instead of the owner and paid to the approved recipient.
+    /// Program owned owners and delegates can sign through `invoke_signed`, e.g. with the `cpi` feature of this crate.
+    pub fn update_claim_as_delegate(ctx: Context<UpdateClaimAsDelegate>, args: UpdateClaimArgs) -> Result<()> {
+        update_claim_as_delegate::handler(ctx, args)
+    }
+
+    /// Sets the vesting schedule of a claim, signed by the vault authority.
+    pub fn set_claim_vesting(ctx: Context<SetClaimVesting>, args: SetClaimVestingArgs) -> Result<()> {
+        set_claim_vesting::handler(ctx, args)
+    }
+
+    /// Pays the vested part of the approved amount of a claim, signed by the owner.
+    pub fn release_claim(ctx: Context<ReleaseClaim>) -> Result<()> {
+        release_claim::handler(ctx)
+    }
+
+    /// Revokes the unvested amount of a claim and ends its vesting, signed by the vault authority.
+    pub fn revoke_claim(ctx: Context<RevokeClaim>) -> Result<()> {
+        revoke_claim::handler(ctx)
+    }
+
+    /// Creates the vault's associated token account of a reward mint.
+    pub fn create_reward_vault(ctx: Context<CreateRewardVault>) -> Result<()> {
+        create_reward_vault::handler(ctx)
+    }
+
+    /// Creates the claim of an owner for a reward mint.
+    pub fn create_token_claim(ctx: Context<CreateTokenClaim>) -> Result<()> {
+        create_token_claim::handler(ctx)
+    }
+
+    /// Same as `update_claim`, but transfers reward tokens into the owner's associated token account.
+    pub fn update_token_claim<'a, 'b, 'c, 'info>(
+        ctx: Context<'a, 'b, 'c, 'info, UpdateTokenClaim<'info>>,
+        args: UpdateClaimArgs,
+    ) -> Result<()> {
+        update_token_claim::handler(ctx, args)
+    }
+
+    /// Closes a claim, signed by the owner and co-signed or vouched by the vault authority.
+    ///
+    /// The claim is kept as a `ClosedClaim` tombstone so that it can not be created again
+    /// and replay old vouchers: the rent of the tombstone (about 0.00103 SOL) stays locked in it for good,
+    /// only the rest of the rent is refunded to the owner.
+    pub fn close_claim(ctx: Context<CloseClaim>, args: CloseClaimArgs) -> Result<()> {
+        close_claim::handler(ctx, args)
+    }
+
+    /// Re-creates a closed claim with its last nonce and claimed amount, paid by the payer.
+    pub fn reopen_claim(ctx: Context<ReopenClaim>) -> Result<()> {
+        reopen_claim::handler(ctx)
+    }
+
+    /// Publishes the merkle root of an epoch distribution, funded from the vault.
+    pub fn create_distribution(ctx: Context<CreateDistribution>, args: CreateDistributionArgs) -> Result<()> {
+        create_distribution::handler(ctx, args)
+    }
+
+    /// Claims a leaf of a distribution with its merkle proof, permissionless.
+    pub fn claim_distribution(ctx: Context<ClaimDistribution>, args: ClaimDistributionArgs) -> Result<()> {
+        claim_distribution::handler(ctx, args)
+    }
+
+    /// Returns the unclaimed amount of an expired distribution to the vault and its rent to the authority.
+    pub fn clawback_distribution(ctx: Context<ClawbackDistribution>) -> Result<()> {
+        clawback_distribution::handler(ctx)
+    }
+
+    /// Creates the staking pool of a vault, sharing its lamports between the stakers of `mint`.
+    pub fn create_stake_pool(ctx: Context<CreateStakePool>) -> Result<()> {
+        create_stake_pool::handler(ctx)
+    }
+
+    pub fn create_stake_account(ctx: Context<CreateStakeAccount>) -> Result<()> {
+        create_stake_account::handler(ctx)
+    }
+
+    /// Stakes tokens into a pool, settling the pending rewards first and syncing the emission weighted by the pool.
+    pub fn stake<'a, 'b, 'c, 'info>(ctx: Context<'a, 'b, 'c, 'info, Stake<'info>>, args: StakeArgs) -> Result<()> {
+        stake::handler(ctx, args)
+    }
+
+    /// Unstakes tokens from a pool, settling the pending rewards first and syncing the emission weighted by the pool.
+    pub fn unstake<'a, 'b, 'c, 'info>(
+        ctx: Context<'a, 'b, 'c, 'info, Unstake<'info>>,
+        args: UnstakeArgs,
+    ) -> Result<()> {
+        unstake::handler(ctx, args)
+    }
+
+    /// Pays the pending staking rewards of the owner from the vault.
+    pub fn claim_stake_rewards(ctx: Context<ClaimStakeRewards>) -> Result<()> {
+        claim_stake_rewards::handler(ctx)
+    }
+
+    /// Creates the emission of a vault, streaming `rate` lamports per second between its start and end time.
+    pub fn create_emission(ctx: Context<CreateEmission>, args: CreateEmissionArgs) -> Result<()> {
+        create_emission::handler(ctx, args)
+    }
+
+    /// Changes the rate and end time of an emission, signed by the vault authority.
+    pub fn set_emission(ctx: Context<SetEmission>, args: SetEmissionArgs) -> Result<()> {
+        set_emission::handler(ctx, args)
+    }
+
+    pub fn create_emission_share(ctx: Context<CreateEmissionShare>) -> Result<()> {
+        create_emission_share::handler(ctx)
+    }
+
+    /// Sets the weight of a share, signed by the vault authority.
+    pub fn set_emission_weight(ctx: Context<SetEmissionWeight>, args: SetEmissionWeightArgs) -> Result<()> {
+        set_emission_weight::handler(ctx, args)
+    }
+
+    /// Sets the weight of a share to the staked amount of its owner in the emission stake pool,
+    /// for the stakes made before the emission, later ones are synced by `stake` and `unstake`.
+    pub fn sync_emission_weight(ctx: Context<SyncEmissionWeight>) -> Result<()> {
+        sync_emission_weight::handler(ctx)
+    }
+
+    /// Pays the lamports accrued by a share from the vault.
+    pub fn claim_emission(ctx: Context<ClaimEmission>) -> Result<()> {
+        claim_emission::handler(ctx)
+    }
 }
@@ -0,0 +1,60 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::keccak::hashv;
+
+/// Prefixes of the hashed leaves and intermediate nodes,
+/// so that an intermediate node can not be presented as a leaf.
+const LEAF_PREFIX: &[u8] = &[0];
+const INTERMEDIATE_PREFIX: &[u8] = &[1];
+
+/// Hash of the leaf entitling `owner` to `amount` at `index` of a distribution.
+pub fn leaf_hash(index: u32, owner: &Pubkey, amount: u64) -> [u8; 32] {
+    let data = hashv(&[&index.to_le_bytes(), owner.as_ref(), &amount.to_le_bytes()]);
+    hashv(&[LEAF_PREFIX, &data.0]).0
+}
+
+/// Verifies that `leaf` is in the tree of `root`, the siblings of each level are hashed in sorted order.
+pub fn verify(proof: &[[u8; 32]], root: &[u8; 32], leaf: [u8; 32]) -> bool {
+    let computed = proof.iter().fold(leaf, |node, sibling| {
+        if node <= *sibling {
+            hashv(&[INTERMEDIATE_PREFIX, &node, sibling]).0
+        } else {
+            hashv(&[INTERMEDIATE_PREFIX, sibling, &node]).0
+        }
+    });
+    computed == *root
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parent(a: [u8; 32], b: [u8; 32]) -> [u8; 32] {
+        if a <= b {
+            hashv(&[INTERMEDIATE_PREFIX, &a, &b]).0
+        } else {
+            hashv(&[INTERMEDIATE_PREFIX, &b, &a]).0
+        }
+    }
+
+    #[test]
+    fn test_verify() {
+        let owners: Vec<Pubkey> = (0..3).map(|_| Pubkey::new_unique()).collect();
+        let leaves: Vec<[u8; 32]> = owners
+            .iter()
+            .enumerate()
+            .map(|(i, owner)| leaf_hash(i as u32, owner, 100 * (i as u64 + 1)))
+            .collect();
+        let left = parent(leaves[0], leaves[1]);
+        let root = parent(left, leaves[2]);
+
+        assert!(verify(&[leaves[1], leaves[2]], &root, leaves[0]));
+        assert!(verify(&[leaves[0], leaves[2]], &root, leaves[1]));
+        assert!(verify(&[left], &root, leaves[2]));
+
+        // wrong amount, index or owner
+        assert!(!verify(&[leaves[1], leaves[2]], &root, leaf_hash(0, &owners[0], 101)));
+        assert!(!verify(&[leaves[1], leaves[2]], &root, leaf_hash(1, &owners[0], 100)));
+        assert!(!verify(&[leaves[1], leaves[2]], &root, leaf_hash(0, &owners[1], 100)));
+        assert!(!verify(&[], &root, leaves[0]));
+    }
+}
@@ -1,23 +1,253 @@
 use crate::errors::Error;
 use anchor_lang::prelude::*;
 
+/// Releases an amount linearly from `start_time` to `start_time + duration`, nothing before `cliff_time`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct VestingSchedule {
+    pub start_time: i64,
+    pub cliff_time: i64,
+    pub duration: i64,
+}
+
+impl VestingSchedule {
+    pub fn validate(&self) -> Result<()> {
+        require!(
+            self.duration >= 0
+                && self.start_time <= self.cliff_time
+                && self.cliff_time - self.start_time <= self.duration,
+            Error::InvalidVestingSchedule
+        );
+        Ok(())
+    }
+
+    /// The vested part of `total` at `now`.
+    pub fn vested(&self, total: u64, now: i64) -> u64 {
+        if now < self.cliff_time {
+            0
+        } else if now - self.start_time >= self.duration {
+            total
+        } else {
+            (total as u128 * (now - self.start_time) as u128 / self.duration as u128) as u64
+        }
+    }
+}
+
+/// Moves a cumulative `claimed` amount up to `next_claimed`, returns the difference.
+///
+/// Only the cumulative amount has to increase, so claims prepared concurrently can land in any order
+/// and a replayed claim fails. The nonce is kept as the highest one seen.
+pub fn advance_claimed(nonce: &mut u32, claimed: &mut u64, next_nonce: u32, next_claimed: u64) -> Result<u64> {
+    require!(*claimed < next_claimed, Error::ClaimedAmountUnexpected);
+
+    let delta = next_claimed - *claimed;
+    *nonce = (*nonce).max(next_nonce);
+    *claimed = next_claimed;
+    msg!("delta:{}", delta);
+    Ok(delta)
+}
+
 #[account]
 pub struct Claim {
-    pub nonce: u32,   // 4, Nonce of the claim.
-    pub claimed: u64, // 8, Amount claimed.
+    pub nonce: u32,                       // 4, Nonce of the claim.
+    pub claimed: u64,                     // 8, Amount approved by the authority, including unreleased and revoked.
+    pub delegate: Option<Pubkey>,         // 33, Can sign claims instead of the owner.
+    pub recipient: Option<Pubkey>,        // 33, Receives the claims signed by the delegate, the owner if not set.
+    pub vesting: Option<VestingSchedule>, // 25, Releases the approved amount gradually if set.
+    pub unreleased: u64,                  // 8, Amount approved but not vested or not paid yet.
+    pub revoked: u64,                     // 8, Unvested amount revoked by the authority.
 }
 
 impl Claim {
-    pub const LEN: usize = 8 + 4 + 8 + 20; // 20 bytes padding.
+    /// Claims created before delegates have a length of 40 bytes
+    /// and are reallocated when a delegate or a vesting schedule is set.
+    pub const LEN: usize = 8 + 4 + 8 + 33 + 33 + 25 + 8 + 8 + 20; // 20 bytes padding.
+
+    /// The recipient of the claims signed by the delegate.
+    pub fn approved_recipient(&self, owner: &Pubkey) -> Pubkey {
+        self.recipient.unwrap_or(*owner)
+    }
+
+    /// Approves the amount up to `next_claimed`, returns the amount to pay, which is the vested part only.
+    pub fn update_claim(&mut self, next_nonce: u32, next_claimed: u64, now: i64) -> Result<u64> {
+        let delta = advance_claimed(&mut self.nonce, &mut self.claimed, next_nonce, next_claimed)?;
+        self.unreleased += delta;
+        Ok(self.release(now))
+    }
+
+    /// Takes the vested amount not paid yet, returns the amount to pay.
+    pub fn release(&mut self, now: i64) -> u64 {
+        let entitled = self.claimed - self.revoked;
+        let paid = entitled - self.unreleased;
+        let vested = match &self.vesting {
+            Some(vesting) => vesting.vested(entitled, now),
+            None => entitled,
+        };
+
+        let amount = vested.saturating_sub(paid).min(self.unreleased);
+        self.unreleased -= amount;
+        amount
+    }
+
+    /// Revokes the amount not vested at `now` and ends the vesting, returns the revoked amount.
+    pub fn revoke(&mut self, now: i64) -> u64 {
+        let Some(vesting) = self.vesting.take() else {
+            return 0;
+        };
+        let entitled = self.claimed - self.revoked;
+        // the amount already paid can exceed the vested part when the schedule was set later
+        let unvested = (entitled - vesting.vested(entitled, now)).min(self.unreleased);
+
+        self.revoked += unvested;
+        self.unreleased -= unvested;
+        unvested
+    }
+}
+
+/// What is left of a claim closed by `close_claim`.
+///
+/// It keeps the address in use, so that the claim can not be created again from zero
+/// and replay the vouchers of its old claimed amounts, and `reopen_claim` resumes from it.
+#[account]
+pub struct ClosedClaim {
+    pub nonce: u32,   // 4
+    pub claimed: u64, // 8
+}
+
+impl ClosedClaim {
+    pub const LEN: usize = 8 + 4 + 8;
+
+    pub fn reopen(&self) -> Claim {
+        Claim {
+            nonce: self.nonce,
+            claimed: self.claimed,
+            delegate: None,
+            recipient: None,
+            vesting: None,
+            unreleased: 0,
+            revoked: 0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn setup_claim(vesting: Option<VestingSchedule>) -> Claim {
+        Claim {
+            nonce: 0,
+            claimed: 0,
+            delegate: None,
+            recipient: None,
+            vesting,
+            unreleased: 0,
+            revoked: 0,
+        }
+    }
+
+    fn schedule() -> VestingSchedule {
+        VestingSchedule {
+            start_time: 1_000,
+            cliff_time: 1_250,
+            duration: 1_000,
+        }
+    }
+
+    #[test]
+    fn test_validate() {
+        assert!(schedule().validate().is_ok());
+        let mut vesting = schedule();
+        vesting.cliff_time = 999;
+        assert!(vesting.validate().is_err());
+        vesting.cliff_time = 2_001;
+        assert!(vesting.validate().is_err());
+        vesting.duration = -1;
+        vesting.cliff_time = 1_000;
+        assert!(vesting.validate().is_err());
+    }
+
+    #[test]
+    fn test_without_vesting() {
+        let mut claim = setup_claim(None);
+        assert_eq!(claim.update_claim(1, 100, 0).unwrap(), 100);
+        assert_eq!(claim.update_claim(2, 250, 0).unwrap(), 150);
+        assert_eq!(claim.unreleased, 0);
+        assert_eq!(claim.revoke(0), 0);
+        // replayed or stale claims
+        assert!(claim.update_claim(2, 250, 0).is_err());
+        assert!(claim.update_claim(1, 200, 0).is_err());
+        // nonces are not sequenced
+        assert_eq!(claim.update_claim(4, 300, 0).unwrap(), 50);
+        assert_eq!(claim.update_claim(3, 310, 0).unwrap(), 10);
+        assert_eq!(claim.nonce, 4);
+    }
+
+    #[test]
+    fn test_vesting() {
+        let mut claim = setup_claim(Some(schedule()));
+        // before the cliff
+        assert_eq!(claim.update_claim(1, 1_000, 1_100).unwrap(), 0);
+        assert_eq!(claim.release(1_249), 0);
+        // the cliff releases the part vested since the start
+        assert_eq!(claim.release(1_250), 250);
+        // newly approved amounts are vested on the same schedule
+        assert_eq!(claim.update_claim(2, 2_000, 1_500).unwrap(), 750);
+        assert_eq!(claim.release(1_500), 0);
+        assert_eq!(claim.release(3_000), 1_000);
+        assert_eq!(claim.unreleased, 0);
+        // fully vested
+        assert_eq!(claim.update_claim(3, 2_100, 3_000).unwrap(), 100);
+    }
+
+    #[test]
+    fn test_reopen_closed_claim() {
+        let mut claim = setup_claim(None);
+        claim.update_claim(2, 250, 0).unwrap();
+        let closed = ClosedClaim {
+            nonce: claim.nonce,
+            claimed: claim.claimed,
+        };
+        let mut data = vec![];
+        closed.try_serialize(&mut data).unwrap();
+        assert_eq!(data.len(), ClosedClaim::LEN);
+        assert!(Claim::try_deserialize(&mut data.as_slice()).is_err());
+
+        let mut reopened = ClosedClaim::try_deserialize(&mut data.as_slice()).unwrap().reopen();
+        assert_eq!(reopened.nonce, 2);
+        assert_eq!(reopened.claimed, 250);
+        // the old claims can not be replayed
+        assert!(reopened.update_claim(2, 250, 0).is_err());
+        assert_eq!(reopened.update_claim(3, 300, 0).unwrap(), 50);
+    }
+
+    #[test]
+    fn test_revoke() {
+        let mut claim = setup_claim(Some(schedule()));
+        assert_eq!(claim.update_claim(1, 1_000, 1_300).unwrap(), 300);
+
+        // vested at revocation but not paid stays releasable
+        assert_eq!(claim.revoke(1_600), 400);
+        assert_eq!(claim.vesting, None);
+        assert_eq!(claim.unreleased, 300);
+        assert_eq!(claim.release(1_600), 300);
+        assert_eq!(claim.release(5_000), 0);
+        assert_eq!(claim.revoke(5_000), 0);
+
+        // amounts approved after the revocation are paid instantly
+        assert_eq!(claim.update_claim(2, 1_100, 5_000).unwrap(), 100);
+        assert_eq!(claim.revoked, 400);
+    }
 
-    pub fn update_claim(&mut self, next_nonce: u32, next_claimed: u64) -> Result<u64> {
-        require!(self.nonce + 1 == next_nonce, Error::NonceUnexpected);
-        require!(self.claimed < next_claimed, Error::ClaimedAmountUnexpected);
+    #[test]
+    fn test_revoke_after_paid() {
+        let mut claim = setup_claim(None);
+        assert_eq!(claim.update_claim(1, 1_000, 0).unwrap(), 1_000);
 
-        let delta = next_claimed - self.claimed;
-        self.nonce = next_nonce;
-        self.claimed = next_claimed;
-        msg!("delta:{}", delta);
-        Ok(delta)
+        // a schedule set after the payment only holds back the new amounts
+        claim.vesting = Some(schedule());
+        assert_eq!(claim.update_claim(2, 1_500, 1_100).unwrap(), 0);
+        assert_eq!(claim.revoke(1_100), 500);
+        assert_eq!(claim.unreleased, 0);
+        assert_eq!(claim.revoked, 500);
     }
 }
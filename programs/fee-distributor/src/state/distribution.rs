@@ -0,0 +1,62 @@
+use crate::errors::Error;
+use crate::merkle;
+use anchor_lang::prelude::*;
+
+/// The lamports distributed to a set of owners in an epoch, committed by a merkle root.
+#[account]
+pub struct Distribution {
+    pub vault: Pubkey,           // 32
+    pub epoch: u64,              // 8
+    pub merkle_root: [u8; 32],   // 32
+    pub total_amount: u64,       // 8, Sum of the amounts of all leaves.
+    pub claimed_amount: u64,     // 8
+    pub expiry: i64,             // 8, Unix timestamp after which claims are closed and the rest can be clawed back.
+    pub num_nodes: u32,          // 4, Number of leaves.
+    pub claimed_bitmap: Vec<u8>, // 4 + (num_nodes + 7) / 8
+}
+
+impl Distribution {
+    pub fn space(num_nodes: u32) -> usize {
+        8 + 32 + 8 + 32 + 8 + 8 + 8 + 4 + 4 + (num_nodes as usize).div_ceil(8)
+    }
+
+    pub fn initialize(
+        &mut self,
+        vault: Pubkey,
+        epoch: u64,
+        merkle_root: [u8; 32],
+        total_amount: u64,
+        expiry: i64,
+        num_nodes: u32,
+    ) {
+        self.vault = vault;
+        self.epoch = epoch;
+        self.merkle_root = merkle_root;
+        self.total_amount = total_amount;
+        self.claimed_amount = 0;
+        self.expiry = expiry;
+        self.num_nodes = num_nodes;
+        self.claimed_bitmap = vec![0; (num_nodes as usize).div_ceil(8)];
+    }
+
+    /// Marks the leaf at `index` as claimed after verifying its proof.
+    pub fn claim(&mut self, index: u32, owner: &Pubkey, amount: u64, proof: &[[u8; 32]], now: i64) -> Result<()> {
+        require!(now <= self.expiry, Error::DistributionExpired);
+        require!(index < self.num_nodes, Error::InvalidMerkleProof);
+        require!(
+            merkle::verify(proof, &self.merkle_root, merkle::leaf_hash(index, owner, amount)),
+            Error::InvalidMerkleProof
+        );
+
+        let (byte, bit) = ((index / 8) as usize, 1u8 << (index % 8));
+        require!(self.claimed_bitmap[byte] & bit == 0, Error::AlreadyClaimed);
+        self.claimed_bitmap[byte] |= bit;
+
+        self.claimed_amount = self
+            .claimed_amount
+            .checked_add(amount)
+            .filter(|claimed| *claimed <= self.total_amount)
+            .ok_or(Error::DistributionOverclaimed)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,222 @@
+use crate::errors::Error;
+use crate::state::{StakeAccount, StakePool, REWARD_PRECISION};
+use anchor_lang::prelude::*;
+
+/// Lamports of a vault streamed to its participants at a constant rate, pro-rata to their weights.
+#[account]
+pub struct Emission {
+    pub vault: Pubkey,              // 32
+    pub stake_pool: Option<Pubkey>, // 33, Weights are the staked amounts in this pool if set, set by the authority otherwise.
+    pub rate: u64,                  // 8, Lamports emitted per second.
+    pub start_time: i64,            // 8
+    pub end_time: i64,              // 8
+    pub last_update_time: i64,      // 8
+    pub total_weight: u64,          // 8
+    pub reward_per_weight: u128,    // 16, Emitted lamports per weight unit, scaled by `REWARD_PRECISION`.
+}
+
+impl Emission {
+    pub const LEN: usize = 8 + 32 + 33 + 8 + 8 + 8 + 8 + 8 + 16 + 32; // 32 bytes padding.
+
+    pub fn initialize(
+        &mut self,
+        vault: Pubkey,
+        stake_pool: Option<Pubkey>,
+        rate: u64,
+        start_time: i64,
+        end_time: i64,
+    ) -> Result<()> {
+        require!(start_time < end_time, Error::InvalidEmissionWindow);
+        self.vault = vault;
+        self.stake_pool = stake_pool;
+        self.rate = rate;
+        self.start_time = start_time;
+        self.end_time = end_time;
+        self.last_update_time = start_time;
+        self.total_weight = 0;
+        self.reward_per_weight = 0;
+        Ok(())
+    }
+
+    /// Changes the rate and the end of the window, the lamports emitted so far are kept.
+    /// Extending an ended window resumes the emission from `now`, not from the previous end.
+    pub fn configure(&mut self, rate: u64, end_time: i64, now: i64) -> Result<()> {
+        self.update(now)?;
+        require!(
+            self.start_time < end_time && self.last_update_time <= end_time,
+            Error::InvalidEmissionWindow
+        );
+        self.rate = rate;
+        self.end_time = end_time;
+        self.last_update_time = self.last_update_time.max(now.min(end_time));
+        Ok(())
+    }
+
+    /// Accrues the lamports emitted since the last update, nothing is emitted while the total weight is zero.
+    pub fn update(&mut self, now: i64) -> Result<()> {
+        let until = now.min(self.end_time);
+        if until <= self.last_update_time {
+            return Ok(());
+        }
+
+        if self.total_weight > 0 {
+            let emitted = self.rate as u128 * (until - self.last_update_time) as u128;
+            let increment =
+                emitted.checked_mul(REWARD_PRECISION).ok_or(Error::MathOverflow)? / self.total_weight as u128;
+            self.reward_per_weight = self
+                .reward_per_weight
+                .checked_add(increment)
+                .ok_or(Error::MathOverflow)?;
+        }
+        self.last_update_time = until;
+        Ok(())
+    }
+
+    /// Settles the accrued lamports of `share` and changes its weight.
+    pub fn set_weight(&mut self, share: &mut EmissionShare, weight: u64, now: i64) -> Result<()> {
+        self.update(now)?;
+        share.settle(self)?;
+        self.total_weight = (self.total_weight - share.weight)
+            .checked_add(weight)
+            .ok_or(Error::MathOverflow)?;
+        share.weight = weight;
+        share.reward_debt = share.accrued(self)?;
+        Ok(())
+    }
+
+    /// Sets the weight of the share of a staker to its staked amount after a stake or unstake,
+    /// the share is required when the pool weights an emission so that the weight never outlives the stake.
+    pub fn sync_stake(
+        pool: &Account<StakePool>,
+        stake: &StakeAccount,
+        emission: Option<&mut Account<Emission>>,
+        share: Option<&mut Account<EmissionShare>>,
+        now: i64,
+    ) -> Result<()> {
+        if !pool.has_emission {
+            return Ok(());
+        }
+        let (Some(emission), Some(share)) = (emission, share) else {
+            return err!(Error::MissingEmissionShare);
+        };
+        require!(
+            emission.stake_pool == Some(pool.key()) && share.emission == emission.key() && share.owner == stake.owner,
+            Error::StakePoolMismatch
+        );
+        emission.set_weight(share, stake.amount, now)
+    }
+
+    /// Takes the accrued lamports of `share`, returns the lamports to pay.
+    pub fn claim(&mut self, share: &mut EmissionShare, now: i64) -> Result<u64> {
+        self.update(now)?;
+        share.settle(self)?;
+        let amount = share.pending;
+        share.pending = 0;
+        Ok(amount)
+    }
+}
+
+/// The weight of an owner in an emission.
+#[account]
+pub struct EmissionShare {
+    pub emission: Pubkey,  // 32
+    pub owner: Pubkey,     // 32
+    pub weight: u64,       // 8
+    pub reward_debt: u128, // 16, Lamports of `weight` already settled, scaled down.
+    pub pending: u64,      // 8, Settled lamports not claimed yet.
+}
+
+impl EmissionShare {
+    pub const LEN: usize = 8 + 32 + 32 + 8 + 16 + 8 + 32; // 32 bytes padding.
+
+    fn accrued(&self, emission: &Emission) -> Result<u128> {
+        let accrued = (self.weight as u128)
+            .checked_mul(emission.reward_per_weight)
+            .ok_or(Error::MathOverflow)?;
+        Ok(accrued / REWARD_PRECISION)
+    }
+
+    fn settle(&mut self, emission: &Emission) -> Result<()> {
+        let accrued = self.accrued(emission)?;
+        let rewards = u64::try_from(accrued - self.reward_debt).map_err(|_| Error::MathOverflow)?;
+        self.pending = self.pending.checked_add(rewards).ok_or(Error::MathOverflow)?;
+        self.reward_debt = accrued;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn setup_emission() -> Emission {
+        let mut emission = Emission {
+            vault: Pubkey::new_unique(),
+            stake_pool: None,
+            rate: 0,
+            start_time: 0,
+            end_time: 0,
+            last_update_time: 0,
+            total_weight: 0,
+            reward_per_weight: 0,
+        };
+        emission.initialize(emission.vault, None, 100, 1_000, 2_000).unwrap();
+        emission
+    }
+
+    fn setup_share(emission: &Emission) -> EmissionShare {
+        EmissionShare {
+            emission: emission.vault,
+            owner: Pubkey::new_unique(),
+            weight: 0,
+            reward_debt: 0,
+            pending: 0,
+        }
+    }
+
+    #[test]
+    fn test_initialize() {
+        let mut emission = setup_emission();
+        assert!(emission.initialize(emission.vault, None, 100, 2_000, 2_000).is_err());
+    }
+
+    #[test]
+    fn test_emission_window() {
+        let mut emission = setup_emission();
+        let mut alice = setup_share(&emission);
+        let mut bob = setup_share(&emission);
+
+        // weights set before the start accrue nothing until the start
+        emission.set_weight(&mut alice, 1, 500).unwrap();
+        assert_eq!(emission.claim(&mut alice, 1_000).unwrap(), 0);
+
+        // alice alone for 100 seconds, then shares 1:3 with bob for 100 seconds
+        emission.set_weight(&mut bob, 3, 1_100).unwrap();
+        assert_eq!(emission.claim(&mut alice, 1_200).unwrap(), 10_000 + 2_500);
+        assert_eq!(emission.claim(&mut bob, 1_200).unwrap(), 7_500);
+
+        // nothing is emitted after the end
+        assert_eq!(emission.claim(&mut alice, 3_000).unwrap(), 20_000);
+        assert_eq!(emission.claim(&mut bob, 5_000).unwrap(), 60_000);
+        assert_eq!(emission.claim(&mut bob, 6_000).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_configure() {
+        let mut emission = setup_emission();
+        let mut alice = setup_share(&emission);
+        emission.set_weight(&mut alice, 10, 1_000).unwrap();
+
+        emission.configure(200, 1_500, 1_100).unwrap();
+        assert!(emission.configure(200, 1_050, 1_200).is_err());
+        assert_eq!(emission.claim(&mut alice, 2_000).unwrap(), 10_000 + 80_000);
+
+        // extending the ended window does not emit for the gap
+        emission.configure(100, 3_000, 2_000).unwrap();
+        assert_eq!(emission.claim(&mut alice, 2_100).unwrap(), 10_000);
+
+        // removing the weight stops the accrual
+        emission.set_weight(&mut alice, 0, 2_100).unwrap();
+        assert_eq!(emission.claim(&mut alice, 2_500).unwrap(), 0);
+    }
+}
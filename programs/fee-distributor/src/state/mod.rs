@@ -1,5 +1,13 @@
 pub mod claim;
+pub mod distribution;
+pub mod emission;
+pub mod stake_pool;
+pub mod token_claim;
 pub mod vault;
 
 pub use claim::*;
+pub use distribution::*;
+pub use emission::*;
+pub use stake_pool::*;
+pub use token_claim::*;
 pub use vault::*;
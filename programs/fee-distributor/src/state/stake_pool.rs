@@ -0,0 +1,219 @@
+use crate::errors::Error;
+use anchor_lang::prelude::*;
+
+/// Seed of the PDA owning the staked tokens of all pools, allowlisted by the hooks program.
+pub const STAKE_AUTHORITY_SEED: &[u8] = b"stake_authority";
+
+/// Scale of `StakePool::reward_per_share`.
+pub const REWARD_PRECISION: u128 = 1_000_000_000_000;
+
+/// A pool sharing the lamports of its vault pro-rata between the stakers of a token.
+///
+/// The pool accounts for every lamport of the vault above its rent-exempt minimum,
+/// so the other payouts of the vault are disabled once it has a pool (see `Vault::has_stake_pool`).
+#[account]
+pub struct StakePool {
+    pub vault: Pubkey,          // 32
+    pub mint: Pubkey,           // 32, Mint of the staked token.
+    pub token_account: Pubkey,  // 32, Holds the staked tokens, owned by the stake authority.
+    pub total_staked: u64,      // 8
+    pub reward_per_share: u128, // 16, Rewards per staked token unit, scaled by `REWARD_PRECISION`.
+    pub accounted_rewards: u64, // 8, Rewards in the vault already accounted but not claimed, undistributed included.
+    /// Rewards received while nothing was staked, withdrawable by the vault authority.
+    pub undistributed_rewards: u64, // 8
+    /// The weights of an emission are the staked amounts, kept in sync by `stake` and `unstake`.
+    pub has_emission: bool, // 1
+}
+
+impl StakePool {
+    pub const LEN: usize = 8 + 32 + 32 + 32 + 8 + 16 + 8 + 8 + 1 + 23; // 23 bytes padding.
+
+    /// Distributes the lamports of the vault that are not accounted yet to the current stakers,
+    /// `available` is the vault balance above its rent-exempt minimum.
+    ///
+    /// Without stakers, the new lamports are set aside as undistributed rewards,
+    /// otherwise the next staker would take all of them.
+    pub fn update(&mut self, available: u64) -> Result<()> {
+        let new_rewards = available.saturating_sub(self.accounted_rewards);
+        if new_rewards == 0 {
+            return Ok(());
+        }
+        if self.total_staked == 0 {
+            self.undistributed_rewards += new_rewards;
+            self.accounted_rewards += new_rewards;
+            return Ok(());
+        }
+
+        let increment = new_rewards as u128 * REWARD_PRECISION / self.total_staked as u128;
+        self.reward_per_share = self
+            .reward_per_share
+            .checked_add(increment)
+            .ok_or(Error::MathOverflow)?;
+        self.accounted_rewards += new_rewards;
+        Ok(())
+    }
+
+    pub fn stake(&mut self, stake: &mut StakeAccount, amount: u64) -> Result<()> {
+        stake.settle(self)?;
+        self.total_staked = self.total_staked.checked_add(amount).ok_or(Error::MathOverflow)?;
+        stake.amount += amount;
+        stake.reward_debt = stake.accrued(self)?;
+        Ok(())
+    }
+
+    pub fn unstake(&mut self, stake: &mut StakeAccount, amount: u64) -> Result<()> {
+        require!(amount <= stake.amount, Error::InsufficientStake);
+        stake.settle(self)?;
+        self.total_staked -= amount;
+        stake.amount -= amount;
+        stake.reward_debt = stake.accrued(self)?;
+        Ok(())
+    }
+
+    /// Takes `amount` of the undistributed rewards out of the pool.
+    pub fn withdraw_undistributed(&mut self, amount: u64) -> Result<()> {
+        require!(
+            amount <= self.undistributed_rewards,
+            Error::InsufficientUndistributedRewards
+        );
+        self.undistributed_rewards -= amount;
+        self.accounted_rewards -= amount;
+        Ok(())
+    }
+
+    /// Takes the pending rewards of `stake`, returns the lamports to pay.
+    pub fn claim(&mut self, stake: &mut StakeAccount) -> Result<u64> {
+        stake.settle(self)?;
+        let amount = stake.pending;
+        stake.pending = 0;
+        self.accounted_rewards -= amount;
+        Ok(amount)
+    }
+}
+
+/// The stake of an owner in a pool.
+#[account]
+pub struct StakeAccount {
+    pub pool: Pubkey,      // 32
+    pub owner: Pubkey,     // 32
+    pub amount: u64,       // 8, Staked amount.
+    pub reward_debt: u128, // 16, Rewards of `amount` already settled, scaled down.
+    pub pending: u64,      // 8, Settled rewards not claimed yet.
+}
+
+impl StakeAccount {
+    pub const LEN: usize = 8 + 32 + 32 + 8 + 16 + 8 + 32; // 32 bytes padding.
+
+    fn accrued(&self, pool: &StakePool) -> Result<u128> {
+        let accrued = (self.amount as u128)
+            .checked_mul(pool.reward_per_share)
+            .ok_or(Error::MathOverflow)?;
+        Ok(accrued / REWARD_PRECISION)
+    }
+
+    fn settle(&mut self, pool: &StakePool) -> Result<()> {
+        let accrued = self.accrued(pool)?;
+        let rewards = u64::try_from(accrued - self.reward_debt).map_err(|_| Error::MathOverflow)?;
+        self.pending = self.pending.checked_add(rewards).ok_or(Error::MathOverflow)?;
+        self.reward_debt = accrued;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn setup_pool() -> StakePool {
+        StakePool {
+            vault: Pubkey::new_unique(),
+            mint: Pubkey::new_unique(),
+            token_account: Pubkey::new_unique(),
+            total_staked: 0,
+            reward_per_share: 0,
+            accounted_rewards: 0,
+            undistributed_rewards: 0,
+            has_emission: false,
+        }
+    }
+
+    fn setup_stake(pool: &StakePool) -> StakeAccount {
+        StakeAccount {
+            pool: pool.vault,
+            owner: Pubkey::new_unique(),
+            amount: 0,
+            reward_debt: 0,
+            pending: 0,
+        }
+    }
+
+    #[test]
+    fn test_pro_rata_rewards() {
+        let mut pool = setup_pool();
+        let mut alice = setup_stake(&pool);
+        let mut bob = setup_stake(&pool);
+
+        pool.stake(&mut alice, 1_000_000).unwrap();
+        pool.update(1_000).unwrap();
+        pool.stake(&mut bob, 3_000_000).unwrap();
+        // vault received 4_000 more
+        pool.update(5_000).unwrap();
+
+        assert_eq!(pool.claim(&mut alice).unwrap(), 2_000);
+        assert_eq!(pool.claim(&mut bob).unwrap(), 3_000);
+        assert_eq!(pool.accounted_rewards, 0);
+        // nothing left to claim
+        assert_eq!(pool.claim(&mut alice).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_undistributed_rewards() {
+        let mut pool = setup_pool();
+        let mut alice = setup_stake(&pool);
+
+        // rewards before anyone stakes are not given to the first staker
+        pool.update(1_000).unwrap();
+        assert_eq!(pool.undistributed_rewards, 1_000);
+        pool.stake(&mut alice, 1_000).unwrap();
+        pool.update(1_000).unwrap();
+        assert_eq!(pool.claim(&mut alice).unwrap(), 0);
+
+        // only the new rewards are shared
+        pool.update(1_500).unwrap();
+        assert_eq!(pool.claim(&mut alice).unwrap(), 500);
+
+        // rewards after everyone unstaked are set aside again
+        pool.unstake(&mut alice, 1_000).unwrap();
+        pool.update(1_200).unwrap();
+        assert_eq!(pool.undistributed_rewards, 1_200);
+        assert_eq!(pool.accounted_rewards, 1_200);
+
+        assert!(pool.withdraw_undistributed(1_201).is_err());
+        pool.withdraw_undistributed(1_200).unwrap();
+        assert_eq!(pool.undistributed_rewards, 0);
+        assert_eq!(pool.accounted_rewards, 0);
+        pool.update(0).unwrap();
+        assert_eq!(pool.claim(&mut alice).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_unstake() {
+        let mut pool = setup_pool();
+        let mut alice = setup_stake(&pool);
+        let mut bob = setup_stake(&pool);
+
+        pool.stake(&mut alice, 1_000).unwrap();
+        pool.stake(&mut bob, 1_000).unwrap();
+        pool.update(2_000).unwrap();
+
+        assert!(pool.unstake(&mut alice, 1_001).is_err());
+        pool.unstake(&mut alice, 1_000).unwrap();
+        assert_eq!(pool.total_staked, 1_000);
+        assert_eq!(alice.pending, 1_000);
+
+        // rewards after unstaking go to the remaining stakers
+        pool.update(3_000).unwrap();
+        assert_eq!(pool.claim(&mut alice).unwrap(), 1_000);
+        assert_eq!(pool.claim(&mut bob).unwrap(), 2_000);
+    }
+}
@@ -0,0 +1,22 @@
+use crate::state::advance_claimed;
+use anchor_lang::prelude::*;
+
+/// Seed of the PDA owning the reward token accounts of all vaults, allowlisted by the hooks program.
+pub const REWARD_AUTHORITY_SEED: &[u8] = b"reward_authority";
+
+/// The claim of an owner for a reward token, seeded by the vault, the owner and the mint.
+#[account]
+pub struct TokenClaim {
+    pub mint: Pubkey, // 32, Mint of the reward token.
+    pub nonce: u32,   // 4, Nonce of the claim.
+    pub claimed: u64, // 8, Amount claimed.
+}
+
+impl TokenClaim {
+    pub const LEN: usize = 8 + 32 + 4 + 8 + 20; // 20 bytes padding.
+
+    /// Same as `Claim::update_claim` without vesting, returns the amount to pay.
+    pub fn update_claim(&mut self, next_nonce: u32, next_claimed: u64) -> Result<u64> {
+        advance_claimed(&mut self.nonce, &mut self.claimed, next_nonce, next_claimed)
+    }
+}
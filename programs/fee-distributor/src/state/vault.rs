@@ -1,30 +1,84 @@
+use crate::errors::Error;
 use anchor_lang::prelude::*;
 
+/// Id of the vault seeded by `[b"vault"]` only, created before vaults had ids.
+pub const LEGACY_VAULT_ID: u64 = 0;
+
 #[account]
 pub struct Vault {
-    pub authority: Pubkey, // 32
-    pub bump: [u8; 1],     // 1
+    pub authority: Pubkey,    // 32
+    pub bump: [u8; 1],        // 1
+    pub id: u64,              // 8
+    pub total_deposited: u64, // 8, Lamports deposited through `deposit`, fees sent directly are not included.
+    pub total_claimed: u64,   // 8, Lamports paid out by claims, including the funding of distributions.
+    pub has_stake_pool: bool, // 1, The lamports of the vault are paid out by its stake pool only.
+    pub has_emission: bool,   // 1, Part of the lamports of the vault are streamed by its emission.
 }
 
 impl Vault {
-    pub const LEN: usize = 8 + 32 + 1 + 31; // 31 bytes padding
-
-    pub fn seeds(&self) -> [&[u8]; 2] {
-        [b"vault", self.bump.as_ref()]
-    }
+    pub const LEN: usize = 8 + 32 + 1 + 8 + 8 + 8 + 1 + 1 + 5; // 5 bytes padding
 
-    pub fn initialize(&mut self, authority: Pubkey, program_id: &Pubkey) {
+    pub fn initialize(&mut self, id: u64, authority: Pubkey, bump: u8) {
         self.authority = authority;
-        let (_pub_key, bump) = Pubkey::find_program_address(&[b"vault"], program_id);
         self.bump = [bump];
+        self.id = id;
+    }
+
+    /// Lamports of the vault above its rent-exempt minimum.
+    pub fn available_lamports(vault: &Account<Vault>) -> Result<u64> {
+        let rent_exempt = Rent::get()?.minimum_balance(vault.to_account_info().data_len());
+        Ok(vault.get_lamports().saturating_sub(rent_exempt))
     }
 
+    /// Pays a claim, the vault never falls below its rent-exempt minimum.
     pub fn transfer<'info>(
-        &self,
+        vault: &mut Account<'info, Vault>,
+        recipient: &AccountInfo<'info>,
+        amount: u64,
+    ) -> Result<()> {
+        require!(!vault.has_stake_pool, Error::VaultHasStakePool);
+        Self::pay(vault, recipient, amount)
+    }
+
+    /// Pays the rewards of a staker, the only claim on the lamports of a vault with a stake pool.
+    pub fn transfer_stake_rewards<'info>(
+        vault: &mut Account<'info, Vault>,
+        recipient: &AccountInfo<'info>,
+        amount: u64,
+    ) -> Result<()> {
+        Self::pay(vault, recipient, amount)
+    }
+
+    fn pay<'info>(vault: &mut Account<'info, Vault>, recipient: &AccountInfo<'info>, amount: u64) -> Result<()> {
+        Self::debit(vault, recipient, amount)?;
+        vault.total_claimed = vault.total_claimed.saturating_add(amount);
+        Ok(())
+    }
+
+    /// Moves lamports out of the vault without counting them as claimed,
+    /// fails without paying anything if the vault would fall below its rent-exempt minimum.
+    ///
+    /// The lamports of a vault with a stake pool belong to the stakers,
+    /// only its undistributed rewards can be withdrawn, see `StakePool::withdraw_undistributed`.
+    pub fn withdraw<'info>(vault: &Account<'info, Vault>, recipient: &AccountInfo<'info>, amount: u64) -> Result<()> {
+        require!(!vault.has_stake_pool, Error::VaultHasStakePool);
+        Self::debit(vault, recipient, amount)
+    }
+
+    /// Same as `withdraw`, for the undistributed rewards of the stake pool of the vault.
+    pub fn withdraw_undistributed<'info>(
         vault: &Account<'info, Vault>,
-        recipient: &UncheckedAccount<'info>,
+        recipient: &AccountInfo<'info>,
         amount: u64,
     ) -> Result<()> {
+        Self::debit(vault, recipient, amount)
+    }
+
+    fn debit<'info>(vault: &Account<'info, Vault>, recipient: &AccountInfo<'info>, amount: u64) -> Result<()> {
+        require!(
+            amount <= Self::available_lamports(vault)?,
+            Error::InsufficientVaultBalance
+        );
         vault.sub_lamports(amount)?;
         recipient.add_lamports(amount)?;
         Ok(())
@@ -0,0 +1,200 @@
+use crate::errors::Error;
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::ed25519_program;
+use anchor_lang::solana_program::sysvar::instructions::{load_current_index_checked, load_instruction_at_checked};
+
+/// Prefix of the message signed by the vault authority to allow closing a claim.
+pub const CLOSE_VOUCHER_DOMAIN: &[u8] = b"fee_distributor:close_claim_voucher";
+
+/// Size of the offsets of each signature in the Ed25519 program instruction data.
+const SIGNATURE_OFFSETS_LEN: usize = 14;
+
+/// An off-chain proof of the vault authority that a claim has no unclaimed balance.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct CloseClaimVoucher {
+    pub claim: Pubkey,
+    /// The current nonce of the claim.
+    pub nonce: u32,
+    /// The current claimed amount of the claim.
+    pub claimed: u64,
+    /// Unix timestamp after which the voucher can no longer be used.
+    pub expiry: i64,
+}
+
+impl CloseClaimVoucher {
+    /// The message signed by the vault authority.
+    pub fn message(&self) -> Result<Vec<u8>> {
+        let mut message = CLOSE_VOUCHER_DOMAIN.to_vec();
+        self.serialize(&mut message)?;
+        Ok(message)
+    }
+
+    /// Verifies that the voucher is not expired and signed by `authority`
+    /// in the first signature of the Ed25519 program instruction right before the current one.
+    pub fn verify(&self, instructions_sysvar: &AccountInfo, authority: &Pubkey, now: i64) -> Result<()> {
+        require!(now <= self.expiry, Error::VoucherExpired);
+        let previous = previous_instruction_index(instructions_sysvar)?;
+        verify_ed25519_signature(instructions_sysvar, previous, 0, authority, &self.message()?)
+    }
+}
+
+/// Returns the index of the instruction right before the current one,
+/// where the Ed25519 program instruction is expected.
+fn previous_instruction_index(instructions_sysvar: &AccountInfo) -> Result<u16> {
+    let current = load_current_index_checked(instructions_sysvar)?;
+    current.checked_sub(1).ok_or(error!(Error::InvalidVoucherSignature))
+}
+
+/// Verifies that the Ed25519 program instruction at `instruction_index` contains a signature of `message`
+/// by `signer` at `signature_index`.
+///
+/// The signature itself is verified by the Ed25519 program, whose instruction would fail otherwise,
+/// so only the signed public key and message are checked here.
+fn verify_ed25519_signature(
+    instructions_sysvar: &AccountInfo,
+    instruction_index: u16,
+    signature_index: u8,
+    signer: &Pubkey,
+    message: &[u8],
+) -> Result<()> {
+    let ix = load_instruction_at_checked(instruction_index as usize, instructions_sysvar)?;
+    require_keys_eq!(ix.program_id, ed25519_program::ID, Error::InvalidVoucherSignature);
+
+    let data = ix.data.as_slice();
+    require!(
+        data.len() >= 2 && signature_index < data[0],
+        Error::InvalidVoucherSignature
+    );
+    let start = 2 + signature_index as usize * SIGNATURE_OFFSETS_LEN;
+    let offsets = data
+        .get(start..start + SIGNATURE_OFFSETS_LEN)
+        .ok_or(Error::InvalidVoucherSignature)?;
+    let read = |i: usize| u16::from_le_bytes([offsets[i], offsets[i + 1]]) as usize;
+    let (signature_ix, public_key_offset, public_key_ix) = (read(2), read(4), read(6));
+    let (message_offset, message_size, message_ix) = (read(8), read(10), read(12));
+
+    // All the data must be in the Ed25519 program instruction itself
+    let in_this_ix = u16::MAX as usize;
+    require!(
+        signature_ix == in_this_ix && public_key_ix == in_this_ix && message_ix == in_this_ix,
+        Error::InvalidVoucherSignature
+    );
+    let signed_public_key = data
+        .get(public_key_offset..public_key_offset + 32)
+        .ok_or(Error::InvalidVoucherSignature)?;
+    let signed_message = data
+        .get(message_offset..message_offset + message_size)
+        .ok_or(Error::InvalidVoucherSignature)?;
+    require!(
+        signed_public_key == signer.as_ref() && signed_message == message,
+        Error::InvalidVoucherSignature
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::solana_program::sysvar::instructions::{self, construct_instructions_data, BorrowedInstruction};
+
+    /// Builds the Ed25519 program instruction data with all data in the instruction, signature left zeroed.
+    fn ed25519_data(signatures: &[(Pubkey, Vec<u8>)]) -> Vec<u8> {
+        let header_len = 2 + signatures.len() * SIGNATURE_OFFSETS_LEN;
+        let mut data = vec![signatures.len() as u8, 0];
+        let mut body = vec![];
+        for (public_key, message) in signatures {
+            let public_key_offset = header_len + body.len();
+            body.extend_from_slice(public_key.as_ref());
+            let signature_offset = header_len + body.len();
+            body.extend_from_slice(&[0; 64]);
+            let message_offset = header_len + body.len();
+            body.extend_from_slice(message);
+            for value in [
+                signature_offset,
+                u16::MAX as usize,
+                public_key_offset,
+                u16::MAX as usize,
+                message_offset,
+                message.len(),
+                u16::MAX as usize,
+            ] {
+                data.extend_from_slice(&(value as u16).to_le_bytes());
+            }
+        }
+        data.extend(body);
+        data
+    }
+
+    /// Calls `f` with an instructions sysvar holding a single instruction.
+    fn with_instructions_sysvar<T>(ix_program_id: Pubkey, ix_data: &[u8], f: impl FnOnce(&AccountInfo) -> T) -> T {
+        let ixs = [BorrowedInstruction {
+            program_id: &ix_program_id,
+            accounts: vec![],
+            data: ix_data,
+        }];
+        let mut sysvar_data = construct_instructions_data(&ixs);
+        let mut lamports = 0;
+        let sysvar_info = AccountInfo::new(
+            &instructions::ID,
+            false,
+            false,
+            &mut lamports,
+            &mut sysvar_data,
+            &instructions::ID,
+            false,
+            0,
+        );
+        f(&sysvar_info)
+    }
+
+    fn verify(
+        ix_program_id: Pubkey,
+        ix_data: &[u8],
+        signature_index: u8,
+        signer: &Pubkey,
+        message: &[u8],
+    ) -> Result<()> {
+        with_instructions_sysvar(ix_program_id, ix_data, |sysvar_info| {
+            verify_ed25519_signature(sysvar_info, 0, signature_index, signer, message)
+        })
+    }
+
+    fn setup_voucher() -> CloseClaimVoucher {
+        CloseClaimVoucher {
+            claim: Pubkey::new_unique(),
+            nonce: 1,
+            claimed: 100,
+            expiry: 1_000,
+        }
+    }
+
+    #[test]
+    fn test_verify_ed25519_signature() {
+        let authority = Pubkey::new_unique();
+        let message = setup_voucher().message().unwrap();
+        let other = CloseClaimVoucher {
+            nonce: 2,
+            ..setup_voucher()
+        }
+        .message()
+        .unwrap();
+        let data = ed25519_data(&[(Pubkey::new_unique(), other.clone()), (authority, message.clone())]);
+
+        assert!(verify(ed25519_program::ID, &data, 1, &authority, &message).is_ok());
+        assert!(verify(ed25519_program::ID, &data, 0, &authority, &message).is_err());
+        assert!(verify(ed25519_program::ID, &data, 1, &authority, &other).is_err());
+        assert!(verify(ed25519_program::ID, &data, 2, &authority, &message).is_err());
+        assert!(verify(Pubkey::new_unique(), &data, 1, &authority, &message).is_err());
+    }
+
+    #[test]
+    fn test_voucher_message() {
+        let voucher = setup_voucher();
+        let message = voucher.message().unwrap();
+        assert!(message.starts_with(CLOSE_VOUCHER_DOMAIN));
+        assert_eq!(
+            CloseClaimVoucher::try_from_slice(&message[CLOSE_VOUCHER_DOMAIN.len()..]).unwrap(),
+            voucher
+        );
+    }
+}
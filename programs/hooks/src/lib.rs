@@ -10,6 +10,20 @@ pub mod errors;
 
 declare_id!("burnhzSCeNMFuTsQJRC8dc1EPffWAecnYk8CxxRuQzT");
 
+// The burn program invokes `initialize_account_meta_list` by its discriminator, as it cannot depend on this crate.
+const _: () = {
+    let expected = <instruction::InitializeAccountMetaList as anchor_lang::Discriminator>::DISCRIMINATOR;
+    let actual = burn::constants::INITIALIZE_ACCOUNT_META_LIST_DISCRIMINATOR;
+    let mut i = 0;
+    while i < expected.len() {
+        assert!(
+            expected[i] == actual[i],
+            "INITIALIZE_ACCOUNT_META_LIST_DISCRIMINATOR mismatch"
+        );
+        i += 1;
+    }
+};
+
 #[program]
 pub mod hooks {
     use anchor_lang::system_program;
@@ -124,6 +138,10 @@ pub mod utils {
     use burn::state::Market;
 
     const BLACK_HOLE: Pubkey = pubkey!("1nc1nerator11111111111111111111111111111111");
+    /// Owner of the tokens staked in the fee distributor pools, PDA of `[b"stake_authority"]`.
+    const STAKE_AUTHORITY: Pubkey = pubkey!("EdAtWwxdhnHtpjx2odsztQpmzRQjvwac7ud9Hrxcr1jz");
+    /// Owner of the reward tokens of the fee distributor vaults, PDA of `[b"reward_authority"]`.
+    const REWARD_AUTHORITY: Pubkey = pubkey!("3Tco9nYpZzpodieq1u8LyFpfHdpWbj9cFYWAundodUPE");
 
     pub fn is_transfer_allowed<'info>(
         market: &Account<'info, Market>,
@@ -144,7 +162,7 @@ pub mod utils {
     }
 
     pub fn is_allowed_owner(owner: &Pubkey, market: &Pubkey) -> bool {
-        owner.eq(market) || owner.eq(&BLACK_HOLE)
+        owner.eq(market) || owner.eq(&BLACK_HOLE) || owner.eq(&STAKE_AUTHORITY) || owner.eq(&REWARD_AUTHORITY)
     }
 
     pub fn assert_is_transferring<'info>(source_token: &InterfaceAccount<'info, TokenAccount>) -> Result<()> {
@@ -91,6 +91,7 @@ async function buyAndSellToken(symbol: string) {
     .buyToken({
       buyAmount: new anchor.BN(100e6), // buy 100 tokens, all token decimals are 6
       maxPay: new anchor.BN(1e9), // max pay 1 SOL, decimals = 9. If the actual pay amount is more than 1 SOL, the transaction will fail
+      guard: { deadline: null, limitPrice: null },
     })
     .accountsPartial({
       config: configPda,
@@ -125,6 +126,7 @@ async function buyAndSellToken(symbol: string) {
     .buyTokenExactIn({
       payAmount: new anchor.BN(1e8), // fixed pay 0.1 SOL, decimals = 9
       minReceive: new anchor.BN(100e6), // min receive 100 tokens, all token decimals are 6. If the actual receive amount is less than 100 tokens, the transaction will fail
+      guard: { deadline: null, limitPrice: null },
     })
     .accountsPartial({
       config: configPda,
@@ -159,6 +161,7 @@ async function buyAndSellToken(symbol: string) {
     .sellToken({
       sellAmount: new anchor.BN(1000e6), // sell 1000 tokens, all token decimals are 6
       minReceive: new anchor.BN(10), // min receive 0.00000001 SOL, decimals = 9. If the actual receive amount is less than 0.00000001 SOL, the transaction will fail
+      guard: { deadline: null, limitPrice: null },
     })
     .accountsPartial({
       config: configPda,
@@ -87,6 +87,7 @@ async function initializeMarket(
     name: name,
     symbol: symbol,
     uri: uri,
+    initialBuy: null,
   };
   let initializeMarketIx: anchor.web3.TransactionInstruction;
   if (transferHookEnabled) {
@@ -151,6 +152,7 @@ async function initializeMarket(
         .buyToken({
           buyAmount: new anchor.BN(initializeBuyAmount.toString()),
           maxPay: new anchor.BN(total.toString()),
+          guard: { deadline: null, limitPrice: null },
         })
         .accountsPartial({
           config: configPda,
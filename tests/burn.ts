@@ -1,21 +1,31 @@
 import * as anchor from "@coral-xyz/anchor";
 import { Program } from "@coral-xyz/anchor";
 import { Burn } from "../target/types/burn";
-import { BLACK_HOLE, DECIMALS, MAX_TOKEN_SUPPLY } from "./constants";
+import { BLACK_HOLE, DECIMALS, MAX_TOKEN_SUPPLY, TOKEN_METADATA_PROGRAM_ID } from "./constants";
 import { assert, expect } from "chai";
 import {
   createAssociatedTokenAccountInstruction,
+  createInitializeMintInstruction,
+  createInitializeTransferFeeConfigInstruction,
+  createMint,
+  createSyncNativeInstruction,
   createTransferCheckedWithTransferHookInstruction,
+  ExtensionType,
   getAccount,
   getAssociatedTokenAddressSync,
   getMetadataPointerState,
   getMint,
+  getMintLen,
   getOrCreateAssociatedTokenAccount,
   getTokenMetadata,
+  mintTo,
+  NATIVE_MINT,
   TOKEN_2022_PROGRAM_ID,
+  TOKEN_PROGRAM_ID,
 } from "@solana/spl-token";
 import { compute_buy_token_exact_in_with_fee, compute_swap_with_fee } from "./math/swap_math";
 import { Hooks } from "../target/types/hooks";
+import { FeeDistributor } from "../target/types/fee_distributor";
 
 (BigInt.prototype as any).toJSON = function () {
   return this.toString();
@@ -27,6 +37,7 @@ describe("burn", () => {
 
   const program = anchor.workspace.Burn as Program<Burn>;
   const hooksProgram = anchor.workspace.Hooks as anchor.Program<Hooks>;
+  const feeDistributorProgram = anchor.workspace.FeeDistributor as anchor.Program<FeeDistributor>;
   const wallet = anchor.Wallet.local().payer;
 
   let config: {
@@ -166,6 +177,7 @@ describe("burn", () => {
             name: "Token name",
             symbol: symbol,
             uri: "https://example.org",
+            initialBuy: null,
           };
           try {
             await program.methods
@@ -195,6 +207,7 @@ describe("burn", () => {
         name: "Token name",
         symbol: "TS" + nextSymbolIndex++,
         uri: "https://example.org",
+        initialBuy: null,
       };
       const [marketPda, marketBump] = anchor.web3.PublicKey.findProgramAddressSync(
         [Buffer.from("market"), Buffer.from(args.symbol), config.configPda.toBuffer()],
@@ -236,6 +249,7 @@ describe("burn", () => {
         name: "Token name",
         symbol: "TS" + nextSymbolIndex++,
         uri: "https://example.org",
+        initialBuy: null,
       };
       const [marketPda, marketBump] = anchor.web3.PublicKey.findProgramAddressSync(
         [Buffer.from("market"), Buffer.from(args.symbol), config.configPda.toBuffer()],
@@ -313,6 +327,74 @@ describe("burn", () => {
       expect(metadata.symbol).to.eq(args.symbol);
       expect(metadata.uri).to.eq(args.uri);
     });
+
+    it("should succeed with an initial buy", async () => {
+      const mintKeypair = anchor.web3.Keypair.generate();
+      const payAmount = BigInt(1e9);
+      const { buy_amount, y, fee } = compute_buy_token_exact_in_with_fee(payAmount, MAX_TOKEN_SUPPLY);
+      const args = {
+        name: "Token name",
+        symbol: "TS" + nextSymbolIndex++,
+        uri: "https://example.org",
+        initialBuy: {
+          payAmount: new anchor.BN(payAmount.toString()),
+          minReceive: new anchor.BN(buy_amount.toString()),
+        },
+      };
+      const [marketPda] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("market"), Buffer.from(args.symbol), config.configPda.toBuffer()],
+        program.programId
+      );
+      const [nativeVaultPda] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("market_vault"), Buffer.from(args.symbol), config.configPda.toBuffer()],
+        program.programId
+      );
+      const tokenVaultAta = getAssociatedTokenAddressSync(
+        mintKeypair.publicKey,
+        marketPda,
+        true,
+        TOKEN_2022_PROGRAM_ID
+      );
+      const tokenRecipient = getAssociatedTokenAddressSync(
+        mintKeypair.publicKey,
+        wallet.publicKey,
+        undefined,
+        TOKEN_2022_PROGRAM_ID
+      );
+      const feeRecipientBalanceBefore = await anchor
+        .getProvider()
+        .connection.getBalance(config.feeRecipientKeypair.publicKey);
+      await program.methods
+        .initializeMarket(args)
+        .accountsPartial({
+          config: config.configPda,
+          tokenMint: mintKeypair.publicKey,
+          tokenVault: tokenVaultAta,
+          market: marketPda,
+          nativeVault: nativeVaultPda,
+          feeRecipient: config.feeRecipientKeypair.publicKey,
+          tokenRecipient: tokenRecipient,
+        })
+        .signers([mintKeypair])
+        .rpc();
+
+      const tokenRecipientAccount = await getAccount(
+        anchor.getProvider().connection,
+        tokenRecipient,
+        undefined,
+        TOKEN_2022_PROGRAM_ID
+      );
+      expect(tokenRecipientAccount.amount).to.eq(buy_amount);
+      const market = await program.account.market.fetch(marketPda);
+      expect(market.remainingSupply.toString()).to.eq((MAX_TOKEN_SUPPLY - buy_amount).toString());
+      const rent = await anchor.getProvider().connection.getMinimumBalanceForRentExemption(0);
+      const nativeVaultBalance = await anchor.getProvider().connection.getBalance(nativeVaultPda);
+      expect(nativeVaultBalance - rent).to.eq(Number(y));
+      const feeRecipientBalanceAfter = await anchor
+        .getProvider()
+        .connection.getBalance(config.feeRecipientKeypair.publicKey);
+      expect(feeRecipientBalanceAfter - feeRecipientBalanceBefore).to.eq(Number(fee));
+    });
   });
 
   describe("#initialize_transfer_hook_market", () => {
@@ -339,6 +421,7 @@ describe("burn", () => {
             name: "Token name",
             symbol: symbol,
             uri: "https://example.org",
+            initialBuy: null,
           };
           try {
             await program.methods
@@ -369,6 +452,7 @@ describe("burn", () => {
         name: "Token name",
         symbol: "TS" + nextSymbolIndex++,
         uri: "https://example.org",
+        initialBuy: null,
       };
       const [marketPda, marketBump] = anchor.web3.PublicKey.findProgramAddressSync(
         [Buffer.from("market"), Buffer.from(args.symbol), config.configPda.toBuffer()],
@@ -410,6 +494,7 @@ describe("burn", () => {
         name: "Token name",
         symbol: "TS" + nextSymbolIndex++,
         uri: "https://example.org",
+        initialBuy: null,
       };
       const [marketPda, marketBump] = anchor.web3.PublicKey.findProgramAddressSync(
         [Buffer.from("market"), Buffer.from(args.symbol), config.configPda.toBuffer()],
@@ -489,6 +574,246 @@ describe("burn", () => {
     });
   });
 
+  describe("#initialize_legacy_token_market", () => {
+    it("should succeed", async () => {
+      const mintKeypair = anchor.web3.Keypair.generate();
+      const args = {
+        name: "Token name",
+        symbol: "TS" + nextSymbolIndex++,
+        uri: "https://example.org",
+        initialBuy: null,
+      };
+      const [marketPda, marketBump] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("market"), Buffer.from(args.symbol), config.configPda.toBuffer()],
+        program.programId
+      );
+      const [nativeVaultPda] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("market_vault"), Buffer.from(args.symbol), config.configPda.toBuffer()],
+        program.programId
+      );
+      const [metadataPda] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("metadata"), TOKEN_METADATA_PROGRAM_ID.toBuffer(), mintKeypair.publicKey.toBuffer()],
+        TOKEN_METADATA_PROGRAM_ID
+      );
+      const tokenVaultAta = getAssociatedTokenAddressSync(mintKeypair.publicKey, marketPda, true, TOKEN_PROGRAM_ID);
+      await program.methods
+        .initializeLegacyTokenMarket(args)
+        .accountsPartial({
+          config: config.configPda,
+          tokenMint: mintKeypair.publicKey,
+          tokenVault: tokenVaultAta,
+          market: marketPda,
+          nativeVault: nativeVaultPda,
+          metadata: metadataPda,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+        })
+        .signers([mintKeypair])
+        .rpc();
+
+      const tokenVault = await getAccount(anchor.getProvider().connection, tokenVaultAta, undefined, TOKEN_PROGRAM_ID);
+      expect(tokenVault.amount).to.eq(MAX_TOKEN_SUPPLY);
+
+      const market = await program.account.market.fetch(marketPda);
+      expect(market.tokenMint.toBase58()).to.eq(mintKeypair.publicKey.toBase58());
+      expect(market.tokenVault.toBase58()).to.eq(tokenVaultAta.toBase58());
+      expect(market.tokenProgram.toBase58()).to.eq(TOKEN_PROGRAM_ID.toBase58());
+      expect(market.creator.toBase58()).to.eq(wallet.publicKey.toBase58());
+      expect(market.bump[0]).to.eq(marketBump);
+      expect(market.transferHookEnabled).to.be.false;
+      expect(market.freeTransferAllowed).to.be.true;
+      expect(market.version).to.eq(1);
+
+      const mint = await getMint(anchor.getProvider().connection, mintKeypair.publicKey, undefined, TOKEN_PROGRAM_ID);
+      expect(mint.decimals).to.eq(DECIMALS);
+      expect(mint.mintAuthority).to.null;
+      expect(mint.freezeAuthority).to.null;
+      expect(mint.supply).to.eq(MAX_TOKEN_SUPPLY);
+
+      const metadata = await anchor.getProvider().connection.getAccountInfo(metadataPda);
+      expect(metadata).to.not.null;
+      expect(metadata.owner.toBase58()).to.eq(TOKEN_METADATA_PROGRAM_ID.toBase58());
+    });
+  });
+
+  describe("#migrate_burn_account", () => {
+    it("should fail if the burn account is already migrated", async () => {
+      const owner = anchor.web3.Keypair.generate();
+      const [burnAccountPda] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("burn_account"), owner.publicKey.toBuffer(), config.configPda.toBuffer()],
+        program.programId
+      );
+      await program.methods
+        .createBurnAccount()
+        .accountsPartial({
+          config: config.configPda,
+          market: burn.marketPda,
+          burnAccount: burnAccountPda,
+          owner: owner.publicKey,
+        })
+        .rpc();
+      try {
+        await program.methods
+          .migrateBurnAccount()
+          .accountsPartial({
+            config: config.configPda,
+            market: burn.marketPda,
+            burnAccount: burnAccountPda,
+            owner: owner.publicKey,
+          })
+          .rpc();
+        expect.fail("should have failed");
+      } catch (e) {
+        expect(e instanceof anchor.AnchorError).to.be.true;
+        const anchorError = e as anchor.AnchorError;
+        expect(anchorError.error.errorCode.code).to.be.eq("BurnAccountAlreadyMigrated");
+      }
+    });
+  });
+
+  describe("#update_market_metadata", () => {
+    it("should fail if neither the config authority nor the creator", async () => {
+      const { mintKeypair, marketPda } = await initializeMarket(config.configPda);
+      const authority = anchor.web3.Keypair.generate();
+      try {
+        await program.methods
+          .updateMarketMetadata({
+            name: "New name",
+            uri: null,
+            additionalMetadata: [],
+            removeKeys: [],
+            renounceUpdateAuthority: false,
+          })
+          .accountsPartial({
+            config: config.configPda,
+            market: marketPda,
+            tokenMint: mintKeypair.publicKey,
+            metadata: null,
+            authority: authority.publicKey,
+            tokenProgram: TOKEN_2022_PROGRAM_ID,
+            tokenMetadataProgram: null,
+          })
+          .signers([authority])
+          .rpc();
+        expect.fail("should have failed");
+      } catch (e) {
+        expect(e instanceof anchor.AnchorError).to.be.true;
+        const anchorError = e as anchor.AnchorError;
+        expect(anchorError.error.errorCode.code).to.be.eq("NotMetadataAuthority");
+      }
+    });
+
+    it("should update and renounce the metadata", async () => {
+      const { mintKeypair, marketPda } = await initializeMarket(config.configPda);
+      const market = await program.account.market.fetch(marketPda);
+      expect(market.creator.toBase58()).to.eq(wallet.publicKey.toBase58());
+
+      await program.methods
+        .updateMarketMetadata({
+          name: "New name",
+          uri: "https://example.org/new",
+          additionalMetadata: [{ key: "website", value: "https://example.org/website" }],
+          removeKeys: [],
+          renounceUpdateAuthority: false,
+        })
+        .accountsPartial({
+          config: config.configPda,
+          market: marketPda,
+          tokenMint: mintKeypair.publicKey,
+          metadata: null,
+          authority: wallet.publicKey,
+          tokenProgram: TOKEN_2022_PROGRAM_ID,
+          tokenMetadataProgram: null,
+        })
+        .rpc();
+
+      let metadata = await getTokenMetadata(
+        anchor.getProvider().connection,
+        mintKeypair.publicKey,
+        undefined,
+        TOKEN_2022_PROGRAM_ID
+      );
+      expect(metadata.name).to.eq("New name");
+      expect(metadata.uri).to.eq("https://example.org/new");
+      expect(metadata.additionalMetadata).to.deep.eq([["website", "https://example.org/website"]]);
+      expect(metadata.updateAuthority.toBase58()).to.eq(marketPda.toBase58());
+
+      // The config authority removes the key and renounces the update authority.
+      await program.methods
+        .updateMarketMetadata({
+          name: null,
+          uri: null,
+          additionalMetadata: [],
+          removeKeys: ["website"],
+          renounceUpdateAuthority: true,
+        })
+        .accountsPartial({
+          config: config.configPda,
+          market: marketPda,
+          tokenMint: mintKeypair.publicKey,
+          metadata: null,
+          authority: config.authorityKeypair.publicKey,
+          tokenProgram: TOKEN_2022_PROGRAM_ID,
+          tokenMetadataProgram: null,
+        })
+        .signers([config.authorityKeypair])
+        .rpc();
+
+      metadata = await getTokenMetadata(
+        anchor.getProvider().connection,
+        mintKeypair.publicKey,
+        undefined,
+        TOKEN_2022_PROGRAM_ID
+      );
+      expect(metadata.name).to.eq("New name");
+      expect(metadata.additionalMetadata).to.deep.eq([]);
+      expect(!metadata.updateAuthority || metadata.updateAuthority.equals(anchor.web3.PublicKey.default)).to.be.true;
+
+      try {
+        await program.methods
+          .updateMarketMetadata({
+            name: "Another name",
+            uri: null,
+            additionalMetadata: [],
+            removeKeys: [],
+            renounceUpdateAuthority: false,
+          })
+          .accountsPartial({
+            config: config.configPda,
+            market: marketPda,
+            tokenMint: mintKeypair.publicKey,
+            metadata: null,
+            authority: wallet.publicKey,
+            tokenProgram: TOKEN_2022_PROGRAM_ID,
+            tokenMetadataProgram: null,
+          })
+          .rpc();
+        expect.fail("should have failed");
+      } catch (e) {
+        expect(e instanceof anchor.web3.SendTransactionError).to.be.true;
+      }
+    });
+  });
+
+  describe("#migrate_market", () => {
+    it("should fail if the market is already migrated", async () => {
+      try {
+        await program.methods
+          .migrateMarket()
+          .accountsPartial({
+            config: config.configPda,
+            market: burn.marketPda,
+          })
+          .rpc();
+        expect.fail("should have failed");
+      } catch (e) {
+        expect(e instanceof anchor.AnchorError).to.be.true;
+        const anchorError = e as anchor.AnchorError;
+        expect(anchorError.error.errorCode.code).to.be.eq("MarketAlreadyMigrated");
+      }
+    });
+  });
+
   describe("#buy_token", () => {
     it("should failed if native vault account mismatch", async () => {
       const { mintKeypair, marketPda, marketBump, tokenVaultAta, extraAccountMetaListPda } = await initializeMarket(
@@ -511,6 +836,7 @@ describe("burn", () => {
           .buyToken({
             buyAmount: new anchor.BN(1e9),
             maxPay: new anchor.BN(1e9),
+            guard: { deadline: null, limitPrice: null },
           })
           .accountsPartial({
             config: config.configPda,
@@ -556,6 +882,7 @@ describe("burn", () => {
           .buyToken({
             buyAmount: new anchor.BN(1e9),
             maxPay: new anchor.BN(1e9),
+            guard: { deadline: null, limitPrice: null },
           })
           .accountsPartial({
             config: config.configPda,
@@ -595,6 +922,7 @@ describe("burn", () => {
           .buyToken({
             buyAmount: new anchor.BN(1e9),
             maxPay: new anchor.BN(1e9),
+            guard: { deadline: null, limitPrice: null },
           })
           .accountsPartial({
             config: config.configPda,
@@ -640,6 +968,7 @@ describe("burn", () => {
           .buyToken({
             buyAmount: new anchor.BN(1e9),
             maxPay: new anchor.BN(1e9),
+            guard: { deadline: null, limitPrice: null },
           })
           .accountsPartial({
             config: config.configPda,
@@ -678,6 +1007,7 @@ describe("burn", () => {
           .buyToken({
             buyAmount: new anchor.BN(new anchor.BN(MAX_TOKEN_SUPPLY.toString()).add(new anchor.BN(1))),
             maxPay: new anchor.BN(0),
+            guard: { deadline: null, limitPrice: null },
           })
           .accountsPartial({
             config: config.configPda,
@@ -725,6 +1055,7 @@ describe("burn", () => {
         .buyToken({
           buyAmount: new anchor.BN(1e9),
           maxPay: new anchor.BN((total - BigInt(1)).toString()),
+          guard: { deadline: null, limitPrice: null },
         })
         .accountsPartial({
           config: configPda,
@@ -778,6 +1109,7 @@ describe("burn", () => {
         .buyToken({
           buyAmount: new anchor.BN((MAX_TOKEN_SUPPLY / BigInt(10)).toString()),
           maxPay: new anchor.BN(total.toString()),
+          guard: { deadline: null, limitPrice: null },
         })
         .accountsPartial({
           config: configPda,
@@ -829,6 +1161,7 @@ describe("burn", () => {
         .buyToken({
           buyAmount: new anchor.BN((MAX_TOKEN_SUPPLY / BigInt(10)).toString()),
           maxPay: new anchor.BN(total.toString()),
+          guard: { deadline: null, limitPrice: null },
         })
         .accountsPartial({
           config: configPda,
@@ -889,6 +1222,7 @@ describe("burn", () => {
         .buyToken({
           buyAmount: new anchor.BN(1e9),
           maxPay: new anchor.BN(total.toString()),
+          guard: { deadline: null, limitPrice: null },
         })
         .accountsPartial({
           config: configPda,
@@ -975,6 +1309,7 @@ describe("burn", () => {
           .buyTokenExactIn({
             payAmount: new anchor.BN(1e9),
             minReceive: new anchor.BN(1e9),
+            guard: { deadline: null, limitPrice: null },
           })
           .accountsPartial({
             config: configPda,
@@ -1021,6 +1356,7 @@ describe("burn", () => {
           .buyTokenExactIn({
             payAmount: new anchor.BN(1e9),
             minReceive: new anchor.BN(1e9),
+            guard: { deadline: null, limitPrice: null },
           })
           .accountsPartial({
             config: configPda,
@@ -1060,6 +1396,7 @@ describe("burn", () => {
           .buyTokenExactIn({
             payAmount: new anchor.BN(1e9),
             minReceive: new anchor.BN(1e9),
+            guard: { deadline: null, limitPrice: null },
           })
           .accountsPartial({
             config: config.configPda,
@@ -1106,6 +1443,7 @@ describe("burn", () => {
           .buyTokenExactIn({
             payAmount: new anchor.BN(1e9),
             minReceive: new anchor.BN(1e9),
+            guard: { deadline: null, limitPrice: null },
           })
           .accountsPartial({
             config: configPda,
@@ -1146,6 +1484,7 @@ describe("burn", () => {
           .buyTokenExactIn({
             payAmount: new anchor.BN(0),
             minReceive: new anchor.BN(1e9),
+            guard: { deadline: null, limitPrice: null },
           })
           .accountsPartial({
             config: configPda,
@@ -1193,6 +1532,7 @@ describe("burn", () => {
         .buyTokenExactIn({
           payAmount: new anchor.BN(1e9),
           minReceive: new anchor.BN(Number(buy_amount) + 1),
+          guard: { deadline: null, limitPrice: null },
         })
         .accountsPartial({
           config: configPda,
@@ -1246,6 +1586,7 @@ describe("burn", () => {
         .buyTokenExactIn({
           payAmount: new anchor.BN(1e9),
           minReceive: new anchor.BN(Number(buy_amount) + 1),
+          guard: { deadline: null, limitPrice: null },
         })
         .accountsPartial({
           config: configPda,
@@ -1297,6 +1638,7 @@ describe("burn", () => {
         .buyTokenExactIn({
           payAmount: new anchor.BN(1e9),
           minReceive: new anchor.BN(Number(buy_amount) + 1),
+          guard: { deadline: null, limitPrice: null },
         })
         .accountsPartial({
           config: configPda,
@@ -1353,6 +1695,7 @@ describe("burn", () => {
         .buyTokenExactIn({
           payAmount: new anchor.BN(1e9),
           minReceive: new anchor.BN(Number(buy_amount)),
+          guard: { deadline: null, limitPrice: null },
         })
         .accountsPartial({
           config: configPda,
@@ -1435,6 +1778,7 @@ describe("burn", () => {
           .buyTokenExactIn({
             payAmount: new anchor.BN(1e9),
             minReceive: new anchor.BN(Number(buy_amount)),
+            guard: { deadline: null, limitPrice: null },
           })
           .accountsPartial({
             config: configPda,
@@ -1450,6 +1794,7 @@ describe("burn", () => {
           .sellToken({
             sellAmount: new anchor.BN(Number(buy_amount)),
             minReceive: new anchor.BN(0),
+            guard: { deadline: null, limitPrice: null },
           })
           .accountsPartial({
             config: configPda,
@@ -1521,6 +1866,7 @@ describe("burn", () => {
         .buyBurnExactIn({
           payAmount: new anchor.BN(1e8),
           minReceive: new anchor.BN(0),
+          guard: { deadline: null, limitPrice: null },
         })
         .accountsPartial({
           config: configPda,
@@ -1531,8 +1877,8 @@ describe("burn", () => {
           payer: payer.publicKey,
           nativeVault: nativeVaultPda,
           buyBurnAuthority: buyBurnAuthorityKeypair.publicKey,
-
           extraAccountMetaList: extraAccountMetaListPda,
+          hooksProgram: hooksProgram.programId,
         })
         .instruction();
       const buyTx = new anchor.web3.Transaction().add(ix0, ix1);
@@ -1580,6 +1926,7 @@ describe("burn", () => {
         .createBurnAccount()
         .accountsPartial({
           config: configPda,
+          market: marketPda,
           burnAccount: burnAccountPda,
           owner: payer.publicKey,
         })
@@ -1591,6 +1938,7 @@ describe("burn", () => {
           nextNonce: 1,
           nextBuyAmount: new anchor.BN((MAX_TOKEN_SUPPLY / BigInt(10)).toString()),
           maxPay: new anchor.BN(total.toString()),
+          guard: { deadline: null, limitPrice: null },
         })
         .accountsPartial({
           config: configPda,
@@ -1602,8 +1950,8 @@ describe("burn", () => {
           nativeVault: nativeVaultPda,
           buyBurnAuthority: feeRecipientKeypair.publicKey,
           burnAccount: burnAccountPda,
-
           extraAccountMetaList: extraAccountMetaListPda,
+          hooksProgram: hooksProgram.programId,
         })
         .instruction();
 
@@ -1654,6 +2002,7 @@ describe("burn", () => {
         .createBurnAccount()
         .accountsPartial({
           config: configPda,
+          market: marketPda,
           burnAccount: burnAccountPda,
           owner: payer.publicKey,
         })
@@ -1665,6 +2014,7 @@ describe("burn", () => {
           nextNonce: 1,
           nextBuyAmount: new anchor.BN((MAX_TOKEN_SUPPLY / BigInt(10)).toString()),
           maxPay: new anchor.BN(total.toString()),
+          guard: { deadline: null, limitPrice: null },
         })
         .accountsPartial({
           config: configPda,
@@ -1676,8 +2026,8 @@ describe("burn", () => {
           nativeVault: nativeVaultPda,
           buyBurnAuthority: buyBurnAuthorityKeypair.publicKey,
           burnAccount: burnAccountPda,
-
           extraAccountMetaList: extraAccountMetaListPda,
+          hooksProgram: hooksProgram.programId,
         })
         .instruction();
 
@@ -1725,6 +2075,7 @@ describe("burn", () => {
         .createBurnAccount()
         .accountsPartial({
           config: configPda,
+          market: marketPda,
           burnAccount: burnAccountPda,
           owner: payer.publicKey,
         })
@@ -1737,6 +2088,7 @@ describe("burn", () => {
           nextNonce: 1,
           nextBuyAmount: new anchor.BN(1000e6),
           maxPay: new anchor.BN(total.toString()),
+          guard: { deadline: null, limitPrice: null },
         })
         .accountsPartial({
           config: configPda,
@@ -1748,8 +2100,8 @@ describe("burn", () => {
           nativeVault: nativeVaultPda,
           buyBurnAuthority: buyBurnAuthorityKeypair.publicKey,
           burnAccount: burnAccountPda,
-
           extraAccountMetaList: extraAccountMetaListPda,
+          hooksProgram: hooksProgram.programId,
         })
         .instruction();
 
@@ -1766,7 +2118,7 @@ describe("burn", () => {
         } catch (err) {
           expect(err instanceof anchor.web3.SendTransactionError).to.be.true;
           const sendTxError = err as anchor.web3.SendTransactionError;
-          expect(sendTxError.message.includes("NonceUnexpected")).to.be.true;
+          expect(sendTxError.message.includes("BuyAmountUnexpected")).to.be.true;
         }
       }
     });
@@ -1803,6 +2155,7 @@ describe("burn", () => {
         .createBurnAccount()
         .accountsPartial({
           config: configPda,
+          market: marketPda,
           burnAccount: burnAccountPda,
           owner: payer.publicKey,
         })
@@ -1815,6 +2168,7 @@ describe("burn", () => {
           nextNonce: 1,
           nextBuyAmount: new anchor.BN(1000e6),
           maxPay: new anchor.BN(total.toString()),
+          guard: { deadline: null, limitPrice: null },
         })
         .accountsPartial({
           config: configPda,
@@ -1826,8 +2180,8 @@ describe("burn", () => {
           nativeVault: nativeVaultPda,
           buyBurnAuthority: buyBurnAuthorityKeypair.publicKey,
           burnAccount: burnAccountPda,
-
           extraAccountMetaList: extraAccountMetaListPda,
+          hooksProgram: hooksProgram.programId,
         })
         .instruction();
 
@@ -1919,6 +2273,7 @@ describe("burn", () => {
         .createBurnAccount()
         .accountsPartial({
           config: configPda,
+          market: marketPda,
           burnAccount: burnAccountPda,
           owner: payer.publicKey,
         })
@@ -1931,6 +2286,7 @@ describe("burn", () => {
           nextNonce: 1,
           nextBuyAmount: new anchor.BN(1000e6),
           maxPay: new anchor.BN(total.toString()),
+          guard: { deadline: null, limitPrice: null },
         })
         .accountsPartial({
           config: configPda,
@@ -1942,8 +2298,8 @@ describe("burn", () => {
           nativeVault: nativeVaultPda,
           buyBurnAuthority: buyBurnAuthorityKeypair.publicKey,
           burnAccount: burnAccountPda,
-
           extraAccountMetaList: extraAccountMetaListPda,
+          hooksProgram: hooksProgram.programId,
         })
         .instruction();
 
@@ -2020,6 +2376,7 @@ describe("burn", () => {
         .createBurnAccount()
         .accountsPartial({
           config: configPda,
+          market: marketPda,
           burnAccount: burnAccountPda,
           owner: payer.publicKey,
         })
@@ -2033,6 +2390,7 @@ describe("burn", () => {
           nextNonce: 1,
           nextBuyAmount: new anchor.BN(buy_amount.toString()),
           maxPay: new anchor.BN(total.toString()),
+          guard: { deadline: null, limitPrice: null },
         })
         .accountsPartial({
           config: configPda,
@@ -2044,8 +2402,8 @@ describe("burn", () => {
           nativeVault: nativeVaultPda,
           buyBurnAuthority: buyBurnAuthorityKeypair.publicKey,
           burnAccount: burnAccountPda,
-
           extraAccountMetaList: extraAccountMetaListPda,
+          hooksProgram: hooksProgram.programId,
         })
         .instruction();
       const blackHoleRecipient = getAssociatedTokenAddressSync(
@@ -2066,6 +2424,7 @@ describe("burn", () => {
           nextNonce: 2,
           nextBuyAmount: new anchor.BN((buy_amount + MAX_TOKEN_SUPPLY / BigInt(100)).toString()),
           maxPay: new anchor.BN(payerBalanceBefore.toString()),
+          guard: { deadline: null, limitPrice: null },
         })
         .accountsPartial({
           config: configPda,
@@ -2077,8 +2436,8 @@ describe("burn", () => {
           nativeVault: nativeVaultPda,
           buyBurnAuthority: buyBurnAuthorityKeypair.publicKey,
           burnAccount: burnAccountPda,
-
           extraAccountMetaList: extraAccountMetaListPda,
+          hooksProgram: hooksProgram.programId,
         })
         .instruction();
       const buyTx = new anchor.web3.Transaction().add(ix0, ix1, ix2, ix4);
@@ -2102,6 +2461,8 @@ describe("burn", () => {
       const ix1 = await program.methods
         .useFundsBuyBurn({
           maxBuyAmount: new anchor.BN(1000 * 1e6),
+          burn: false,
+          guard: { deadline: null, limitPrice: null },
         })
         .accountsPartial({
           config: configPda,
@@ -2112,6 +2473,7 @@ describe("burn", () => {
           nativeVault: nativeVaultPda,
           buyBurnAuthority: buyBurnAuthorityKeypair.publicKey,
           extraAccountMetaList: extraAccountMetaListPda,
+          hooksProgram: hooksProgram.programId,
         })
         .instruction();
       const tx = new anchor.web3.Transaction();
@@ -2135,6 +2497,8 @@ describe("burn", () => {
       const ix1 = await program.methods
         .useFundsBuyBurn({
           maxBuyAmount: new anchor.BN(1000 * 1e6),
+          burn: false,
+          guard: { deadline: null, limitPrice: null },
         })
         .accountsPartial({
           config: configPda,
@@ -2145,6 +2509,7 @@ describe("burn", () => {
           nativeVault: nativeVaultPda,
           buyBurnAuthority: buyBurnAuthorityKeypair.publicKey,
           extraAccountMetaList: extraAccountMetaListPda,
+          hooksProgram: hooksProgram.programId,
         })
         .instruction();
       const tx = new anchor.web3.Transaction();
@@ -2187,6 +2552,7 @@ describe("burn", () => {
         .createBurnAccount()
         .accountsPartial({
           config: configPda,
+          market: marketPda,
           burnAccount: burnAccountPda,
           owner: payer.publicKey,
         })
@@ -2199,6 +2565,7 @@ describe("burn", () => {
           nextNonce: 1,
           nextBuyAmount: new anchor.BN(1000e6),
           maxPay: new anchor.BN(total.toString()),
+          guard: { deadline: null, limitPrice: null },
         })
         .accountsPartial({
           config: configPda,
@@ -2210,8 +2577,8 @@ describe("burn", () => {
           nativeVault: nativeVaultPda,
           buyBurnAuthority: buyBurnAuthorityKeypair.publicKey,
           burnAccount: burnAccountPda,
-
           extraAccountMetaList: extraAccountMetaListPda,
+          hooksProgram: hooksProgram.programId,
         })
         .instruction();
 
@@ -2291,6 +2658,7 @@ describe("burn", () => {
         .buyToken({
           buyAmount: new anchor.BN(1000e6),
           maxPay: new anchor.BN(total.toString()),
+          guard: { deadline: null, limitPrice: null },
         })
         .accountsPartial({
           config: configPda,
@@ -2355,6 +2723,7 @@ describe("burn", () => {
         .buyTokenExactIn({
           payAmount: new anchor.BN(1e9),
           minReceive: new anchor.BN(1),
+          guard: { deadline: null, limitPrice: null },
         })
         .accountsPartial({
           config: configPda,
@@ -2420,6 +2789,7 @@ describe("burn", () => {
         .buyBurnExactIn({
           payAmount: new anchor.BN(1e8),
           minReceive: new anchor.BN(0),
+          guard: { deadline: null, limitPrice: null },
         })
         .accountsPartial({
           config: configPda,
@@ -2430,8 +2800,8 @@ describe("burn", () => {
           payer: payer.publicKey,
           nativeVault: nativeVaultPda,
           buyBurnAuthority: buyBurnAuthorityKeypair.publicKey,
-
           extraAccountMetaList: extraAccountMetaListPda,
+          hooksProgram: hooksProgram.programId,
         })
         .instruction();
       const buyTx = new anchor.web3.Transaction().add(ix1);
@@ -2464,6 +2834,8 @@ describe("burn", () => {
         const ix1 = await program.methods
           .useFundsBuyBurn({
             maxBuyAmount: new anchor.BN(0),
+            burn: false,
+            guard: { deadline: null, limitPrice: null },
           })
           .accountsPartial({
             config: configPda,
@@ -2474,6 +2846,7 @@ describe("burn", () => {
             nativeVault: nativeVaultPda,
             buyBurnAuthority: buyBurnAuthorityKeypair.publicKey,
             extraAccountMetaList: extraAccountMetaListPda,
+            hooksProgram: hooksProgram.programId,
           })
           .instruction();
         const tx = new anchor.web3.Transaction();
@@ -2501,6 +2874,8 @@ describe("burn", () => {
         const ix1 = await program.methods
           .useFundsBuyBurn({
             maxBuyAmount: new anchor.BN(0),
+            burn: false,
+            guard: { deadline: null, limitPrice: null },
           })
           .accountsPartial({
             config: configPda,
@@ -2511,6 +2886,7 @@ describe("burn", () => {
             nativeVault: nativeVaultPda,
             buyBurnAuthority: buyBurnAuthorityKeypair.publicKey,
             extraAccountMetaList: extraAccountMetaListPda,
+            hooksProgram: hooksProgram.programId,
           })
           .instruction();
         const tx = new anchor.web3.Transaction();
@@ -2523,43 +2899,263 @@ describe("burn", () => {
         expect(sendTxError.message.includes("AmountCannotBeZero")).to.be.true;
       }
     });
-  });
 
-  describe("#sell_token", () => {
-    it("should failed if native vault account mismatch", async () => {
-      const { configPda, feeRecipientKeypair } = await initializeConfig();
-      const { mintKeypair, marketPda, tokenVaultAta, extraAccountMetaListPda } = await initializeMarket(configPda);
+    it("should burn the bought tokens when `burn` is true", async () => {
+      const { configPda, feeRecipientKeypair, buyBurnAuthorityKeypair } = await initializeConfig();
+      const { mintKeypair, marketPda, tokenVaultAta, nativeVaultPda } = await initializeMarket(configPda);
+      await enableBuyBurn(marketPda);
 
-      const tokenRecipient = await getOrCreateAssociatedTokenAccount(
-        anchor.getProvider().connection,
-        wallet,
+      const blackHoleRecipient = getAssociatedTokenAddressSync(
         mintKeypair.publicKey,
-        wallet.publicKey,
-        undefined,
-        undefined,
-        undefined,
-        TOKEN_2022_PROGRAM_ID,
-        undefined
+        BLACK_HOLE,
+        true,
+        TOKEN_2022_PROGRAM_ID
       );
-      try {
-        await program.methods
-          .sellToken({
-            sellAmount: new anchor.BN(1e9),
-            minReceive: new anchor.BN(0),
+      // Fund the market over its curve reserve, so that there are funds to buy-burn with.
+      const fundTx = new anchor.web3.Transaction().add(
+        createAssociatedTokenAccountInstruction(
+          wallet.publicKey,
+          blackHoleRecipient,
+          BLACK_HOLE,
+          mintKeypair.publicKey,
+          TOKEN_2022_PROGRAM_ID
+        ),
+        anchor.web3.SystemProgram.transfer({
+          fromPubkey: wallet.publicKey,
+          toPubkey: nativeVaultPda,
+          lamports: 1e9,
+        })
+      );
+      await sendAndConfirmTransaction(fundTx, wallet);
+
+      const useFundsBuyBurn = async (limitPrice: anchor.BN | null) => {
+        const ix = await program.methods
+          .useFundsBuyBurn({
+            maxBuyAmount: new anchor.BN(1000 * 1e6),
+            burn: true,
+            guard: { deadline: null, limitPrice },
           })
           .accountsPartial({
             config: configPda,
             market: marketPda,
             feeRecipient: feeRecipientKeypair.publicKey,
             tokenVault: tokenVaultAta,
-            nativeRecipient: wallet.publicKey,
-            tokenPayer: tokenRecipient.address,
-            nativeVault: wallet.publicKey,
+            tokenRecipient: blackHoleRecipient,
+            nativeVault: nativeVaultPda,
+            buyBurnAuthority: buyBurnAuthorityKeypair.publicKey,
+            extraAccountMetaList: null,
+            hooksProgram: null,
           })
-          .rpc();
+          .instruction();
+        await sendAndConfirmTransaction(new anchor.web3.Transaction().add(ix), wallet, buyBurnAuthorityKeypair);
+      };
+
+      // the fees push the average price over the price without fees
+      const { y } = compute_swap_with_fee(1000e6, MAX_TOKEN_SUPPLY, true);
+      try {
+        await useFundsBuyBurn(new anchor.BN((y / BigInt(1000)).toString()));
         expect.fail("should have failed");
       } catch (e) {
-        expect(e instanceof anchor.AnchorError).to.be.true;
+        expect(e instanceof anchor.web3.SendTransactionError).to.be.true;
+        expect((e as anchor.web3.SendTransactionError).message.includes("PriceLimitExceeded")).to.be.true;
+      }
+      await useFundsBuyBurn(null);
+
+      const market = await program.account.market.fetch(marketPda);
+      const burned = BigInt(market.totalBurned.toString());
+      expect(burned > BigInt(0)).to.be.true;
+      expect(BigInt(market.remainingSupply.toString())).to.eq(MAX_TOKEN_SUPPLY - burned);
+
+      const mint = await getMint(
+        anchor.getProvider().connection,
+        mintKeypair.publicKey,
+        undefined,
+        TOKEN_2022_PROGRAM_ID
+      );
+      expect(mint.supply).to.eq(MAX_TOKEN_SUPPLY - burned);
+      const tokenVault = await getAccount(
+        anchor.getProvider().connection,
+        tokenVaultAta,
+        undefined,
+        TOKEN_2022_PROGRAM_ID
+      );
+      expect(tokenVault.amount).to.eq(MAX_TOKEN_SUPPLY - burned);
+      const blackHole = await getAccount(
+        anchor.getProvider().connection,
+        blackHoleRecipient,
+        undefined,
+        TOKEN_2022_PROGRAM_ID
+      );
+      expect(blackHole.amount).to.eq(BigInt(0));
+    });
+
+    it("should spend the whole window allowance when cranked permissionlessly", async () => {
+      const { configPda, feeRecipientKeypair } = await initializeConfig();
+      const { mintKeypair, marketPda, tokenVaultAta, nativeVaultPda } = await initializeMarket(configPda);
+      await enableBuyBurn(marketPda);
+
+      const blackHoleRecipient = getAssociatedTokenAddressSync(
+        mintKeypair.publicKey,
+        BLACK_HOLE,
+        true,
+        TOKEN_2022_PROGRAM_ID
+      );
+      const fundTx = new anchor.web3.Transaction().add(
+        createAssociatedTokenAccountInstruction(
+          wallet.publicKey,
+          blackHoleRecipient,
+          BLACK_HOLE,
+          mintKeypair.publicKey,
+          TOKEN_2022_PROGRAM_ID
+        ),
+        anchor.web3.SystemProgram.transfer({
+          fromPubkey: wallet.publicKey,
+          toPubkey: nativeVaultPda,
+          lamports: 1e9,
+        })
+      );
+      await sendAndConfirmTransaction(fundTx, wallet);
+
+      const [schedulePda] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("buy_burn_schedule"), marketPda.toBuffer()],
+        program.programId
+      );
+      const crank = async () => {
+        const ix = await program.methods
+          // the amount of a permissionless caller is ignored
+          .useFundsBuyBurn({ maxBuyAmount: new anchor.BN(1), burn: true, guard: { deadline: null, limitPrice: null } })
+          .accountsPartial({
+            config: configPda,
+            market: marketPda,
+            feeRecipient: feeRecipientKeypair.publicKey,
+            tokenVault: tokenVaultAta,
+            tokenRecipient: blackHoleRecipient,
+            nativeVault: nativeVaultPda,
+            buyBurnAuthority: null,
+            schedule: schedulePda,
+            extraAccountMetaList: null,
+            hooksProgram: null,
+          })
+          .instruction();
+        await sendAndConfirmTransaction(new anchor.web3.Transaction().add(ix), wallet);
+      };
+
+      const setSchedule = (maxPrice: anchor.BN) =>
+        program.methods
+          .setBuyBurnSchedule({
+            maxAmountPerWindow: new anchor.BN(0.1e9),
+            maxAmountPerCall: new anchor.BN(0.06e9),
+            maxPrice,
+            windowSlots: new anchor.BN(0),
+            minIntervalSlots: new anchor.BN(0),
+            permissionless: true,
+          })
+          .accountsPartial({
+            config: configPda,
+            schedule: schedulePda,
+            authority: config.authorityKeypair.publicKey,
+          })
+          .signers([config.authorityKeypair])
+          .rpc();
+
+      await program.methods
+        .createBuyBurnSchedule({
+          maxAmountPerWindow: new anchor.BN(0.1e9),
+          maxAmountPerCall: new anchor.BN(0.06e9),
+          maxPrice: new anchor.BN(0),
+          windowSlots: new anchor.BN(0),
+          minIntervalSlots: new anchor.BN(0),
+          permissionless: false,
+        })
+        .accountsPartial({
+          config: configPda,
+          market: marketPda,
+          schedule: schedulePda,
+          authority: config.authorityKeypair.publicKey,
+        })
+        .signers([config.authorityKeypair])
+        .rpc();
+      try {
+        await crank();
+        expect.fail("should have failed");
+      } catch (e) {
+        expect(e instanceof anchor.web3.SendTransactionError).to.be.true;
+        expect((e as anchor.web3.SendTransactionError).message.includes("BuyBurnAuthorityIsMissing")).to.be.true;
+      }
+
+      // a permissionless schedule must have a max price
+      try {
+        await setSchedule(new anchor.BN(0));
+        expect.fail("should have failed");
+      } catch (e) {
+        expect(e.message.includes("AmountCannotBeZero")).to.be.true;
+      }
+      // the crank can't buy above the max price, here the average price of the first 1000 tokens without fees
+      const { y } = compute_swap_with_fee(1000e6, MAX_TOKEN_SUPPLY, true);
+      await setSchedule(new anchor.BN((y / BigInt(1000)).toString()));
+      try {
+        await crank();
+        expect.fail("should have failed");
+      } catch (e) {
+        expect(e instanceof anchor.web3.SendTransactionError).to.be.true;
+        expect((e as anchor.web3.SendTransactionError).message.includes("PriceLimitExceeded")).to.be.true;
+      }
+
+      // the spend per call is capped
+      await setSchedule(new anchor.BN(1e12));
+      await crank();
+      let schedule = await program.account.buyBurnSchedule.fetch(schedulePda);
+      expect(schedule.spentInWindow.toNumber()).to.eq(0.06e9);
+      await crank();
+      schedule = await program.account.buyBurnSchedule.fetch(schedulePda);
+      expect(schedule.spentInWindow.toNumber()).to.eq(0.1e9);
+      let market = await program.account.market.fetch(marketPda);
+      const burned = BigInt(market.totalBurned.toString());
+      expect(burned > BigInt(1)).to.be.true;
+
+      // nothing is left to spend in the window
+      await crank();
+      market = await program.account.market.fetch(marketPda);
+      expect(BigInt(market.totalBurned.toString())).to.eq(burned);
+    });
+  });
+
+  describe("#sell_token", () => {
+    it("should failed if native vault account mismatch", async () => {
+      const { configPda, feeRecipientKeypair } = await initializeConfig();
+      const { mintKeypair, marketPda, tokenVaultAta, extraAccountMetaListPda } = await initializeMarket(configPda);
+
+      const tokenRecipient = await getOrCreateAssociatedTokenAccount(
+        anchor.getProvider().connection,
+        wallet,
+        mintKeypair.publicKey,
+        wallet.publicKey,
+        undefined,
+        undefined,
+        undefined,
+        TOKEN_2022_PROGRAM_ID,
+        undefined
+      );
+      try {
+        await program.methods
+          .sellToken({
+            sellAmount: new anchor.BN(1e9),
+            minReceive: new anchor.BN(0),
+            guard: { deadline: null, limitPrice: null },
+          })
+          .accountsPartial({
+            config: configPda,
+            market: marketPda,
+            feeRecipient: feeRecipientKeypair.publicKey,
+            tokenVault: tokenVaultAta,
+            nativeRecipient: wallet.publicKey,
+            tokenPayer: tokenRecipient.address,
+            nativeVault: wallet.publicKey,
+          })
+          .rpc();
+        expect.fail("should have failed");
+      } catch (e) {
+        expect(e instanceof anchor.AnchorError).to.be.true;
         const anchorError = e as anchor.AnchorError;
         expect(anchorError.error.errorCode.code).to.be.eq("NativeVaultAccountMismatch");
       }
@@ -2591,6 +3187,7 @@ describe("burn", () => {
           .sellToken({
             sellAmount: new anchor.BN(1e9),
             minReceive: new anchor.BN(0),
+            guard: { deadline: null, limitPrice: null },
           })
           .accountsPartial({
             config: configPda,
@@ -2631,6 +3228,7 @@ describe("burn", () => {
           .sellToken({
             sellAmount: new anchor.BN(1e9),
             minReceive: new anchor.BN(0),
+            guard: { deadline: null, limitPrice: null },
           })
           .accountsPartial({
             config: config.configPda,
@@ -2678,6 +3276,7 @@ describe("burn", () => {
           .sellToken({
             sellAmount: new anchor.BN(1e9),
             minReceive: new anchor.BN(0),
+            guard: { deadline: null, limitPrice: null },
           })
           .accountsPartial({
             config: configPda,
@@ -2719,6 +3318,7 @@ describe("burn", () => {
           .sellToken({
             sellAmount: new anchor.BN(1e9),
             minReceive: new anchor.BN(0),
+            guard: { deadline: null, limitPrice: null },
           })
           .accountsPartial({
             config: configPda,
@@ -2760,6 +3360,7 @@ describe("burn", () => {
           .sellToken({
             sellAmount: new anchor.BN(0),
             minReceive: new anchor.BN(0),
+            guard: { deadline: null, limitPrice: null },
           })
           .accountsPartial({
             config: configPda,
@@ -2807,6 +3408,7 @@ describe("burn", () => {
         .buyToken({
           buyAmount: new anchor.BN(buyAmount.toString()),
           maxPay: new anchor.BN(total.toString()),
+          guard: { deadline: null, limitPrice: null },
         })
         .accountsPartial({
           config: configPda,
@@ -2827,6 +3429,7 @@ describe("burn", () => {
         .sellToken({
           sellAmount: new anchor.BN(buyAmount.toString()),
           minReceive: new anchor.BN(Number(sellTotal) + 1),
+          guard: { deadline: null, limitPrice: null },
         })
         .accountsPartial({
           config: configPda,
@@ -2893,6 +3496,7 @@ describe("burn", () => {
         .buyToken({
           buyAmount: new anchor.BN(buyAmount.toString()),
           maxPay: new anchor.BN(total.toString()),
+          guard: { deadline: null, limitPrice: null },
         })
         .accountsPartial({
           config: configPda,
@@ -2926,6 +3530,7 @@ describe("burn", () => {
         .sellToken({
           sellAmount: new anchor.BN(buyAmount.toString()),
           minReceive: new anchor.BN(Number(sellTotal)),
+          guard: { deadline: null, limitPrice: null },
         })
         .accountsPartial({
           config: configPda,
@@ -3009,107 +3614,1607 @@ describe("burn", () => {
     });
   });
 
-  async function initializeConfig() {
-    // check if config already initialized
-    if (config !== null) {
-      return config;
-    }
-    const [configPda] = anchor.web3.PublicKey.findProgramAddressSync([Buffer.from("config")], program.programId);
-    const authorityKeypair = anchor.web3.Keypair.generate();
-    const feeRecipientKeypair = anchor.web3.Keypair.generate();
-    const buyBurnAuthorityKeypair = anchor.web3.Keypair.generate();
-    await program.methods
-      .initializeConfig({
-        authority: authorityKeypair.publicKey,
-        feeRecipient: feeRecipientKeypair.publicKey,
-        buyBurnAuthority: buyBurnAuthorityKeypair.publicKey,
-      })
-      .accountsPartial({
-        config: configPda,
-      })
-      .rpc();
-
-    return {
-      configPda,
-      authorityKeypair,
-      feeRecipientKeypair,
-      buyBurnAuthorityKeypair,
-    };
-  }
-
-  async function initializeMarket(
-    configPublickey: anchor.web3.PublicKey,
-    name: string = "Token name",
-    symbol: string = "TS" + nextSymbolIndex++,
-    uri: string = "https://example.org",
-    transferHookEnabled: boolean = false
-  ) {
-    const mintKeypair = anchor.web3.Keypair.generate();
-    const [marketPda, marketBump] = anchor.web3.PublicKey.findProgramAddressSync(
-      [Buffer.from("market"), Buffer.from(symbol), configPublickey.toBuffer()],
-      program.programId
-    );
-    const [nativeVaultPda, nativeVaultBump] = anchor.web3.PublicKey.findProgramAddressSync(
-      [Buffer.from("market_vault"), Buffer.from(symbol), configPublickey.toBuffer()],
-      program.programId
-    );
-    const [extraAccountMetaListPda] = anchor.web3.PublicKey.findProgramAddressSync(
-      [Buffer.from("extra-account-metas"), mintKeypair.publicKey.toBuffer()],
-      hooksProgram.programId
-    );
-    const initializeAccountMetaListIx = await hooksProgram.methods
-      .initializeAccountMetaList(symbol)
-      .accountsPartial({
-        tokenMint: mintKeypair.publicKey,
-        extraAccountMetaList: extraAccountMetaListPda,
-      })
-      .instruction();
+  describe("#buy_burn without transfer hook", () => {
+    it("should succeed on a market with buy-burn enabled", async () => {
+      const { configPda, feeRecipientKeypair, buyBurnAuthorityKeypair } = await initializeConfig();
+      const { mintKeypair, marketPda, tokenVaultAta, nativeVaultPda } = await initializeMarket(configPda);
+      await enableBuyBurn(marketPda);
 
-    const tokenVaultAta = getAssociatedTokenAddressSync(mintKeypair.publicKey, marketPda, true, TOKEN_2022_PROGRAM_ID);
-    const args = {
-      name: name,
-      symbol: symbol,
-      uri: uri,
-    };
+      const payer = anchor.web3.Keypair.generate();
+      const payerBalanceBefore = 1e9;
+      let tx = await anchor.getProvider().connection.requestAirdrop(payer.publicKey, payerBalanceBefore);
+      await confirmTransaction(tx);
+      tx = await anchor.getProvider().connection.requestAirdrop(feeRecipientKeypair.publicKey, 1e9);
+      await confirmTransaction(tx);
 
-    let initializeMarketIx: anchor.web3.TransactionInstruction;
-    if (transferHookEnabled) {
-      initializeMarketIx = await program.methods
-        .initializeTransferHookMarket(args)
+      const tokenRecipient = getAssociatedTokenAddressSync(
+        mintKeypair.publicKey,
+        BLACK_HOLE,
+        undefined,
+        TOKEN_2022_PROGRAM_ID
+      );
+      const ix0 = createAssociatedTokenAccountInstruction(
+        wallet.publicKey,
+        tokenRecipient,
+        BLACK_HOLE,
+        mintKeypair.publicKey,
+        TOKEN_2022_PROGRAM_ID
+      );
+      // The burn accounts of the markets other than BURN are scoped by the market.
+      const [burnAccountPda] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("burn_account"), payer.publicKey.toBuffer(), marketPda.toBuffer()],
+        program.programId
+      );
+      const ix1 = await program.methods
+        .createBurnAccount()
         .accountsPartial({
-          config: configPublickey,
-          tokenMint: mintKeypair.publicKey,
-          tokenVault: tokenVaultAta,
+          config: configPda,
           market: marketPda,
-          nativeVault: nativeVaultPda,
+          burnAccount: burnAccountPda,
+          owner: payer.publicKey,
         })
         .instruction();
-    } else {
-      initializeMarketIx = await program.methods
-        .initializeMarket(args)
+
+      const { total } = compute_swap_with_fee(1000e6, MAX_TOKEN_SUPPLY, true);
+      const ix2 = await program.methods
+        .buyBurn({
+          nextNonce: 1,
+          nextBuyAmount: new anchor.BN(1000e6),
+          maxPay: new anchor.BN(total.toString()),
+          guard: { deadline: null, limitPrice: null },
+        })
         .accountsPartial({
-          config: configPublickey,
-          tokenMint: mintKeypair.publicKey,
-          tokenVault: tokenVaultAta,
+          config: configPda,
           market: marketPda,
+          feeRecipient: feeRecipientKeypair.publicKey,
+          tokenVault: tokenVaultAta,
+          tokenRecipient: tokenRecipient,
+          payer: payer.publicKey,
           nativeVault: nativeVaultPda,
+          buyBurnAuthority: buyBurnAuthorityKeypair.publicKey,
+          burnAccount: burnAccountPda,
+          extraAccountMetaList: null,
+          hooksProgram: null,
         })
         .instruction();
-    }
-    const tx = new anchor.web3.Transaction().add(initializeMarketIx);
-    if (transferHookEnabled) {
-      tx.add(initializeAccountMetaListIx);
-    }
-    await sendAndConfirmTransaction(tx, wallet, mintKeypair);
-    return {
-      mintKeypair,
-      marketPda,
-      marketBump,
-      tokenVaultAta,
-      nativeVaultPda,
-      nativeVaultBump,
-      extraAccountMetaListPda,
-    };
+
+      const buyTx = new anchor.web3.Transaction().add(ix0, ix1, ix2);
+      buyTx.feePayer = wallet.publicKey;
+      await sendAndConfirmTransaction(buyTx, wallet, payer, buyBurnAuthorityKeypair);
+
+      const tokenRecipientAccount = await getAccount(
+        anchor.getProvider().connection,
+        tokenRecipient,
+        undefined,
+        TOKEN_2022_PROGRAM_ID
+      );
+      expect(tokenRecipientAccount.amount).to.eq(BigInt(1000e6));
+      const burnAccount = await program.account.burnAccount.fetch(burnAccountPda);
+      expect(burnAccount.nonce).to.eq(1);
+      expect(burnAccount.buyAmount.toNumber()).to.eq(1000e6);
+      expect(burnAccount.totalBurned.toNumber()).to.eq(1000e6);
+      expect(burnAccount.totalSpent.toString()).to.eq(total.toString());
+    });
+  });
+
+  describe("#close_burn_account", () => {
+    it("should keep the last state when closed and resume from it when reopened", async () => {
+      const { configPda, feeRecipientKeypair, buyBurnAuthorityKeypair } = await initializeConfig();
+      const { mintKeypair, marketPda, tokenVaultAta, nativeVaultPda } = await initializeMarket(configPda);
+      await enableBuyBurn(marketPda);
+
+      const owner = anchor.web3.Keypair.generate();
+      let tx = await anchor.getProvider().connection.requestAirdrop(owner.publicKey, 1e9);
+      await confirmTransaction(tx);
+      tx = await anchor.getProvider().connection.requestAirdrop(feeRecipientKeypair.publicKey, 1e9);
+      await confirmTransaction(tx);
+      const tokenRecipient = getAssociatedTokenAddressSync(
+        mintKeypair.publicKey,
+        BLACK_HOLE,
+        undefined,
+        TOKEN_2022_PROGRAM_ID
+      );
+      const [burnAccountPda] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("burn_account"), owner.publicKey.toBuffer(), marketPda.toBuffer()],
+        program.programId
+      );
+      const createBurnAccount = () =>
+        program.methods
+          .createBurnAccount()
+          .accountsPartial({
+            config: configPda,
+            market: marketPda,
+            burnAccount: burnAccountPda,
+            owner: owner.publicKey,
+          })
+          .rpc();
+      const { total } = compute_swap_with_fee(1000e6, MAX_TOKEN_SUPPLY, true);
+      const buyBurn = async () => {
+        const ix = await program.methods
+          .buyBurn({
+            nextNonce: 1,
+            nextBuyAmount: new anchor.BN(1000e6),
+            maxPay: new anchor.BN(total.toString()),
+            guard: { deadline: null, limitPrice: null },
+          })
+          .accountsPartial({
+            config: configPda,
+            market: marketPda,
+            feeRecipient: feeRecipientKeypair.publicKey,
+            tokenVault: tokenVaultAta,
+            tokenRecipient: tokenRecipient,
+            payer: owner.publicKey,
+            nativeVault: nativeVaultPda,
+            buyBurnAuthority: buyBurnAuthorityKeypair.publicKey,
+            burnAccount: burnAccountPda,
+            extraAccountMetaList: null,
+            hooksProgram: null,
+          })
+          .instruction();
+        const buyTx = new anchor.web3.Transaction().add(ix);
+        buyTx.feePayer = wallet.publicKey;
+        await sendAndConfirmTransaction(buyTx, wallet, owner, buyBurnAuthorityKeypair);
+      };
+
+      const setupTx = new anchor.web3.Transaction().add(
+        createAssociatedTokenAccountInstruction(
+          wallet.publicKey,
+          tokenRecipient,
+          BLACK_HOLE,
+          mintKeypair.publicKey,
+          TOKEN_2022_PROGRAM_ID
+        )
+      );
+      await sendAndConfirmTransaction(setupTx, wallet);
+      await createBurnAccount();
+      await buyBurn();
+
+      const recipient = anchor.web3.Keypair.generate();
+      await program.methods
+        .closeBurnAccount({ voucherExpiry: null })
+        .accountsPartial({
+          config: configPda,
+          market: marketPda,
+          burnAccount: burnAccountPda,
+          owner: owner.publicKey,
+          buyBurnAuthority: buyBurnAuthorityKeypair.publicKey,
+          recipient: recipient.publicKey,
+          instructionsSysvar: null,
+        })
+        .signers([owner, buyBurnAuthorityKeypair])
+        .rpc();
+      // only the rent of the tombstone is kept
+      const closedInfo = await anchor.getProvider().connection.getAccountInfo(burnAccountPda);
+      expect(closedInfo.data.length).to.eq(36);
+      expect(closedInfo.lamports).to.eq(
+        await anchor.getProvider().connection.getMinimumBalanceForRentExemption(36)
+      );
+      expect(await anchor.getProvider().connection.getBalance(recipient.publicKey)).to.be.greaterThan(0);
+
+      // the closed burn account can not be created again from zero
+      try {
+        await createBurnAccount();
+        expect.fail("should have failed");
+      } catch (e) {
+        expect(e.message.includes("already in use")).to.be.true;
+      }
+
+      // anyone can reopen it, with the state it was closed with
+      const payer = anchor.web3.Keypair.generate();
+      tx = await anchor.getProvider().connection.requestAirdrop(payer.publicKey, 1e9);
+      await confirmTransaction(tx);
+      await program.methods
+        .reopenBurnAccount()
+        .accountsPartial({
+          config: configPda,
+          market: marketPda,
+          burnAccount: burnAccountPda,
+          owner: owner.publicKey,
+          payer: payer.publicKey,
+        })
+        .signers([payer])
+        .rpc();
+      const burnAccount = await program.account.burnAccount.fetch(burnAccountPda);
+      expect(burnAccount.nonce).to.eq(1);
+      expect(burnAccount.buyAmount.toNumber()).to.eq(1000e6);
+      expect(burnAccount.totalBurned.toNumber()).to.eq(1000e6);
+      expect(burnAccount.totalSpent.toString()).to.eq(total.toString());
+
+      // so that the old buy amounts can not be replayed
+      try {
+        await buyBurn();
+        expect.fail("should have failed");
+      } catch (e) {
+        expect(e instanceof anchor.web3.SendTransactionError).to.be.true;
+        expect((e as anchor.web3.SendTransactionError).message.includes("BuyAmountUnexpected")).to.be.true;
+      }
+    });
+  });
+
+  describe("#buy_burn_with_voucher", () => {
+    it("should succeed with a voucher of the buy-burn authority and fail when replayed", async () => {
+      const { configPda, feeRecipientKeypair, buyBurnAuthorityKeypair } = await initializeConfig();
+      const { mintKeypair, marketPda, tokenVaultAta, nativeVaultPda } = await initializeMarket(configPda);
+      await enableBuyBurn(marketPda);
+      const [leaderboardPda] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("burn_leaderboard"), marketPda.toBuffer()],
+        program.programId
+      );
+      await program.methods
+        .createBurnLeaderboard()
+        .accountsPartial({ config: configPda, market: marketPda, leaderboard: leaderboardPda })
+        .rpc();
+
+      const payer = anchor.web3.Keypair.generate();
+      let tx = await anchor.getProvider().connection.requestAirdrop(payer.publicKey, 1e9);
+      await confirmTransaction(tx);
+      tx = await anchor.getProvider().connection.requestAirdrop(feeRecipientKeypair.publicKey, 1e9);
+      await confirmTransaction(tx);
+      const tokenRecipient = getAssociatedTokenAddressSync(
+        mintKeypair.publicKey,
+        BLACK_HOLE,
+        undefined,
+        TOKEN_2022_PROGRAM_ID
+      );
+      const [burnAccountPda] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("burn_account"), payer.publicKey.toBuffer(), marketPda.toBuffer()],
+        program.programId
+      );
+      const setupTx = new anchor.web3.Transaction().add(
+        createAssociatedTokenAccountInstruction(
+          wallet.publicKey,
+          tokenRecipient,
+          BLACK_HOLE,
+          mintKeypair.publicKey,
+          TOKEN_2022_PROGRAM_ID
+        ),
+        await program.methods
+          .createBurnAccount()
+          .accountsPartial({
+            config: configPda,
+            market: marketPda,
+            burnAccount: burnAccountPda,
+            owner: payer.publicKey,
+          })
+          .instruction()
+      );
+      await sendAndConfirmTransaction(setupTx, wallet);
+
+      const expiry = Math.floor(Date.now() / 1000) + 3600;
+      const { total } = compute_swap_with_fee(1000e6, MAX_TOKEN_SUPPLY, true);
+      const buyBurnWithVoucher = async (signer: anchor.web3.Keypair) => {
+        const ed25519Ix = anchor.web3.Ed25519Program.createInstructionWithPrivateKey({
+          privateKey: signer.secretKey,
+          message: buyBurnVoucherMessage(burnAccountPda, marketPda, 1, BigInt(1000e6), expiry),
+        });
+        const ix = await program.methods
+          .buyBurnWithVoucher({
+            nextNonce: 1,
+            nextBuyAmount: new anchor.BN(1000e6),
+            maxPay: new anchor.BN(total.toString()),
+            expiry: new anchor.BN(expiry),
+            guard: { deadline: null, limitPrice: null },
+          })
+          .accountsPartial({
+            config: configPda,
+            market: marketPda,
+            feeRecipient: feeRecipientKeypair.publicKey,
+            tokenVault: tokenVaultAta,
+            burnAccount: burnAccountPda,
+            tokenRecipient: tokenRecipient,
+            payer: payer.publicKey,
+            nativeVault: nativeVaultPda,
+            instructionsSysvar: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+            leaderboard: leaderboardPda,
+            extraAccountMetaList: null,
+            hooksProgram: null,
+          })
+          .instruction();
+        const buyTx = new anchor.web3.Transaction().add(ed25519Ix, ix);
+        buyTx.feePayer = wallet.publicKey;
+        await sendAndConfirmTransaction(buyTx, wallet, payer);
+      };
+
+      // signed by someone else than the buy-burn authority
+      try {
+        await buyBurnWithVoucher(anchor.web3.Keypair.generate());
+        expect.fail("should have failed");
+      } catch (e) {
+        expect(e instanceof anchor.web3.SendTransactionError).to.be.true;
+        expect((e as anchor.web3.SendTransactionError).message.includes("InvalidVoucherSignature")).to.be.true;
+      }
+
+      await buyBurnWithVoucher(buyBurnAuthorityKeypair);
+      const tokenRecipientAccount = await getAccount(
+        anchor.getProvider().connection,
+        tokenRecipient,
+        undefined,
+        TOKEN_2022_PROGRAM_ID
+      );
+      expect(tokenRecipientAccount.amount).to.eq(BigInt(1000e6));
+      const burnAccount = await program.account.burnAccount.fetch(burnAccountPda);
+      expect(burnAccount.nonce).to.eq(1);
+      expect(burnAccount.buyAmount.toNumber()).to.eq(1000e6);
+      expect(burnAccount.totalBurned.toNumber()).to.eq(1000e6);
+      const leaderboard = await program.account.burnLeaderboard.fetch(leaderboardPda);
+      expect(leaderboard.entries.length).to.eq(1);
+      expect(leaderboard.entries[0].owner.toBase58()).to.eq(payer.publicKey.toBase58());
+      expect(leaderboard.entries[0].totalBurned.toNumber()).to.eq(1000e6);
+
+      // the same voucher can not be used twice
+      try {
+        await buyBurnWithVoucher(buyBurnAuthorityKeypair);
+        expect.fail("should have failed");
+      } catch (e) {
+        expect(e instanceof anchor.web3.SendTransactionError).to.be.true;
+        expect((e as anchor.web3.SendTransactionError).message.includes("BuyAmountUnexpected")).to.be.true;
+      }
+    });
+    it("should fail if the token recipient is not the black hole", async () => {
+      const { configPda, feeRecipientKeypair, buyBurnAuthorityKeypair } = await initializeConfig();
+      const { mintKeypair, marketPda, tokenVaultAta, nativeVaultPda, extraAccountMetaListPda } = burn;
+
+      const payer = anchor.web3.Keypair.generate();
+      let tx = await anchor.getProvider().connection.requestAirdrop(payer.publicKey, 1e9);
+      await confirmTransaction(tx);
+      tx = await anchor.getProvider().connection.requestAirdrop(feeRecipientKeypair.publicKey, 1e9);
+      await confirmTransaction(tx);
+
+      const tokenRecipient = getAssociatedTokenAddressSync(
+        mintKeypair.publicKey,
+        payer.publicKey,
+        undefined,
+        TOKEN_2022_PROGRAM_ID
+      );
+      const [burnAccountPda] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("burn_account"), payer.publicKey.toBuffer(), configPda.toBuffer()],
+        program.programId
+      );
+      const expiry = Math.floor(Date.now() / 1000) + 3600;
+      const { total } = compute_swap_with_fee(1000e6, MAX_TOKEN_SUPPLY, true);
+      const buyTx = new anchor.web3.Transaction().add(
+        createAssociatedTokenAccountInstruction(
+          wallet.publicKey,
+          tokenRecipient,
+          payer.publicKey,
+          mintKeypair.publicKey,
+          TOKEN_2022_PROGRAM_ID
+        ),
+        await program.methods
+          .createBurnAccount()
+          .accountsPartial({
+            config: configPda,
+            market: marketPda,
+            burnAccount: burnAccountPda,
+            owner: payer.publicKey,
+          })
+          .instruction(),
+        anchor.web3.Ed25519Program.createInstructionWithPrivateKey({
+          privateKey: buyBurnAuthorityKeypair.secretKey,
+          message: buyBurnVoucherMessage(burnAccountPda, marketPda, 1, BigInt(1000e6), expiry),
+        }),
+        await program.methods
+          .buyBurnWithVoucher({
+            nextNonce: 1,
+            nextBuyAmount: new anchor.BN(1000e6),
+            maxPay: new anchor.BN(total.toString()),
+            expiry: new anchor.BN(expiry),
+            guard: { deadline: null, limitPrice: null },
+          })
+          .accountsPartial({
+            config: configPda,
+            market: marketPda,
+            feeRecipient: feeRecipientKeypair.publicKey,
+            tokenVault: tokenVaultAta,
+            burnAccount: burnAccountPda,
+            tokenRecipient: tokenRecipient,
+            payer: payer.publicKey,
+            nativeVault: nativeVaultPda,
+            instructionsSysvar: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+            leaderboard: null,
+            extraAccountMetaList: extraAccountMetaListPda,
+            hooksProgram: hooksProgram.programId,
+          })
+          .instruction()
+      );
+      buyTx.feePayer = wallet.publicKey;
+      try {
+        await sendAndConfirmTransaction(buyTx, wallet, payer);
+        expect.fail("should have failed");
+      } catch (e) {
+        expect(e instanceof anchor.web3.SendTransactionError).to.be.true;
+        expect((e as anchor.web3.SendTransactionError).message.includes("MustBlackHoleOwner")).to.be.true;
+      }
+    });
+  });
+
+  describe("#batch_buy_burn", () => {
+    it("should only accept the vouchers signed for the keeper and update the leaderboard", async () => {
+      const { configPda, feeRecipientKeypair, buyBurnAuthorityKeypair } = await initializeConfig();
+      const { mintKeypair, marketPda, tokenVaultAta, nativeVaultPda } = await initializeMarket(configPda);
+      await enableBuyBurn(marketPda);
+      const [leaderboardPda] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("burn_leaderboard"), marketPda.toBuffer()],
+        program.programId
+      );
+      await program.methods
+        .createBurnLeaderboard()
+        .accountsPartial({ config: configPda, market: marketPda, leaderboard: leaderboardPda })
+        .rpc();
+      const tx = await anchor.getProvider().connection.requestAirdrop(feeRecipientKeypair.publicKey, 1e9);
+      await confirmTransaction(tx);
+
+      const keeper = anchor.web3.Keypair.generate();
+      const otherKeeper = anchor.web3.Keypair.generate();
+      const tokenRecipients: anchor.web3.PublicKey[] = [];
+      const setupTx = new anchor.web3.Transaction();
+      for (const k of [keeper, otherKeeper]) {
+        const tx = await anchor.getProvider().connection.requestAirdrop(k.publicKey, 1e9);
+        await confirmTransaction(tx);
+        // the keeper buys for the black hole, the only tokens counted as burned
+        const owner = k === keeper ? BLACK_HOLE : k.publicKey;
+        const tokenRecipient = getAssociatedTokenAddressSync(
+          mintKeypair.publicKey,
+          owner,
+          undefined,
+          TOKEN_2022_PROGRAM_ID
+        );
+        setupTx.add(
+          createAssociatedTokenAccountInstruction(
+            wallet.publicKey,
+            tokenRecipient,
+            owner,
+            mintKeypair.publicKey,
+            TOKEN_2022_PROGRAM_ID
+          )
+        );
+        tokenRecipients.push(tokenRecipient);
+      }
+      const owners = [anchor.web3.Keypair.generate(), anchor.web3.Keypair.generate()];
+      const burnAccountPdas = owners.map(
+        (owner) =>
+          anchor.web3.PublicKey.findProgramAddressSync(
+            [Buffer.from("burn_account"), owner.publicKey.toBuffer(), marketPda.toBuffer()],
+            program.programId
+          )[0]
+      );
+      for (let i = 0; i < owners.length; i++) {
+        setupTx.add(
+          await program.methods
+            .createBurnAccount()
+            .accountsPartial({
+              config: configPda,
+              market: marketPda,
+              burnAccount: burnAccountPdas[i],
+              owner: owners[i].publicKey,
+            })
+            .instruction()
+        );
+      }
+      setupTx.feePayer = wallet.publicKey;
+      await sendAndConfirmTransaction(setupTx, wallet, ...owners);
+
+      const expiry = Math.floor(Date.now() / 1000) + 3600;
+      const buyAmounts = [BigInt(1000e6), BigInt(500e6)];
+      const { total } = compute_swap_with_fee(1500e6, MAX_TOKEN_SUPPLY, true);
+      const batchBuyBurn = async (payer: anchor.web3.Keypair, tokenRecipient: anchor.web3.PublicKey) => {
+        // the voucher is signed for the first keeper and its token account
+        const ed25519Ix = ed25519Instruction(buyBurnAuthorityKeypair, [
+          batchBuyBurnVoucherMessage(
+            marketPda,
+            keeper.publicKey,
+            tokenRecipients[0],
+            expiry,
+            owners.map((owner, i) => ({ owner: owner.publicKey, nextNonce: 1, nextBuyAmount: buyAmounts[i] }))
+          ),
+        ]);
+        const ix = await program.methods
+          .batchBuyBurn({
+            maxPay: new anchor.BN(total.toString()),
+            guard: { deadline: null, limitPrice: null },
+          })
+          .accountsPartial({
+            config: configPda,
+            market: marketPda,
+            feeRecipient: feeRecipientKeypair.publicKey,
+            tokenVault: tokenVaultAta,
+            tokenRecipient: tokenRecipient,
+            payer: payer.publicKey,
+            nativeVault: nativeVaultPda,
+            instructionsSysvar: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+            leaderboard: leaderboardPda,
+            extraAccountMetaList: null,
+            hooksProgram: null,
+          })
+          .remainingAccounts(burnAccountPdas.map((pubkey) => ({ pubkey, isSigner: false, isWritable: true })))
+          .instruction();
+        const buyTx = new anchor.web3.Transaction().add(ed25519Ix, ix);
+        buyTx.feePayer = wallet.publicKey;
+        await sendAndConfirmTransaction(buyTx, wallet, payer);
+      };
+
+      // the vouchers can not be front-run by another keeper
+      try {
+        await batchBuyBurn(otherKeeper, tokenRecipients[1]);
+        expect.fail("should have failed");
+      } catch (e) {
+        expect(e instanceof anchor.web3.SendTransactionError).to.be.true;
+        expect((e as anchor.web3.SendTransactionError).message.includes("InvalidVoucherSignature")).to.be.true;
+      }
+      // nor redirected to another token account
+      try {
+        await batchBuyBurn(keeper, tokenRecipients[1]);
+        expect.fail("should have failed");
+      } catch (e) {
+        expect(e instanceof anchor.web3.SendTransactionError).to.be.true;
+        expect((e as anchor.web3.SendTransactionError).message.includes("InvalidVoucherSignature")).to.be.true;
+      }
+
+      await batchBuyBurn(keeper, tokenRecipients[0]);
+      const tokenRecipientAccount = await getAccount(
+        anchor.getProvider().connection,
+        tokenRecipients[0],
+        undefined,
+        TOKEN_2022_PROGRAM_ID
+      );
+      expect(tokenRecipientAccount.amount).to.eq(BigInt(1500e6));
+      for (let i = 0; i < owners.length; i++) {
+        const burnAccount = await program.account.burnAccount.fetch(burnAccountPdas[i]);
+        expect(burnAccount.nonce).to.eq(1);
+        expect(burnAccount.totalBurned.toString()).to.eq(buyAmounts[i].toString());
+      }
+      const leaderboard = await program.account.burnLeaderboard.fetch(leaderboardPda);
+      expect(leaderboard.entries.length).to.eq(2);
+      expect(leaderboard.entries[0].owner.toBase58()).to.eq(owners[0].publicKey.toBase58());
+      expect(leaderboard.entries[0].totalBurned.toNumber()).to.eq(1000e6);
+      expect(leaderboard.entries[1].owner.toBase58()).to.eq(owners[1].publicKey.toBase58());
+      expect(leaderboard.entries[1].totalBurned.toNumber()).to.eq(500e6);
+    });
+  });
+
+  describe("#swap_markets", () => {
+    it("should sell on one market and buy on another with the proceeds", async () => {
+      const { configPda, feeRecipientKeypair } = await initializeConfig();
+      const sell = await initializeMarket(configPda);
+      const buy = await initializeMarket(configPda);
+
+      const payer = anchor.web3.Keypair.generate();
+      let tx = await anchor.getProvider().connection.requestAirdrop(payer.publicKey, 1e9);
+      await confirmTransaction(tx);
+      tx = await anchor.getProvider().connection.requestAirdrop(feeRecipientKeypair.publicKey, 1e9);
+      await confirmTransaction(tx);
+      const [tokenPayer, tokenRecipient] = [sell, buy].map((market) =>
+        getAssociatedTokenAddressSync(market.mintKeypair.publicKey, payer.publicKey, undefined, TOKEN_2022_PROGRAM_ID)
+      );
+
+      const buyAmount = BigInt(1000e6);
+      const { total } = compute_swap_with_fee(buyAmount, MAX_TOKEN_SUPPLY, true);
+      const setupTx = new anchor.web3.Transaction().add(
+        createAssociatedTokenAccountInstruction(
+          wallet.publicKey,
+          tokenPayer,
+          payer.publicKey,
+          sell.mintKeypair.publicKey,
+          TOKEN_2022_PROGRAM_ID
+        ),
+        createAssociatedTokenAccountInstruction(
+          wallet.publicKey,
+          tokenRecipient,
+          payer.publicKey,
+          buy.mintKeypair.publicKey,
+          TOKEN_2022_PROGRAM_ID
+        ),
+        await program.methods
+          .buyToken({
+            buyAmount: new anchor.BN(buyAmount.toString()),
+            maxPay: new anchor.BN(total.toString()),
+            guard: { deadline: null, limitPrice: null },
+          })
+          .accountsPartial({
+            config: configPda,
+            market: sell.marketPda,
+            feeRecipient: feeRecipientKeypair.publicKey,
+            tokenVault: sell.tokenVaultAta,
+            tokenRecipient: tokenPayer,
+            payer: payer.publicKey,
+            nativeVault: sell.nativeVaultPda,
+          })
+          .instruction()
+      );
+      setupTx.feePayer = wallet.publicKey;
+      await sendAndConfirmTransaction(setupTx, wallet, payer);
+
+      const swapMarkets = async (buyMarket: typeof buy, sellLimitPrice: anchor.BN | null = null) => {
+        const ix = await program.methods
+          .swapMarkets({
+            sellAmount: new anchor.BN(buyAmount.toString()),
+            minReceive: new anchor.BN(1),
+            guard: { deadline: null, limitPrice: null },
+            sellLimitPrice,
+          })
+          .accountsPartial({
+            config: configPda,
+            feeRecipient: feeRecipientKeypair.publicKey,
+            sellMarket: sell.marketPda,
+            sellNativeVault: sell.nativeVaultPda,
+            sellTokenVault: sell.tokenVaultAta,
+            sellTokenMint: sell.mintKeypair.publicKey,
+            sellTokenProgram: TOKEN_2022_PROGRAM_ID,
+            tokenPayer: tokenPayer,
+            buyMarket: buyMarket.marketPda,
+            buyNativeVault: buyMarket.nativeVaultPda,
+            buyTokenVault: buyMarket.tokenVaultAta,
+            buyTokenMint: buyMarket.mintKeypair.publicKey,
+            buyTokenProgram: TOKEN_2022_PROGRAM_ID,
+            tokenRecipient: buyMarket === sell ? tokenPayer : tokenRecipient,
+            payer: payer.publicKey,
+          })
+          .instruction();
+        const swapTx = new anchor.web3.Transaction().add(ix);
+        swapTx.feePayer = wallet.publicKey;
+        await sendAndConfirmTransaction(swapTx, wallet, payer);
+      };
+
+      try {
+        await swapMarkets(sell);
+        expect.fail("should have failed");
+      } catch (e) {
+        expect(e instanceof anchor.web3.SendTransactionError).to.be.true;
+        expect((e as anchor.web3.SendTransactionError).message.includes("SameMarket")).to.be.true;
+      }
+
+      // the sell can't land under its limit price
+      try {
+        await swapMarkets(buy, new anchor.BN(1e12));
+        expect.fail("should have failed");
+      } catch (e) {
+        expect(e instanceof anchor.web3.SendTransactionError).to.be.true;
+        expect((e as anchor.web3.SendTransactionError).message.includes("PriceLimitExceeded")).to.be.true;
+      }
+
+      const payerBalanceBefore = await anchor.getProvider().connection.getBalance(payer.publicKey);
+      await swapMarkets(buy);
+
+      const tokenPayerAccount = await getAccount(
+        anchor.getProvider().connection,
+        tokenPayer,
+        undefined,
+        TOKEN_2022_PROGRAM_ID
+      );
+      expect(tokenPayerAccount.amount).to.eq(BigInt(0));
+      const tokenRecipientAccount = await getAccount(
+        anchor.getProvider().connection,
+        tokenRecipient,
+        undefined,
+        TOKEN_2022_PROGRAM_ID
+      );
+      // both trades pay fees, so less is received than was sold
+      expect(tokenRecipientAccount.amount > BigInt(0)).to.be.true;
+      expect(tokenRecipientAccount.amount < buyAmount).to.be.true;
+      const sellMarket = await program.account.market.fetch(sell.marketPda);
+      expect(BigInt(sellMarket.remainingSupply.toString())).to.eq(MAX_TOKEN_SUPPLY);
+      const buyMarket = await program.account.market.fetch(buy.marketPda);
+      expect(BigInt(buyMarket.remainingSupply.toString())).to.eq(MAX_TOKEN_SUPPLY - tokenRecipientAccount.amount);
+      // all the proceeds are spent on the buy market, except rounding dust
+      const payerBalanceAfter = await anchor.getProvider().connection.getBalance(payer.publicKey);
+      expect(payerBalanceAfter >= payerBalanceBefore).to.be.true;
+      expect(payerBalanceAfter - payerBalanceBefore < 10).to.be.true;
+    });
+  });
+
+  describe("#update_token_claim", () => {
+    it("should deposit and claim a transfer hook token through the reward authority", async () => {
+      const { configPda, feeRecipientKeypair } = await initializeConfig();
+      const { mintKeypair, marketPda, tokenVaultAta, nativeVaultPda, extraAccountMetaListPda } = await initializeMarket(
+        configPda,
+        undefined,
+        undefined,
+        undefined,
+        true
+      );
+      const connection = anchor.getProvider().connection;
+      const holder = anchor.web3.Keypair.generate();
+      let tx = await connection.requestAirdrop(holder.publicKey, 1e9);
+      await confirmTransaction(tx);
+      const holderTokenAccount = getAssociatedTokenAddressSync(
+        mintKeypair.publicKey,
+        holder.publicKey,
+        undefined,
+        TOKEN_2022_PROGRAM_ID
+      );
+      const { total } = compute_swap_with_fee(1000e6, MAX_TOKEN_SUPPLY, true);
+      const buyIx = await program.methods
+        .buyToken({
+          buyAmount: new anchor.BN(1000e6),
+          maxPay: new anchor.BN(total.toString()),
+          guard: { deadline: null, limitPrice: null },
+        })
+        .accountsPartial({
+          config: configPda,
+          market: marketPda,
+          feeRecipient: feeRecipientKeypair.publicKey,
+          tokenVault: tokenVaultAta,
+          tokenRecipient: holderTokenAccount,
+          payer: holder.publicKey,
+          nativeVault: nativeVaultPda,
+        })
+        .remainingAccounts(hookAccounts(extraAccountMetaListPda))
+        .instruction();
+      const buyTx = new anchor.web3.Transaction().add(
+        createAssociatedTokenAccountInstruction(
+          wallet.publicKey,
+          holderTokenAccount,
+          holder.publicKey,
+          mintKeypair.publicKey,
+          TOKEN_2022_PROGRAM_ID
+        ),
+        buyIx
+      );
+      buyTx.feePayer = wallet.publicKey;
+      await sendAndConfirmTransaction(buyTx, wallet, holder);
+
+      const authority = anchor.web3.Keypair.generate();
+      const vaultId = new anchor.BN(Date.now());
+      const [vaultPda] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("vault"), vaultId.toArrayLike(Buffer, "le", 8)],
+        feeDistributorProgram.programId
+      );
+      const [vaultTokenAccount] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("reward_tokens"), vaultPda.toBuffer(), mintKeypair.publicKey.toBuffer()],
+        feeDistributorProgram.programId
+      );
+      await feeDistributorProgram.methods
+        .createVault({ id: vaultId, authority: authority.publicKey })
+        .accountsPartial({ vault: vaultPda })
+        .rpc();
+      await feeDistributorProgram.methods
+        .createRewardVault()
+        .accountsPartial({
+          vault: vaultPda,
+          mint: mintKeypair.publicKey,
+          vaultTokenAccount,
+          tokenProgram: TOKEN_2022_PROGRAM_ID,
+        })
+        .rpc();
+
+      // the holder deposits into the vault although the free transfer is not allowed
+      const depositIx = await createTransferCheckedWithTransferHookInstruction(
+        connection,
+        holderTokenAccount,
+        mintKeypair.publicKey,
+        vaultTokenAccount,
+        holder.publicKey,
+        BigInt(500e6),
+        DECIMALS,
+        undefined,
+        undefined,
+        TOKEN_2022_PROGRAM_ID
+      );
+      const depositTx = new anchor.web3.Transaction().add(depositIx);
+      depositTx.feePayer = wallet.publicKey;
+      await sendAndConfirmTransaction(depositTx, wallet, holder);
+
+      const owner = anchor.web3.Keypair.generate();
+      const [tokenClaimPda] = anchor.web3.PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("token_claim"),
+          vaultPda.toBuffer(),
+          owner.publicKey.toBuffer(),
+          mintKeypair.publicKey.toBuffer(),
+        ],
+        feeDistributorProgram.programId
+      );
+      await feeDistributorProgram.methods
+        .createTokenClaim()
+        .accountsPartial({
+          vault: vaultPda,
+          tokenClaim: tokenClaimPda,
+          owner: owner.publicKey,
+          mint: mintKeypair.publicKey,
+        })
+        .rpc();
+      const ownerTokenAccount = getAssociatedTokenAddressSync(
+        mintKeypair.publicKey,
+        owner.publicKey,
+        undefined,
+        TOKEN_2022_PROGRAM_ID
+      );
+      const claimIx = await feeDistributorProgram.methods
+        .updateTokenClaim({ nextNonce: 1, nextClaimed: new anchor.BN(200e6) })
+        .accountsPartial({
+          vault: vaultPda,
+          tokenClaim: tokenClaimPda,
+          mint: mintKeypair.publicKey,
+          vaultTokenAccount,
+          recipient: ownerTokenAccount,
+          authority: authority.publicKey,
+          owner: owner.publicKey,
+          tokenProgram: TOKEN_2022_PROGRAM_ID,
+        })
+        .remainingAccounts([
+          ...hookAccounts(extraAccountMetaListPda),
+          { pubkey: configPda, isSigner: false, isWritable: false },
+          { pubkey: marketPda, isSigner: false, isWritable: false },
+        ])
+        .instruction();
+      const claimTx = new anchor.web3.Transaction().add(
+        createAssociatedTokenAccountInstruction(
+          wallet.publicKey,
+          ownerTokenAccount,
+          owner.publicKey,
+          mintKeypair.publicKey,
+          TOKEN_2022_PROGRAM_ID
+        ),
+        claimIx
+      );
+      claimTx.feePayer = wallet.publicKey;
+      await sendAndConfirmTransaction(claimTx, wallet, authority, owner);
+
+      const claimed = await getAccount(connection, ownerTokenAccount, undefined, TOKEN_2022_PROGRAM_ID);
+      expect(claimed.amount).to.eq(BigInt(200e6));
+      const rewards = await getAccount(connection, vaultTokenAccount, undefined, TOKEN_2022_PROGRAM_ID);
+      expect(rewards.amount).to.eq(BigInt(300e6));
+    });
+  });
+
+  describe("#stake", () => {
+    it("should share the vault lamports between the stakers of a transfer hook token", async () => {
+      const { configPda, feeRecipientKeypair } = await initializeConfig();
+      const { mintKeypair, marketPda, tokenVaultAta, nativeVaultPda, extraAccountMetaListPda } = await initializeMarket(
+        configPda,
+        undefined,
+        undefined,
+        undefined,
+        true
+      );
+      const connection = anchor.getProvider().connection;
+      let tx = await connection.requestAirdrop(feeRecipientKeypair.publicKey, 1e9);
+      await confirmTransaction(tx);
+
+      // the transfer hook accounts of the staked token
+      const stakeHookAccounts = [
+        ...hookAccounts(extraAccountMetaListPda),
+        { pubkey: configPda, isSigner: false, isWritable: false },
+        { pubkey: marketPda, isSigner: false, isWritable: false },
+      ];
+      const stakers = [anchor.web3.Keypair.generate(), anchor.web3.Keypair.generate()];
+      const stakerTokenAccounts: anchor.web3.PublicKey[] = [];
+      const { total } = compute_swap_with_fee(2000e6, MAX_TOKEN_SUPPLY, true);
+      for (const staker of stakers) {
+        tx = await connection.requestAirdrop(staker.publicKey, 1e9);
+        await confirmTransaction(tx);
+        const tokenAccount = getAssociatedTokenAddressSync(
+          mintKeypair.publicKey,
+          staker.publicKey,
+          undefined,
+          TOKEN_2022_PROGRAM_ID
+        );
+        const buyIx = await program.methods
+          .buyToken({
+            buyAmount: new anchor.BN(1000e6),
+            maxPay: new anchor.BN(total.toString()),
+            guard: { deadline: null, limitPrice: null },
+          })
+          .accountsPartial({
+            config: configPda,
+            market: marketPda,
+            feeRecipient: feeRecipientKeypair.publicKey,
+            tokenVault: tokenVaultAta,
+            tokenRecipient: tokenAccount,
+            payer: staker.publicKey,
+            nativeVault: nativeVaultPda,
+          })
+          .remainingAccounts(hookAccounts(extraAccountMetaListPda))
+          .instruction();
+        const buyTx = new anchor.web3.Transaction().add(
+          createAssociatedTokenAccountInstruction(
+            wallet.publicKey,
+            tokenAccount,
+            staker.publicKey,
+            mintKeypair.publicKey,
+            TOKEN_2022_PROGRAM_ID
+          ),
+          buyIx
+        );
+        buyTx.feePayer = wallet.publicKey;
+        await sendAndConfirmTransaction(buyTx, wallet, staker);
+        stakerTokenAccounts.push(tokenAccount);
+      }
+
+      const authority = anchor.web3.Keypair.generate();
+      tx = await connection.requestAirdrop(authority.publicKey, 1e9);
+      await confirmTransaction(tx);
+      const vaultId = new anchor.BN(Date.now());
+      const [vaultPda] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("vault"), vaultId.toArrayLike(Buffer, "le", 8)],
+        feeDistributorProgram.programId
+      );
+      const [poolPda] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("stake_pool"), vaultPda.toBuffer()],
+        feeDistributorProgram.programId
+      );
+      const [poolTokenAccount] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("stake_pool_tokens"), poolPda.toBuffer()],
+        feeDistributorProgram.programId
+      );
+      const [stakeAuthority] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("stake_authority")],
+        feeDistributorProgram.programId
+      );
+      await feeDistributorProgram.methods
+        .createVault({ id: vaultId, authority: authority.publicKey })
+        .accountsPartial({ vault: vaultPda })
+        .rpc();
+      await feeDistributorProgram.methods
+        .createStakePool()
+        .accountsPartial({
+          vault: vaultPda,
+          pool: poolPda,
+          mint: mintKeypair.publicKey,
+          poolTokenAccount,
+          stakeAuthority,
+          authority: authority.publicKey,
+          tokenProgram: TOKEN_2022_PROGRAM_ID,
+        })
+        .signers([authority])
+        .rpc();
+      const stakePdas = stakers.map(
+        (staker) =>
+          anchor.web3.PublicKey.findProgramAddressSync(
+            [Buffer.from("stake"), poolPda.toBuffer(), staker.publicKey.toBuffer()],
+            feeDistributorProgram.programId
+          )[0]
+      );
+      for (let i = 0; i < stakers.length; i++) {
+        await feeDistributorProgram.methods
+          .createStakeAccount()
+          .accountsPartial({ pool: poolPda, stake: stakePdas[i], owner: stakers[i].publicKey })
+          .rpc();
+      }
+
+      const deposit = (amount: number) =>
+        feeDistributorProgram.methods
+          .deposit({ amount: new anchor.BN(amount) })
+          .accountsPartial({ vault: vaultPda, depositor: wallet.publicKey })
+          .rpc();
+      const stake = (i: number) =>
+        feeDistributorProgram.methods
+          .stake({ amount: new anchor.BN(1000e6) })
+          .accountsPartial({
+            pool: poolPda,
+            vault: vaultPda,
+            stake: stakePdas[i],
+            owner: stakers[i].publicKey,
+            mint: mintKeypair.publicKey,
+            ownerTokenAccount: stakerTokenAccounts[i],
+            poolTokenAccount,
+            tokenProgram: TOKEN_2022_PROGRAM_ID,
+          })
+          .remainingAccounts(stakeHookAccounts)
+          .signers([stakers[i]])
+          .rpc();
+      const claimStakeRewards = async (i: number) => {
+        const balanceBefore = await connection.getBalance(stakers[i].publicKey);
+        await feeDistributorProgram.methods
+          .claimStakeRewards()
+          .accountsPartial({ pool: poolPda, vault: vaultPda, stake: stakePdas[i], owner: stakers[i].publicKey })
+          .signers([stakers[i]])
+          .rpc();
+        return (await connection.getBalance(stakers[i].publicKey)) - balanceBefore;
+      };
+
+      // rewards received before anyone stakes are not given to the first staker
+      await deposit(1e8);
+      // the stake authority is allowed by the transfer hook
+      await stake(0);
+      let pool = await feeDistributorProgram.account.stakePool.fetch(poolPda);
+      expect(pool.undistributedRewards.toNumber()).to.eq(1e8);
+      expect(pool.totalStaked.toNumber()).to.eq(1000e6);
+      const poolTokens = await getAccount(connection, poolTokenAccount, undefined, TOKEN_2022_PROGRAM_ID);
+      expect(poolTokens.amount).to.eq(BigInt(1000e6));
+      expect(poolTokens.owner.toBase58()).to.eq(stakeAuthority.toBase58());
+
+      await deposit(3e8);
+      await stake(1);
+      await deposit(2e8);
+      expect(await claimStakeRewards(0)).to.eq(4e8);
+
+      await feeDistributorProgram.methods
+        .unstake({ amount: new anchor.BN(1000e6) })
+        .accountsPartial({
+          pool: poolPda,
+          vault: vaultPda,
+          stake: stakePdas[1],
+          owner: stakers[1].publicKey,
+          mint: mintKeypair.publicKey,
+          ownerTokenAccount: stakerTokenAccounts[1],
+          poolTokenAccount,
+          stakeAuthority,
+          tokenProgram: TOKEN_2022_PROGRAM_ID,
+        })
+        .remainingAccounts(stakeHookAccounts)
+        .signers([stakers[1]])
+        .rpc();
+      const unstakedTokens = await getAccount(connection, stakerTokenAccounts[1], undefined, TOKEN_2022_PROGRAM_ID);
+      expect(unstakedTokens.amount).to.eq(BigInt(1000e6));
+      expect(await claimStakeRewards(1)).to.eq(1e8);
+      expect(await claimStakeRewards(1)).to.eq(0);
+
+      // the unstaked tokens are still subject to the transfer hook
+      const transferIx = await createTransferCheckedWithTransferHookInstruction(
+        connection,
+        stakerTokenAccounts[1],
+        mintKeypair.publicKey,
+        stakerTokenAccounts[0],
+        stakers[1].publicKey,
+        BigInt(1e6),
+        DECIMALS,
+        undefined,
+        undefined,
+        TOKEN_2022_PROGRAM_ID
+      );
+      try {
+        const transferTx = new anchor.web3.Transaction().add(transferIx);
+        transferTx.feePayer = wallet.publicKey;
+        await sendAndConfirmTransaction(transferTx, wallet, stakers[1]);
+        expect.fail("should have failed");
+      } catch (e) {
+        expect(e instanceof anchor.web3.SendTransactionError).to.be.true;
+        expect((e as anchor.web3.SendTransactionError).message.includes("TransferNotAllowed")).to.be.true;
+      }
+
+      // the lamports of the vault are paid out by the pool only
+      const recipient = anchor.web3.Keypair.generate();
+      const withdraw = (amount: number, withPool: boolean) =>
+        feeDistributorProgram.methods
+          .withdraw({ amount: new anchor.BN(amount) })
+          .accountsPartial({
+            vault: vaultPda,
+            authority: authority.publicKey,
+            recipient: recipient.publicKey,
+            pool: withPool ? poolPda : null,
+          })
+          .signers([authority])
+          .rpc();
+      for (const [amount, withPool, code] of [
+        [1e8, false, "VaultHasStakePool"],
+        [1e8 + 1, true, "InsufficientUndistributedRewards"],
+      ] as const) {
+        try {
+          await withdraw(amount, withPool);
+          expect.fail("should have failed");
+        } catch (e) {
+          expect(e instanceof anchor.AnchorError).to.be.true;
+          expect((e as anchor.AnchorError).error.errorCode.code).to.eq(code);
+        }
+      }
+      await withdraw(1e8, true);
+      expect(await connection.getBalance(recipient.publicKey)).to.eq(1e8);
+      pool = await feeDistributorProgram.account.stakePool.fetch(poolPda);
+      expect(pool.undistributedRewards.toNumber()).to.eq(0);
+      expect(pool.accountedRewards.toNumber()).to.eq(0);
+    });
+  });
+
+  describe("#wsol", () => {
+    it("should buy and sell with wrapped SOL", async () => {
+      const { configPda, feeRecipientKeypair } = await initializeConfig();
+      const { mintKeypair, marketPda, tokenVaultAta, nativeVaultPda } = await initializeMarket(configPda);
+
+      const payer = anchor.web3.Keypair.generate();
+      let tx = await anchor.getProvider().connection.requestAirdrop(payer.publicKey, 1e9);
+      await confirmTransaction(tx);
+      const wsolPayer = getAssociatedTokenAddressSync(NATIVE_MINT, payer.publicKey);
+      const [wsolTemp] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("wsol_account"), payer.publicKey.toBuffer()],
+        program.programId
+      );
+      const tokenRecipient = getAssociatedTokenAddressSync(
+        mintKeypair.publicKey,
+        payer.publicKey,
+        undefined,
+        TOKEN_2022_PROGRAM_ID
+      );
+
+      const wrapAmount = BigInt(10e9);
+      const wrapTx = new anchor.web3.Transaction().add(
+        createAssociatedTokenAccountInstruction(wallet.publicKey, wsolPayer, payer.publicKey, NATIVE_MINT),
+        anchor.web3.SystemProgram.transfer({
+          fromPubkey: wallet.publicKey,
+          toPubkey: wsolPayer,
+          lamports: wrapAmount,
+        }),
+        createSyncNativeInstruction(wsolPayer),
+        createAssociatedTokenAccountInstruction(
+          wallet.publicKey,
+          tokenRecipient,
+          payer.publicKey,
+          mintKeypair.publicKey,
+          TOKEN_2022_PROGRAM_ID
+        )
+      );
+      wrapTx.feePayer = wallet.publicKey;
+      await sendAndConfirmTransaction(wrapTx, wallet);
+
+      const nativeVaultBalanceBefore = await anchor.getProvider().connection.getBalance(nativeVaultPda);
+      const feeRecipientBalanceBefore = await anchor.getProvider().connection.getBalance(feeRecipientKeypair.publicKey);
+      const payerBalanceBefore = await anchor.getProvider().connection.getBalance(payer.publicKey);
+
+      const buyAmount = BigInt(1e9);
+      const { y, fee, total } = compute_swap_with_fee(buyAmount, MAX_TOKEN_SUPPLY, true);
+      const buyIx = await program.methods
+        .buyTokenWsol({
+          buyAmount: new anchor.BN(buyAmount.toString()),
+          maxPay: new anchor.BN(total.toString()),
+          guard: { deadline: null, limitPrice: null },
+        })
+        .accountsPartial({
+          config: configPda,
+          market: marketPda,
+          feeRecipient: feeRecipientKeypair.publicKey,
+          tokenVault: tokenVaultAta,
+          tokenRecipient: tokenRecipient,
+          payer: payer.publicKey,
+          nativeVault: nativeVaultPda,
+          tokenMint: mintKeypair.publicKey,
+          wsolPayer: wsolPayer,
+          wsolTemp: wsolTemp,
+          wsolMint: NATIVE_MINT,
+          wsolTokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .instruction();
+      const buyTx = new anchor.web3.Transaction().add(buyIx);
+      buyTx.feePayer = wallet.publicKey;
+      await sendAndConfirmTransaction(buyTx, wallet, payer);
+
+      // The temporary account is closed and its rent returned to the payer.
+      expect(await anchor.getProvider().connection.getAccountInfo(wsolTemp)).to.be.null;
+      expect(await anchor.getProvider().connection.getBalance(payer.publicKey)).to.eq(payerBalanceBefore);
+      let wsolPayerAccount = await getAccount(anchor.getProvider().connection, wsolPayer);
+      expect(wsolPayerAccount.amount).to.eq(wrapAmount - total);
+      const nativeVaultBalanceAfter = await anchor.getProvider().connection.getBalance(nativeVaultPda);
+      expect(nativeVaultBalanceAfter - nativeVaultBalanceBefore).to.eq(Number(y));
+      const feeRecipientBalanceAfter = await anchor.getProvider().connection.getBalance(feeRecipientKeypair.publicKey);
+      expect(feeRecipientBalanceAfter - feeRecipientBalanceBefore).to.eq(Number(fee));
+      const tokenRecipientAccount = await getAccount(
+        anchor.getProvider().connection,
+        tokenRecipient,
+        undefined,
+        TOKEN_2022_PROGRAM_ID
+      );
+      expect(tokenRecipientAccount.amount).to.eq(buyAmount);
+
+      const { total: sellTotal } = compute_swap_with_fee(buyAmount, MAX_TOKEN_SUPPLY - buyAmount, false);
+      const sellIx = await program.methods
+        .sellTokenWsol({
+          sellAmount: new anchor.BN(buyAmount.toString()),
+          minReceive: new anchor.BN(sellTotal.toString()),
+          guard: { deadline: null, limitPrice: null },
+        })
+        .accountsPartial({
+          config: configPda,
+          market: marketPda,
+          feeRecipient: feeRecipientKeypair.publicKey,
+          tokenVault: tokenVaultAta,
+          wsolRecipient: wsolPayer,
+          tokenPayer: tokenRecipient,
+          payer: payer.publicKey,
+          nativeVault: nativeVaultPda,
+          tokenMint: mintKeypair.publicKey,
+          wsolMint: NATIVE_MINT,
+          wsolTokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .instruction();
+      const sellTx = new anchor.web3.Transaction().add(sellIx);
+      sellTx.feePayer = wallet.publicKey;
+      await sendAndConfirmTransaction(sellTx, wallet, payer);
+
+      // The received lamports are synced into the wrapped SOL amount.
+      wsolPayerAccount = await getAccount(anchor.getProvider().connection, wsolPayer);
+      expect(wsolPayerAccount.amount).to.eq(wrapAmount - total + sellTotal);
+      expect(await anchor.getProvider().connection.getBalance(payer.publicKey)).to.eq(payerBalanceBefore);
+    });
+  });
+
+  describe("#quote_market", () => {
+    it("should buy and sell with the quote token", async () => {
+      const { configPda, feeRecipientKeypair } = await initializeConfig();
+      const quoteMint = await createMint(anchor.getProvider().connection, wallet, wallet.publicKey, null, 9);
+      const { mintKeypair, marketPda, tokenVaultAta, nativeVaultPda } = await initializeQuoteMarket(
+        configPda,
+        quoteMint
+      );
+
+      const market = await program.account.market.fetch(marketPda);
+      expect(market.quoteMint.toBase58()).to.eq(quoteMint.toBase58());
+      expect(market.quoteDecimals).to.eq(9);
+      expect(market.nativeVault.toBase58()).to.eq(nativeVaultPda.toBase58());
+
+      const payer = anchor.web3.Keypair.generate();
+      let tx = await anchor.getProvider().connection.requestAirdrop(payer.publicKey, 1e9);
+      await confirmTransaction(tx);
+      const quoteTokenPayer = await getOrCreateAssociatedTokenAccount(
+        anchor.getProvider().connection,
+        wallet,
+        quoteMint,
+        payer.publicKey
+      );
+      await mintTo(anchor.getProvider().connection, wallet, quoteMint, quoteTokenPayer.address, wallet, 100e9);
+      const feeRecipientQuoteAccount = await getOrCreateAssociatedTokenAccount(
+        anchor.getProvider().connection,
+        wallet,
+        quoteMint,
+        feeRecipientKeypair.publicKey
+      );
+      const tokenRecipient = getAssociatedTokenAddressSync(
+        mintKeypair.publicKey,
+        payer.publicKey,
+        undefined,
+        TOKEN_2022_PROGRAM_ID
+      );
+
+      const buyAmount = BigInt(1e9);
+      const { y, fee, total } = compute_swap_with_fee(buyAmount, MAX_TOKEN_SUPPLY, true);
+      const buyIx = await program.methods
+        .buyToken({
+          buyAmount: new anchor.BN(buyAmount.toString()),
+          maxPay: new anchor.BN(total.toString()),
+          guard: { deadline: null, limitPrice: null },
+        })
+        .accountsPartial({
+          config: configPda,
+          market: marketPda,
+          feeRecipient: feeRecipientKeypair.publicKey,
+          tokenVault: tokenVaultAta,
+          tokenRecipient: tokenRecipient,
+          payer: payer.publicKey,
+          nativeVault: nativeVaultPda,
+          tokenMint: mintKeypair.publicKey,
+          quoteMint: quoteMint,
+          quoteTokenPayer: quoteTokenPayer.address,
+          feeRecipientQuoteAccount: feeRecipientQuoteAccount.address,
+          quoteTokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .instruction();
+      const buyTx = new anchor.web3.Transaction().add(
+        createAssociatedTokenAccountInstruction(
+          wallet.publicKey,
+          tokenRecipient,
+          payer.publicKey,
+          mintKeypair.publicKey,
+          TOKEN_2022_PROGRAM_ID
+        ),
+        buyIx
+      );
+      buyTx.feePayer = wallet.publicKey;
+      await sendAndConfirmTransaction(buyTx, wallet, payer);
+
+      let nativeVault = await getAccount(anchor.getProvider().connection, nativeVaultPda);
+      expect(nativeVault.amount).to.eq(y);
+      let feeRecipientQuote = await getAccount(anchor.getProvider().connection, feeRecipientQuoteAccount.address);
+      expect(feeRecipientQuote.amount).to.eq(fee);
+      let payerQuote = await getAccount(anchor.getProvider().connection, quoteTokenPayer.address);
+      expect(payerQuote.amount).to.eq(BigInt(100e9) - total);
+      const tokenRecipientAccount = await getAccount(
+        anchor.getProvider().connection,
+        tokenRecipient,
+        undefined,
+        TOKEN_2022_PROGRAM_ID
+      );
+      expect(tokenRecipientAccount.amount).to.eq(buyAmount);
+      // The payer paid with the quote token only.
+      expect(await anchor.getProvider().connection.getBalance(payer.publicKey)).to.eq(1e9);
+
+      const { y: sellY, fee: sellFee, total: sellTotal } = compute_swap_with_fee(
+        buyAmount,
+        MAX_TOKEN_SUPPLY - buyAmount,
+        false
+      );
+      const sellIx = await program.methods
+        .sellToken({
+          sellAmount: new anchor.BN(buyAmount.toString()),
+          minReceive: new anchor.BN(sellTotal.toString()),
+          guard: { deadline: null, limitPrice: null },
+        })
+        .accountsPartial({
+          config: configPda,
+          market: marketPda,
+          feeRecipient: feeRecipientKeypair.publicKey,
+          tokenVault: tokenVaultAta,
+          nativeRecipient: quoteTokenPayer.address,
+          tokenPayer: tokenRecipient,
+          payer: payer.publicKey,
+          nativeVault: nativeVaultPda,
+          tokenMint: mintKeypair.publicKey,
+          quoteMint: quoteMint,
+          feeRecipientQuoteAccount: feeRecipientQuoteAccount.address,
+          quoteTokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .instruction();
+      const sellTx = new anchor.web3.Transaction().add(sellIx);
+      sellTx.feePayer = wallet.publicKey;
+      await sendAndConfirmTransaction(sellTx, wallet, payer);
+
+      nativeVault = await getAccount(anchor.getProvider().connection, nativeVaultPda);
+      expect(nativeVault.amount).to.eq(y - sellY);
+      feeRecipientQuote = await getAccount(anchor.getProvider().connection, feeRecipientQuoteAccount.address);
+      expect(feeRecipientQuote.amount).to.eq(fee + sellFee);
+      payerQuote = await getAccount(anchor.getProvider().connection, quoteTokenPayer.address);
+      expect(payerQuote.amount).to.eq(BigInt(100e9) - total + sellTotal);
+
+      const { remainingSupply } = await program.account.market.fetch(marketPda);
+      expect(remainingSupply.toString()).to.eq(MAX_TOKEN_SUPPLY.toString());
+    });
+
+    it("should reject a quote mint with a transfer fee", async () => {
+      const { configPda } = await initializeConfig();
+      const quoteMintKeypair = anchor.web3.Keypair.generate();
+      const mintLen = getMintLen([ExtensionType.TransferFeeConfig]);
+      const mintTx = new anchor.web3.Transaction().add(
+        anchor.web3.SystemProgram.createAccount({
+          fromPubkey: wallet.publicKey,
+          newAccountPubkey: quoteMintKeypair.publicKey,
+          space: mintLen,
+          lamports: await anchor.getProvider().connection.getMinimumBalanceForRentExemption(mintLen),
+          programId: TOKEN_2022_PROGRAM_ID,
+        }),
+        createInitializeTransferFeeConfigInstruction(
+          quoteMintKeypair.publicKey,
+          wallet.publicKey,
+          wallet.publicKey,
+          100,
+          BigInt(1e9),
+          TOKEN_2022_PROGRAM_ID
+        ),
+        createInitializeMintInstruction(quoteMintKeypair.publicKey, 9, wallet.publicKey, null, TOKEN_2022_PROGRAM_ID)
+      );
+      mintTx.feePayer = wallet.publicKey;
+      await sendAndConfirmTransaction(mintTx, wallet, quoteMintKeypair);
+
+      try {
+        await initializeQuoteMarket(configPda, quoteMintKeypair.publicKey, undefined, TOKEN_2022_PROGRAM_ID);
+        expect.fail("should have failed");
+      } catch (e) {
+        expect(e.message.includes("UnsupportedQuoteMint")).to.be.true;
+      }
+    });
+  });
+
+  async function initializeConfig() {
+    // check if config already initialized
+    if (config !== null) {
+      return config;
+    }
+    const [configPda] = anchor.web3.PublicKey.findProgramAddressSync([Buffer.from("config")], program.programId);
+    const authorityKeypair = anchor.web3.Keypair.generate();
+    const feeRecipientKeypair = anchor.web3.Keypair.generate();
+    const buyBurnAuthorityKeypair = anchor.web3.Keypair.generate();
+    await program.methods
+      .initializeConfig({
+        authority: authorityKeypair.publicKey,
+        feeRecipient: feeRecipientKeypair.publicKey,
+        buyBurnAuthority: buyBurnAuthorityKeypair.publicKey,
+      })
+      .accountsPartial({
+        config: configPda,
+      })
+      .rpc();
+
+    return {
+      configPda,
+      authorityKeypair,
+      feeRecipientKeypair,
+      buyBurnAuthorityKeypair,
+    };
+  }
+
+  async function initializeMarket(
+    configPublickey: anchor.web3.PublicKey,
+    name: string = "Token name",
+    symbol: string = "TS" + nextSymbolIndex++,
+    uri: string = "https://example.org",
+    transferHookEnabled: boolean = false
+  ) {
+    const mintKeypair = anchor.web3.Keypair.generate();
+    const [marketPda, marketBump] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("market"), Buffer.from(symbol), configPublickey.toBuffer()],
+      program.programId
+    );
+    const [nativeVaultPda, nativeVaultBump] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("market_vault"), Buffer.from(symbol), configPublickey.toBuffer()],
+      program.programId
+    );
+    const [extraAccountMetaListPda] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("extra-account-metas"), mintKeypair.publicKey.toBuffer()],
+      hooksProgram.programId
+    );
+    const initializeAccountMetaListIx = await hooksProgram.methods
+      .initializeAccountMetaList(symbol)
+      .accountsPartial({
+        tokenMint: mintKeypair.publicKey,
+        extraAccountMetaList: extraAccountMetaListPda,
+      })
+      .instruction();
+
+    const tokenVaultAta = getAssociatedTokenAddressSync(mintKeypair.publicKey, marketPda, true, TOKEN_2022_PROGRAM_ID);
+    const args = {
+      name: name,
+      symbol: symbol,
+      uri: uri,
+      initialBuy: null,
+    };
+
+    let initializeMarketIx: anchor.web3.TransactionInstruction;
+    if (transferHookEnabled) {
+      initializeMarketIx = await program.methods
+        .initializeTransferHookMarket(args)
+        .accountsPartial({
+          config: configPublickey,
+          tokenMint: mintKeypair.publicKey,
+          tokenVault: tokenVaultAta,
+          market: marketPda,
+          nativeVault: nativeVaultPda,
+        })
+        .instruction();
+    } else {
+      initializeMarketIx = await program.methods
+        .initializeMarket(args)
+        .accountsPartial({
+          config: configPublickey,
+          tokenMint: mintKeypair.publicKey,
+          tokenVault: tokenVaultAta,
+          market: marketPda,
+          nativeVault: nativeVaultPda,
+        })
+        .instruction();
+    }
+    const tx = new anchor.web3.Transaction().add(initializeMarketIx);
+    if (transferHookEnabled) {
+      tx.add(initializeAccountMetaListIx);
+    }
+    await sendAndConfirmTransaction(tx, wallet, mintKeypair);
+    return {
+      mintKeypair,
+      marketPda,
+      marketBump,
+      tokenVaultAta,
+      nativeVaultPda,
+      nativeVaultBump,
+      extraAccountMetaListPda,
+    };
+  }
+
+  async function initializeQuoteMarket(
+    configPublickey: anchor.web3.PublicKey,
+    quoteMint: anchor.web3.PublicKey,
+    symbol: string = "TS" + nextSymbolIndex++,
+    quoteTokenProgram: anchor.web3.PublicKey = TOKEN_PROGRAM_ID
+  ) {
+    const mintKeypair = anchor.web3.Keypair.generate();
+    const [marketPda, marketBump] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("market"), Buffer.from(symbol), configPublickey.toBuffer()],
+      program.programId
+    );
+    const nativeVaultPda = getAssociatedTokenAddressSync(quoteMint, marketPda, true, quoteTokenProgram);
+    const tokenVaultAta = getAssociatedTokenAddressSync(mintKeypair.publicKey, marketPda, true, TOKEN_2022_PROGRAM_ID);
+    await program.methods
+      .initializeMarket({
+        name: "Token name",
+        symbol: symbol,
+        uri: "https://example.org",
+        initialBuy: null,
+      })
+      .accountsPartial({
+        config: configPublickey,
+        tokenMint: mintKeypair.publicKey,
+        tokenVault: tokenVaultAta,
+        market: marketPda,
+        nativeVault: nativeVaultPda,
+        quoteMint: quoteMint,
+        quoteTokenProgram: quoteTokenProgram,
+      })
+      .signers([mintKeypair])
+      .rpc();
+    return {
+      mintKeypair,
+      marketPda,
+      marketBump,
+      tokenVaultAta,
+      nativeVaultPda,
+    };
+  }
+
+  /** The message of a `BuyBurnVoucher`, signed by the buy-burn authority. */
+  function buyBurnVoucherMessage(
+    burnAccount: anchor.web3.PublicKey,
+    market: anchor.web3.PublicKey,
+    nextNonce: number,
+    nextBuyAmount: bigint,
+    expiry: number,
+    domain: string = "burn:buy_burn_voucher"
+  ) {
+    const fields = Buffer.alloc(4 + 8 + 8);
+    fields.writeUInt32LE(nextNonce, 0);
+    fields.writeBigUInt64LE(nextBuyAmount, 4);
+    fields.writeBigInt64LE(BigInt(expiry), 12);
+    return Buffer.concat([Buffer.from(domain), burnAccount.toBuffer(), market.toBuffer(), fields]);
+  }
+
+  /** The message of a `BatchBuyBurnVoucher`, signed once by the buy-burn authority for the whole batch. */
+  function batchBuyBurnVoucherMessage(
+    market: anchor.web3.PublicKey,
+    keeper: anchor.web3.PublicKey,
+    tokenRecipient: anchor.web3.PublicKey,
+    expiry: number,
+    entries: { owner: anchor.web3.PublicKey; nextNonce: number; nextBuyAmount: bigint }[]
+  ) {
+    const header = Buffer.alloc(8 + 4);
+    header.writeBigInt64LE(BigInt(expiry), 0);
+    header.writeUInt32LE(entries.length, 8);
+    const fields = entries.map((entry) => {
+      const buffer = Buffer.alloc(4 + 8);
+      buffer.writeUInt32LE(entry.nextNonce, 0);
+      buffer.writeBigUInt64LE(entry.nextBuyAmount, 4);
+      return Buffer.concat([entry.owner.toBuffer(), buffer]);
+    });
+    return Buffer.concat([
+      Buffer.from("burn:batch_buy_burn_voucher"),
+      market.toBuffer(),
+      keeper.toBuffer(),
+      tokenRecipient.toBuffer(),
+      header,
+      ...fields,
+    ]);
+  }
+
+  /** An Ed25519 program instruction with one signature of `signer` per message, all data in the instruction. */
+  function ed25519Instruction(signer: anchor.web3.Keypair, messages: Buffer[]) {
+    const offsetsLen = 14;
+    const headerLen = 2 + messages.length * offsetsLen;
+    const header = Buffer.alloc(headerLen);
+    header.writeUInt8(messages.length, 0);
+    const body: Buffer[] = [];
+    let bodyLen = 0;
+    messages.forEach((message, i) => {
+      // the signature is taken from the single signature instruction of the same message
+      const single = anchor.web3.Ed25519Program.createInstructionWithPrivateKey({
+        privateKey: signer.secretKey,
+        message,
+      });
+      const signature = single.data.subarray(16 + 32, 16 + 32 + 64);
+      const publicKeyOffset = headerLen + bodyLen;
+      const signatureOffset = publicKeyOffset + 32;
+      const messageOffset = signatureOffset + 64;
+      body.push(signer.publicKey.toBuffer(), Buffer.from(signature), message);
+      bodyLen += 32 + 64 + message.length;
+      const offsets = [signatureOffset, 0xffff, publicKeyOffset, 0xffff, messageOffset, message.length, 0xffff];
+      offsets.forEach((value, j) => header.writeUInt16LE(value, 2 + i * offsetsLen + j * 2));
+    });
+    return new anchor.web3.TransactionInstruction({
+      keys: [],
+      programId: anchor.web3.Ed25519Program.programId,
+      data: Buffer.concat([header, ...body]),
+    });
+  }
+
+  async function enableBuyBurn(marketPda: anchor.web3.PublicKey) {
+    await program.methods
+      .setMarketBuyBurnEnabled(true)
+      .accountsPartial({
+        config: config.configPda,
+        market: marketPda,
+        authority: config.authorityKeypair.publicKey,
+      })
+      .signers([config.authorityKeypair])
+      .rpc();
+  }
+
+  function hookAccounts(extraAccountMetaListPda: anchor.web3.PublicKey) {
+    return [
+      { pubkey: extraAccountMetaListPda, isSigner: false, isWritable: false },
+      { pubkey: hooksProgram.programId, isSigner: false, isWritable: false },
+      { pubkey: program.programId, isSigner: false, isWritable: false },
+    ];
   }
 
   async function sendAndConfirmTransaction(tx: anchor.web3.Transaction, ...signers: Array<anchor.web3.Keypair>) {
@@ -5,3 +5,6 @@ export const MAX_TOKEN_SUPPLY = BigInt(10e8) * BigInt(1e6);
 export const BLACK_HOLE = new anchor.web3.PublicKey(
   "1nc1nerator11111111111111111111111111111111"
 );
+export const TOKEN_METADATA_PROGRAM_ID = new anchor.web3.PublicKey(
+  "metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s"
+);
@@ -2,6 +2,14 @@ import * as anchor from "@coral-xyz/anchor";
 import { Program } from "@coral-xyz/anchor";
 import { FeeDistributor } from "../target/types/fee_distributor";
 import { expect } from "chai";
+import {
+  createMint,
+  getAccount,
+  getOrCreateAssociatedTokenAccount,
+  mintTo,
+  TOKEN_PROGRAM_ID,
+} from "@solana/spl-token";
+import { keccak_256 } from "@noble/hashes/sha3";
 import { confirmTransaction } from "./burn";
 
 describe("fee-distributor", () => {
@@ -51,12 +59,17 @@ describe("fee-distributor", () => {
       await initializeVault();
       const owner = anchor.web3.Keypair.generate();
       const [ownerClaimPda] = anchor.web3.PublicKey.findProgramAddressSync(
-        [Buffer.from("owner"), owner.publicKey.toBuffer()],
+        [
+          Buffer.from("claim"),
+          vaultPda.toBuffer(),
+          owner.publicKey.toBuffer(),
+        ],
         program.programId
       );
       await program.methods
         .createAccount()
         .accountsPartial({
+          vault: vaultPda,
           owner: owner.publicKey,
           claim: ownerClaimPda,
         })
@@ -72,12 +85,17 @@ describe("fee-distributor", () => {
       await initializeVault();
       const owner = anchor.web3.Keypair.generate();
       const [ownerClaimPda] = anchor.web3.PublicKey.findProgramAddressSync(
-        [Buffer.from("owner"), owner.publicKey.toBuffer()],
+        [
+          Buffer.from("claim"),
+          vaultPda.toBuffer(),
+          owner.publicKey.toBuffer(),
+        ],
         program.programId
       );
       await program.methods
         .createAccount()
         .accountsPartial({
+          vault: vaultPda,
           owner: owner.publicKey,
           claim: ownerClaimPda,
         })
@@ -87,6 +105,7 @@ describe("fee-distributor", () => {
         await program.methods
           .createAccount()
           .accountsPartial({
+            vault: vaultPda,
             owner: owner.publicKey,
             claim: ownerClaimPda,
           })
@@ -126,7 +145,6 @@ describe("fee-distributor", () => {
     });
 
     it("should failed if authority mismatch", async () => {
-      await initializeVault();
       const { owner, ownerClaimPda } = await createAccount();
       const fakeAuthority = anchor.web3.Keypair.generate();
       try {
@@ -153,7 +171,6 @@ describe("fee-distributor", () => {
     });
 
     it("should failed if claim account incorrect", async () => {
-      await initializeVault();
       const { owner, ownerClaimPda } = await createAccount();
       const fakeOwner = anchor.web3.Keypair.generate();
       try {
@@ -181,34 +198,7 @@ describe("fee-distributor", () => {
       }
     });
 
-    it("should failed if next nonce incorrect", async () => {
-      await initializeVault();
-      const { owner, ownerClaimPda } = await createAccount();
-      try {
-        await program.methods
-          .updateClaim({
-            nextNonce: 2,
-            nextClaimed: new anchor.BN(1),
-          })
-          .accountsPartial({
-            vault: vaultPda,
-            claim: ownerClaimPda,
-            authority: authority.publicKey,
-            owner: owner.publicKey,
-            recipient: owner.publicKey,
-          })
-          .signers([wallet, owner, authority])
-          .rpc();
-        expect.fail("should have failed");
-      } catch (e) {
-        expect(e instanceof anchor.AnchorError).to.be.true;
-        const err = e as anchor.AnchorError;
-        expect(err.message).to.be.include("Error Code: NonceUnexpected");
-      }
-    });
-
     it("should failed if vault insufficient", async () => {
-      await initializeVault();
       const { owner, ownerClaimPda } = await createAccount();
       try {
         await program.methods
@@ -227,14 +217,15 @@ describe("fee-distributor", () => {
           .rpc();
         expect.fail("should have failed");
       } catch (e) {
-        expect(e instanceof anchor.web3.SendTransactionError);
-        const err = e as anchor.web3.SendTransactionError;
-        expect(err.message).to.be.include("with insufficient funds for rent");
+        expect(e instanceof anchor.AnchorError).to.be.true;
+        const err = e as anchor.AnchorError;
+        expect(err.message).to.be.include(
+          "Error Code: InsufficientVaultBalance"
+        );
       }
     });
 
     it("should failed if next claimed incorrect", async () => {
-      await initializeVault();
       const { owner, ownerClaimPda } = await createAccount();
       const tx = await anchor
         .getProvider()
@@ -283,7 +274,6 @@ describe("fee-distributor", () => {
     });
 
     it("should succeed", async () => {
-      await initializeVault();
       const { owner, ownerClaimPda } = await createAccount();
       const tx = await anchor
         .getProvider()
@@ -344,8 +334,958 @@ describe("fee-distributor", () => {
       expect(nextNonce).to.eq(2);
       expect(nextClaimed.toNumber()).to.eq(2e9);
     });
+
+    it("should reject a stale claim landing after a newer one", async () => {
+      const { owner, ownerClaimPda } = await createAccount();
+      const tx = await anchor
+        .getProvider()
+        .connection.requestAirdrop(vaultPda, anchor.web3.LAMPORTS_PER_SOL * 10);
+      await confirmTransaction(tx);
+
+      const recipient = anchor.web3.Keypair.generate();
+      await program.methods
+        .updateClaim({
+          nextNonce: 2,
+          nextClaimed: new anchor.BN(2e9),
+        })
+        .accountsPartial({
+          vault: vaultPda,
+          claim: ownerClaimPda,
+          authority: authority.publicKey,
+          owner: owner.publicKey,
+          recipient: recipient.publicKey,
+        })
+        .signers([wallet, owner, authority])
+        .rpc();
+
+      try {
+        await program.methods
+          .updateClaim({
+            nextNonce: 1,
+            nextClaimed: new anchor.BN(1e9),
+          })
+          .accountsPartial({
+            vault: vaultPda,
+            claim: ownerClaimPda,
+            authority: authority.publicKey,
+            owner: owner.publicKey,
+            recipient: recipient.publicKey,
+          })
+          .signers([wallet, owner, authority])
+          .rpc();
+        expect.fail("should have failed");
+      } catch (e) {
+        expect(e instanceof anchor.AnchorError).to.be.true;
+        const err = e as anchor.AnchorError;
+        expect(err.message).to.be.include(
+          "Error Code: ClaimedAmountUnexpected"
+        );
+      }
+      expect(
+        await program.provider.connection.getBalance(recipient.publicKey)
+      ).to.eq(2e9);
+      const { nonce, claimed } = await program.account.claim.fetch(
+        ownerClaimPda
+      );
+      expect(nonce).to.eq(2);
+      expect(claimed.toNumber()).to.eq(2e9);
+    });
+  });
+
+  describe("#update_claim_as_delegate", () => {
+    it("should pay the recipient approved by the owner", async () => {
+      const { owner, ownerClaimPda } = await createAccount();
+      const connection = program.provider.connection;
+      let tx = await connection.requestAirdrop(
+        vaultPda,
+        anchor.web3.LAMPORTS_PER_SOL * 10
+      );
+      await confirmTransaction(tx);
+      tx = await connection.requestAirdrop(
+        owner.publicKey,
+        anchor.web3.LAMPORTS_PER_SOL
+      );
+      await confirmTransaction(tx);
+
+      const delegate = anchor.web3.Keypair.generate();
+      const recipient = anchor.web3.Keypair.generate();
+      const setClaimDelegate = (
+        delegate: anchor.web3.PublicKey | null,
+        recipient: anchor.web3.PublicKey | null
+      ) =>
+        program.methods
+          .setClaimDelegate({ delegate, recipient })
+          .accountsPartial({
+            vault: vaultPda,
+            claim: ownerClaimPda,
+            owner: owner.publicKey,
+          })
+          .signers([owner])
+          .rpc();
+      const updateClaimAsDelegate = (
+        signer: anchor.web3.Keypair,
+        recipient: anchor.web3.PublicKey
+      ) =>
+        program.methods
+          .updateClaimAsDelegate({
+            nextNonce: 1,
+            nextClaimed: new anchor.BN(1e9),
+          })
+          .accountsPartial({
+            vault: vaultPda,
+            claim: ownerClaimPda,
+            authority: authority.publicKey,
+            owner: owner.publicKey,
+            delegate: signer.publicKey,
+            recipient,
+          })
+          .signers([authority, signer])
+          .rpc();
+      const expectError = async (promise: Promise<unknown>, code: string) => {
+        try {
+          await promise;
+          expect.fail("should have failed");
+        } catch (e) {
+          expect(e instanceof anchor.AnchorError).to.be.true;
+          const err = e as anchor.AnchorError;
+          expect(err.message).to.be.include(`Error Code: ${code}`);
+        }
+      };
+
+      await setClaimDelegate(delegate.publicKey, recipient.publicKey);
+      const claim = await program.account.claim.fetch(ownerClaimPda);
+      expect(claim.delegate.toBase58()).to.eq(delegate.publicKey.toBase58());
+      expect(claim.recipient.toBase58()).to.eq(recipient.publicKey.toBase58());
+
+      await expectError(
+        updateClaimAsDelegate(
+          anchor.web3.Keypair.generate(),
+          recipient.publicKey
+        ),
+        "InvalidDelegate"
+      );
+      await expectError(
+        updateClaimAsDelegate(delegate, delegate.publicKey),
+        "RecipientNotApproved"
+      );
+      await updateClaimAsDelegate(delegate, recipient.publicKey);
+      expect(await connection.getBalance(recipient.publicKey)).to.eq(1e9);
+      const { nonce, claimed } = await program.account.claim.fetch(
+        ownerClaimPda
+      );
+      expect(nonce).to.eq(1);
+      expect(claimed.toNumber()).to.eq(1e9);
+
+      // the owner removes the delegate
+      await setClaimDelegate(null, null);
+      await expectError(
+        updateClaimAsDelegate(delegate, owner.publicKey),
+        "InvalidDelegate"
+      );
+    });
+  });
+
+  describe("#set_claim_vesting", () => {
+    it("should release the vested amount and revoke the rest", async () => {
+      const { owner, ownerClaimPda } = await createAccount();
+      const connection = program.provider.connection;
+      let tx = await connection.requestAirdrop(
+        vaultPda,
+        anchor.web3.LAMPORTS_PER_SOL * 10
+      );
+      await confirmTransaction(tx);
+      // the authority pays for the reallocation of the claim
+      tx = await connection.requestAirdrop(
+        authority.publicKey,
+        anchor.web3.LAMPORTS_PER_SOL
+      );
+      await confirmTransaction(tx);
+
+      const setClaimVesting = (startTime: number, cliffTime: number) =>
+        program.methods
+          .setClaimVesting({
+            vesting: {
+              startTime: new anchor.BN(startTime),
+              cliffTime: new anchor.BN(cliffTime),
+              duration: new anchor.BN(1000),
+            },
+          })
+          .accountsPartial({
+            vault: vaultPda,
+            claim: ownerClaimPda,
+            owner: owner.publicKey,
+            authority: authority.publicKey,
+          })
+          .signers([authority])
+          .rpc();
+      const updateClaim = (nextNonce: number, nextClaimed: number) =>
+        program.methods
+          .updateClaim({
+            nextNonce,
+            nextClaimed: new anchor.BN(nextClaimed),
+          })
+          .accountsPartial({
+            vault: vaultPda,
+            claim: ownerClaimPda,
+            authority: authority.publicKey,
+            owner: owner.publicKey,
+            recipient: owner.publicKey,
+          })
+          .signers([wallet, owner, authority])
+          .rpc();
+      const recipient = anchor.web3.Keypair.generate();
+      const releaseClaim = () =>
+        program.methods
+          .releaseClaim()
+          .accountsPartial({
+            vault: vaultPda,
+            claim: ownerClaimPda,
+            owner: owner.publicKey,
+            recipient: recipient.publicKey,
+          })
+          .signers([owner])
+          .rpc();
+      const now = await connection.getBlockTime(await connection.getSlot());
+
+      // the cliff can not be after the end
+      try {
+        await setClaimVesting(now, now + 1001);
+        expect.fail("should have failed");
+      } catch (e) {
+        expect(e instanceof anchor.AnchorError).to.be.true;
+        const err = e as anchor.AnchorError;
+        expect(err.message).to.be.include(
+          "Error Code: InvalidVestingSchedule"
+        );
+      }
+
+      // nothing is paid before the cliff
+      await setClaimVesting(now, now + 500);
+      await updateClaim(1, 1e9);
+      await releaseClaim();
+      expect(await connection.getBalance(owner.publicKey)).to.eq(0);
+      expect(await connection.getBalance(recipient.publicKey)).to.eq(0);
+      let claim = await program.account.claim.fetch(ownerClaimPda);
+      expect(claim.unreleased.toNumber()).to.eq(1e9);
+
+      // the owner releases the vested amount without the authority
+      await setClaimVesting(now - 1000, now - 1000);
+      await releaseClaim();
+      expect(await connection.getBalance(recipient.publicKey)).to.eq(1e9);
+      claim = await program.account.claim.fetch(ownerClaimPda);
+      expect(claim.unreleased.toNumber()).to.eq(0);
+
+      // the authority revokes what is not vested yet
+      await setClaimVesting(now, now + 500);
+      await updateClaim(2, 2e9);
+      await program.methods
+        .revokeClaim()
+        .accountsPartial({
+          vault: vaultPda,
+          claim: ownerClaimPda,
+          owner: owner.publicKey,
+          authority: authority.publicKey,
+        })
+        .signers([authority])
+        .rpc();
+      claim = await program.account.claim.fetch(ownerClaimPda);
+      expect(claim.vesting).to.be.null;
+      expect(claim.unreleased.toNumber()).to.eq(0);
+      expect(claim.revoked.toNumber()).to.eq(1e9);
+
+      // and the amounts approved after are paid at once
+      await updateClaim(3, 3e9);
+      expect(await connection.getBalance(owner.publicKey)).to.eq(1e9);
+    });
+  });
+
+  describe("#update_token_claim", () => {
+    it("should pay the reward token from the vault token account", async () => {
+      await initializeVault();
+      const connection = program.provider.connection;
+      const mint = await createMint(
+        connection,
+        wallet,
+        wallet.publicKey,
+        null,
+        6
+      );
+      const [vaultTokenAccount] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("reward_tokens"), vaultPda.toBuffer(), mint.toBuffer()],
+        program.programId
+      );
+      await program.methods
+        .createRewardVault()
+        .accountsPartial({
+          vault: vaultPda,
+          mint,
+          vaultTokenAccount,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .rpc();
+      await mintTo(connection, wallet, mint, vaultTokenAccount, wallet, 1000e6);
+
+      const owner = anchor.web3.Keypair.generate();
+      const [tokenClaimPda] = anchor.web3.PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("token_claim"),
+          vaultPda.toBuffer(),
+          owner.publicKey.toBuffer(),
+          mint.toBuffer(),
+        ],
+        program.programId
+      );
+      await program.methods
+        .createTokenClaim()
+        .accountsPartial({
+          vault: vaultPda,
+          tokenClaim: tokenClaimPda,
+          owner: owner.publicKey,
+          mint,
+        })
+        .rpc();
+      const recipient = await getOrCreateAssociatedTokenAccount(
+        connection,
+        wallet,
+        mint,
+        owner.publicKey
+      );
+
+      const updateTokenClaim = (nextNonce: number, nextClaimed: number) =>
+        program.methods
+          .updateTokenClaim({
+            nextNonce,
+            nextClaimed: new anchor.BN(nextClaimed),
+          })
+          .accountsPartial({
+            vault: vaultPda,
+            tokenClaim: tokenClaimPda,
+            mint,
+            vaultTokenAccount,
+            recipient: recipient.address,
+            authority: authority.publicKey,
+            owner: owner.publicKey,
+            tokenProgram: TOKEN_PROGRAM_ID,
+          })
+          .signers([owner, authority])
+          .rpc();
+      await updateTokenClaim(1, 100e6);
+      await updateTokenClaim(2, 250e6);
+
+      expect((await getAccount(connection, recipient.address)).amount).to.eq(
+        BigInt(250e6)
+      );
+      expect((await getAccount(connection, vaultTokenAccount)).amount).to.eq(
+        BigInt(750e6)
+      );
+      const tokenClaim = await program.account.tokenClaim.fetch(tokenClaimPda);
+      expect(tokenClaim.mint.toBase58()).to.eq(mint.toBase58());
+      expect(tokenClaim.nonce).to.eq(2);
+      expect(tokenClaim.claimed.toNumber()).to.eq(250e6);
+
+      try {
+        await updateTokenClaim(2, 250e6);
+        expect.fail("should have failed");
+      } catch (e) {
+        expect(e instanceof anchor.AnchorError).to.be.true;
+        const err = e as anchor.AnchorError;
+        expect(err.message).to.be.include(
+          "Error Code: ClaimedAmountUnexpected"
+        );
+      }
+    });
+  });
+
+  describe("#claim_distribution", () => {
+    it("should pay the leaves once and claw back the rest after the expiry", async () => {
+      await initializeVault();
+      const connection = program.provider.connection;
+      let tx = await connection.requestAirdrop(
+        vaultPda,
+        anchor.web3.LAMPORTS_PER_SOL * 10
+      );
+      await confirmTransaction(tx);
+      tx = await connection.requestAirdrop(
+        authority.publicKey,
+        anchor.web3.LAMPORTS_PER_SOL
+      );
+      await confirmTransaction(tx);
+
+      const owners = [
+        anchor.web3.Keypair.generate().publicKey,
+        anchor.web3.Keypair.generate().publicKey,
+      ];
+      const amounts = [1e8, 2e8];
+      const leaves = owners.map((owner, i) =>
+        leafHash(i, owner, amounts[i])
+      );
+      const root = parentHash(leaves[0], leaves[1]);
+      const epoch = new anchor.BN(Date.now());
+      const [distributionPda] = anchor.web3.PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("distribution"),
+          vaultPda.toBuffer(),
+          epoch.toArrayLike(Buffer, "le", 8),
+        ],
+        program.programId
+      );
+      const expiry = Math.floor(Date.now() / 1000) + 10;
+      await program.methods
+        .createDistribution({
+          epoch,
+          merkleRoot: Array.from(root),
+          totalAmount: new anchor.BN(3e8),
+          numNodes: 2,
+          expiry: new anchor.BN(expiry),
+        })
+        .accountsPartial({
+          vault: vaultPda,
+          distribution: distributionPda,
+          authority: authority.publicKey,
+        })
+        .signers([authority])
+        .rpc();
+
+      const claimDistribution = (index: number, amount: number) =>
+        program.methods
+          .claimDistribution({
+            index,
+            amount: new anchor.BN(amount),
+            proof: [Array.from(leaves[1 - index])],
+          })
+          .accountsPartial({
+            distribution: distributionPda,
+            owner: owners[index],
+          })
+          .rpc();
+      const expectError = async (promise: Promise<unknown>, code: string) => {
+        try {
+          await promise;
+          expect.fail("should have failed");
+        } catch (e) {
+          expect(e instanceof anchor.AnchorError).to.be.true;
+          const err = e as anchor.AnchorError;
+          expect(err.message).to.be.include(`Error Code: ${code}`);
+        }
+      };
+
+      await expectError(claimDistribution(0, 2e8), "InvalidMerkleProof");
+      await claimDistribution(0, 1e8);
+      expect(await connection.getBalance(owners[0])).to.eq(1e8);
+      await expectError(claimDistribution(0, 1e8), "AlreadyClaimed");
+      const distribution = await program.account.distribution.fetch(
+        distributionPda
+      );
+      expect(distribution.claimedAmount.toNumber()).to.eq(1e8);
+
+      await expectError(
+        program.methods
+          .clawbackDistribution()
+          .accountsPartial({
+            vault: vaultPda,
+            distribution: distributionPda,
+            authority: authority.publicKey,
+          })
+          .signers([authority])
+          .rpc(),
+        "DistributionNotExpired"
+      );
+
+      await waitForClock(expiry);
+      await expectError(claimDistribution(1, 2e8), "DistributionExpired");
+
+      const vaultBefore = await program.account.vault.fetch(vaultPda);
+      const vaultBalanceBefore = await connection.getBalance(vaultPda);
+      const authorityBalanceBefore = await connection.getBalance(
+        authority.publicKey
+      );
+      const distributionBalance = await connection.getBalance(distributionPda);
+      await program.methods
+        .clawbackDistribution()
+        .accountsPartial({
+          vault: vaultPda,
+          distribution: distributionPda,
+          authority: authority.publicKey,
+        })
+        .signers([authority])
+        .rpc();
+      // the unclaimed amount goes back to the vault and is no longer claimed
+      expect(await connection.getBalance(vaultPda)).to.eq(
+        vaultBalanceBefore + 2e8
+      );
+      const vault = await program.account.vault.fetch(vaultPda);
+      expect(vault.totalClaimed.toNumber()).to.eq(
+        vaultBefore.totalClaimed.toNumber() - 2e8
+      );
+      // and the rent goes back to the authority
+      expect(await connection.getBalance(authority.publicKey)).to.eq(
+        authorityBalanceBefore + distributionBalance - 2e8
+      );
+      expect(await connection.getAccountInfo(distributionPda)).to.be.null;
+    });
+  });
+
+  describe("#claim_emission", () => {
+    it("should stream the emission pro-rata to the weights", async () => {
+      const connection = program.provider.connection;
+      const vaultAuthority = anchor.web3.Keypair.generate();
+      let tx = await connection.requestAirdrop(
+        vaultAuthority.publicKey,
+        anchor.web3.LAMPORTS_PER_SOL
+      );
+      await confirmTransaction(tx);
+      const vaultId = new anchor.BN(Date.now());
+      const [emissionVaultPda] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("vault"), vaultId.toArrayLike(Buffer, "le", 8)],
+        program.programId
+      );
+      await program.methods
+        .createVault({ id: vaultId, authority: vaultAuthority.publicKey })
+        .accountsPartial({ vault: emissionVaultPda })
+        .rpc();
+      tx = await connection.requestAirdrop(
+        emissionVaultPda,
+        anchor.web3.LAMPORTS_PER_SOL * 10
+      );
+      await confirmTransaction(tx);
+
+      const [emissionPda] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("emission"), emissionVaultPda.toBuffer()],
+        program.programId
+      );
+      // a multiple of the total weight, so that the shares are exact
+      const rate = 4e6;
+      const now = await connection.getBlockTime(await connection.getSlot());
+      await program.methods
+        .createEmission({
+          rate: new anchor.BN(rate),
+          startTime: new anchor.BN(now),
+          endTime: new anchor.BN(now + 1000),
+        })
+        .accountsPartial({
+          vault: emissionVaultPda,
+          emission: emissionPda,
+          stakePool: null,
+          authority: vaultAuthority.publicKey,
+        })
+        .signers([vaultAuthority])
+        .rpc();
+
+      const owners = [
+        anchor.web3.Keypair.generate(),
+        anchor.web3.Keypair.generate(),
+      ];
+      const sharePdas = owners.map(
+        (owner) =>
+          anchor.web3.PublicKey.findProgramAddressSync(
+            [
+              Buffer.from("emission_share"),
+              emissionPda.toBuffer(),
+              owner.publicKey.toBuffer(),
+            ],
+            program.programId
+          )[0]
+      );
+      const weightsTx = new anchor.web3.Transaction();
+      for (const [i, weight] of [1, 3].entries()) {
+        tx = await connection.requestAirdrop(
+          owners[i].publicKey,
+          anchor.web3.LAMPORTS_PER_SOL
+        );
+        await confirmTransaction(tx);
+        await program.methods
+          .createEmissionShare()
+          .accountsPartial({
+            emission: emissionPda,
+            share: sharePdas[i],
+            owner: owners[i].publicKey,
+          })
+          .rpc();
+        weightsTx.add(
+          await program.methods
+            .setEmissionWeight({ weight: new anchor.BN(weight) })
+            .accountsPartial({
+              vault: emissionVaultPda,
+              emission: emissionPda,
+              share: sharePdas[i],
+              authority: vaultAuthority.publicKey,
+            })
+            .instruction()
+        );
+      }
+      // both weights start at the same time
+      await program.provider.sendAndConfirm(weightsTx, [vaultAuthority]);
+
+      // end the emission soon, so that the emitted amount is final
+      const end =
+        (await connection.getBlockTime(await connection.getSlot())) + 3;
+      await program.methods
+        .setEmission({ rate: new anchor.BN(rate), endTime: new anchor.BN(end) })
+        .accountsPartial({
+          vault: emissionVaultPda,
+          emission: emissionPda,
+          authority: vaultAuthority.publicKey,
+        })
+        .signers([vaultAuthority])
+        .rpc();
+      await waitForClock(end);
+
+      // the owners claim without the authority
+      const claimEmission = async (i: number) => {
+        const balanceBefore = await connection.getBalance(owners[i].publicKey);
+        await program.methods
+          .claimEmission()
+          .accountsPartial({
+            emission: emissionPda,
+            vault: emissionVaultPda,
+            share: sharePdas[i],
+            owner: owners[i].publicKey,
+          })
+          .signers([owners[i]])
+          .rpc();
+        return (
+          (await connection.getBalance(owners[i].publicKey)) - balanceBefore
+        );
+      };
+      const claimed = [await claimEmission(0), await claimEmission(1)];
+      expect(claimed[0]).to.be.greaterThan(0);
+      expect(claimed[1]).to.eq(claimed[0] * 3);
+      expect((claimed[0] + claimed[1]) % rate).to.eq(0);
+      expect(claimed[0] + claimed[1]).to.be.at.most(rate * (end - now));
+      // nothing is emitted after the end
+      expect(await claimEmission(0)).to.eq(0);
+    });
+
+    it("should keep the stake-derived weights in sync with the stakes", async () => {
+      const connection = program.provider.connection;
+      const vaultAuthority = anchor.web3.Keypair.generate();
+      let tx = await connection.requestAirdrop(
+        vaultAuthority.publicKey,
+        anchor.web3.LAMPORTS_PER_SOL
+      );
+      await confirmTransaction(tx);
+      const createVault = async (id: anchor.BN) => {
+        const [pda] = anchor.web3.PublicKey.findProgramAddressSync(
+          [Buffer.from("vault"), id.toArrayLike(Buffer, "le", 8)],
+          program.programId
+        );
+        await program.methods
+          .createVault({ id, authority: vaultAuthority.publicKey })
+          .accountsPartial({ vault: pda })
+          .rpc();
+        return pda;
+      };
+      const poolVaultPda = await createVault(new anchor.BN(Date.now()));
+      const emissionVaultPda = await createVault(
+        new anchor.BN(Date.now() + 1)
+      );
+
+      const mint = await createMint(
+        connection,
+        wallet,
+        wallet.publicKey,
+        null,
+        6
+      );
+      const [poolPda] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("stake_pool"), poolVaultPda.toBuffer()],
+        program.programId
+      );
+      const [poolTokenAccount] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("stake_pool_tokens"), poolPda.toBuffer()],
+        program.programId
+      );
+      const [stakeAuthority] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("stake_authority")],
+        program.programId
+      );
+      const createStakePool = (vault: anchor.web3.PublicKey) => {
+        const [pool] = anchor.web3.PublicKey.findProgramAddressSync(
+          [Buffer.from("stake_pool"), vault.toBuffer()],
+          program.programId
+        );
+        const [poolTokens] = anchor.web3.PublicKey.findProgramAddressSync(
+          [Buffer.from("stake_pool_tokens"), pool.toBuffer()],
+          program.programId
+        );
+        return program.methods
+          .createStakePool()
+          .accountsPartial({
+            vault,
+            pool,
+            mint,
+            poolTokenAccount: poolTokens,
+            stakeAuthority,
+            authority: vaultAuthority.publicKey,
+            tokenProgram: TOKEN_PROGRAM_ID,
+          })
+          .signers([vaultAuthority])
+          .rpc();
+      };
+      await createStakePool(poolVaultPda);
+
+      const [emissionPda] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("emission"), emissionVaultPda.toBuffer()],
+        program.programId
+      );
+      const now = await connection.getBlockTime(await connection.getSlot());
+      const createEmission = (vault: anchor.web3.PublicKey) =>
+        program.methods
+          .createEmission({
+            rate: new anchor.BN(1e6),
+            startTime: new anchor.BN(now),
+            endTime: new anchor.BN(now + 1000),
+          })
+          .accountsPartial({
+            vault,
+            emission: anchor.web3.PublicKey.findProgramAddressSync(
+              [Buffer.from("emission"), vault.toBuffer()],
+              program.programId
+            )[0],
+            stakePool: poolPda,
+            authority: vaultAuthority.publicKey,
+          })
+          .signers([vaultAuthority])
+          .rpc();
+      // the lamports of the pool vault belong to the stakers
+      try {
+        await createEmission(poolVaultPda);
+        expect.fail("should have failed");
+      } catch (e) {
+        expect(e instanceof anchor.AnchorError).to.be.true;
+        const err = e as anchor.AnchorError;
+        expect(err.message).to.include("Error Code: VaultHasStakePool");
+      }
+      await createEmission(emissionVaultPda);
+      // and the emission vault cannot get a stake pool afterwards
+      try {
+        await createStakePool(emissionVaultPda);
+        expect.fail("should have failed");
+      } catch (e) {
+        expect(e instanceof anchor.AnchorError).to.be.true;
+        const err = e as anchor.AnchorError;
+        expect(err.message).to.include("Error Code: VaultHasEmission");
+      }
+
+      const owner = anchor.web3.Keypair.generate();
+      const ownerTokenAccount = await getOrCreateAssociatedTokenAccount(
+        connection,
+        wallet,
+        mint,
+        owner.publicKey
+      );
+      await mintTo(
+        connection,
+        wallet,
+        mint,
+        ownerTokenAccount.address,
+        wallet,
+        1000e6
+      );
+      const [stakePda] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("stake"), poolPda.toBuffer(), owner.publicKey.toBuffer()],
+        program.programId
+      );
+      const [sharePda] = anchor.web3.PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("emission_share"),
+          emissionPda.toBuffer(),
+          owner.publicKey.toBuffer(),
+        ],
+        program.programId
+      );
+      await program.methods
+        .createStakeAccount()
+        .accountsPartial({
+          pool: poolPda,
+          stake: stakePda,
+          owner: owner.publicKey,
+        })
+        .rpc();
+      await program.methods
+        .createEmissionShare()
+        .accountsPartial({
+          emission: emissionPda,
+          share: sharePda,
+          owner: owner.publicKey,
+        })
+        .rpc();
+
+      const stakeAccounts = {
+        pool: poolPda,
+        vault: poolVaultPda,
+        stake: stakePda,
+        owner: owner.publicKey,
+        mint,
+        ownerTokenAccount: ownerTokenAccount.address,
+        poolTokenAccount,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      };
+      const weight = async () =>
+        (await program.account.emissionShare.fetch(sharePda)).weight.toNumber();
+      await program.methods
+        .stake({ amount: new anchor.BN(1000e6) })
+        .accountsPartial({
+          ...stakeAccounts,
+          emission: emissionPda,
+          emissionShare: sharePda,
+        })
+        .signers([owner])
+        .rpc();
+      expect(await weight()).to.eq(1000e6);
+
+      // unstaking without the share would leave the weight behind
+      try {
+        await program.methods
+          .unstake({ amount: new anchor.BN(1000e6) })
+          .accountsPartial({
+            ...stakeAccounts,
+            stakeAuthority,
+            emission: null,
+            emissionShare: null,
+          })
+          .signers([owner])
+          .rpc();
+        expect.fail("should have failed");
+      } catch (e) {
+        expect(e instanceof anchor.AnchorError).to.be.true;
+        const err = e as anchor.AnchorError;
+        expect(err.message).to.include("Error Code: MissingEmissionShare");
+      }
+      await program.methods
+        .unstake({ amount: new anchor.BN(1000e6) })
+        .accountsPartial({
+          ...stakeAccounts,
+          stakeAuthority,
+          emission: emissionPda,
+          emissionShare: sharePda,
+        })
+        .signers([owner])
+        .rpc();
+      expect(await weight()).to.eq(0);
+    });
+  });
+
+  describe("#close_claim", () => {
+    it("should keep the last state when closed and resume from it when reopened", async () => {
+      const { owner, ownerClaimPda } = await createAccount();
+      const tx = await anchor
+        .getProvider()
+        .connection.requestAirdrop(vaultPda, anchor.web3.LAMPORTS_PER_SOL * 10);
+      await confirmTransaction(tx);
+
+      const updateClaim = () =>
+        program.methods
+          .updateClaim({
+            nextNonce: 1,
+            nextClaimed: new anchor.BN(1e9),
+          })
+          .accountsPartial({
+            vault: vaultPda,
+            claim: ownerClaimPda,
+            authority: authority.publicKey,
+            owner: owner.publicKey,
+            recipient: owner.publicKey,
+          })
+          .signers([wallet, owner, authority])
+          .rpc();
+      await updateClaim();
+
+      const recipient = anchor.web3.Keypair.generate();
+      await program.methods
+        .closeClaim({ voucherExpiry: null })
+        .accountsPartial({
+          vault: vaultPda,
+          claim: ownerClaimPda,
+          owner: owner.publicKey,
+          authority: authority.publicKey,
+          recipient: recipient.publicKey,
+          instructionsSysvar: null,
+        })
+        .signers([owner, authority])
+        .rpc();
+      // only the rent of the tombstone is kept
+      const closedInfo = await program.provider.connection.getAccountInfo(
+        ownerClaimPda
+      );
+      expect(closedInfo.data.length).to.eq(20);
+      expect(
+        await program.provider.connection.getBalance(recipient.publicKey)
+      ).to.be.greaterThan(0);
+
+      // the closed claim can not be created again from zero
+      try {
+        await program.methods
+          .createAccount()
+          .accountsPartial({
+            vault: vaultPda,
+            owner: owner.publicKey,
+            claim: ownerClaimPda,
+          })
+          .rpc();
+        expect.fail("should have failed");
+      } catch (e) {
+        expect(e.message).to.be.include("already in use");
+      }
+
+      // anyone can reopen it, with the state it was closed with
+      await program.methods
+        .reopenClaim()
+        .accountsPartial({
+          vault: vaultPda,
+          claim: ownerClaimPda,
+          owner: owner.publicKey,
+        })
+        .rpc();
+      const { nonce, claimed } = await program.account.claim.fetch(
+        ownerClaimPda
+      );
+      expect(nonce).to.eq(1);
+      expect(claimed.toNumber()).to.eq(1e9);
+
+      // so that the old claims can not be replayed
+      try {
+        await updateClaim();
+        expect.fail("should have failed");
+      } catch (e) {
+        expect(e instanceof anchor.AnchorError).to.be.true;
+        const err = e as anchor.AnchorError;
+        expect(err.message).to.be.include(
+          "Error Code: ClaimedAmountUnexpected"
+        );
+      }
+    });
   });
 
+  /** Same as `merkle::leaf_hash`. */
+  function leafHash(
+    index: number,
+    owner: anchor.web3.PublicKey,
+    amount: number
+  ) {
+    const data = Buffer.alloc(4 + 32 + 8);
+    data.writeUInt32LE(index, 0);
+    owner.toBuffer().copy(data, 4);
+    data.writeBigUInt64LE(BigInt(amount), 36);
+    return keccak_256(Buffer.concat([Buffer.from([0]), keccak_256(data)]));
+  }
+
+  /** The parent of two nodes, hashed in sorted order as in `merkle::verify`. */
+  function parentHash(a: Uint8Array, b: Uint8Array) {
+    const [first, second] = Buffer.compare(a, b) <= 0 ? [a, b] : [b, a];
+    return keccak_256(Buffer.concat([Buffer.from([1]), first, second]));
+  }
+
+  /** Waits for the cluster clock to pass `time`. */
+  async function waitForClock(time: number) {
+    const connection = program.provider.connection;
+    while (
+      (await connection.getBlockTime(await connection.getSlot())) <= time
+    ) {
+      await new Promise((resolve) => setTimeout(resolve, 1000));
+    }
+  }
+
   async function initializeVault() {
     if (vaultPda != undefined) {
       return;
@@ -369,14 +1309,20 @@ describe("fee-distributor", () => {
   }
 
   async function createAccount() {
+    await initializeVault();
     const owner = anchor.web3.Keypair.generate();
     const [ownerClaimPda] = anchor.web3.PublicKey.findProgramAddressSync(
-      [Buffer.from("owner"), owner.publicKey.toBuffer()],
+      [
+        Buffer.from("claim"),
+        vaultPda.toBuffer(),
+        owner.publicKey.toBuffer(),
+      ],
       program.programId
     );
     await program.methods
       .createAccount()
       .accountsPartial({
+        vault: vaultPda,
         owner: owner.publicKey,
         claim: ownerClaimPda,
       })